@@ -0,0 +1,28 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use sql_builder::where_clause::WhereClause;
+use sql_builder::SqlBuilder;
+
+fn and_chain(len: usize) -> WhereClause {
+    (1..len).fold(WhereClause::equals("id", 0), |clause, i| {
+        clause.and_equals("id", i as i64)
+    })
+}
+
+fn bench_and_chain(c: &mut Criterion) {
+    let mut group = c.benchmark_group("and_chain");
+    for len in [1_000, 10_000, 100_000] {
+        group.bench_with_input(BenchmarkId::new("chain", len), &len, |b, &len| {
+            b.iter(|| and_chain(len))
+        });
+        let where_clause = and_chain(len);
+        group.bench_with_input(
+            BenchmarkId::new("build", len),
+            &where_clause,
+            |b, clause| b.iter(|| clause.build().unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_and_chain);
+criterion_main!(benches);
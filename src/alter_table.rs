@@ -0,0 +1,862 @@
+use crate::create_index::{CreateIndexQuery, IndexPart};
+use crate::create_table::{
+    named_constraint, ColumnDef, ColumnDefault, ColumnType, CreateTableQuery, ForeignKey,
+    TableConstraint,
+};
+use crate::dialect::Dialect;
+use crate::error::Error;
+use crate::fold::Fold;
+use crate::schema::{Schema, TableDef, Validate};
+use crate::Result;
+use crate::SqlBuilder;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlterOperation {
+    AddColumn(ColumnDef),
+    DropColumn(&'static str),
+    RenameColumn(&'static str, &'static str),
+    /// Redefines type, nullability and default of an existing column at once.
+    ModifyColumn(ColumnDef),
+    ChangeColumnType(&'static str, ColumnType),
+    SetDefault(&'static str, ColumnDefault),
+    DropDefault(&'static str),
+    SetNotNull(&'static str),
+    DropNotNull(&'static str),
+    AddConstraint(&'static str, TableConstraint),
+    DropConstraint(&'static str),
+    RenameTable(&'static str),
+}
+
+impl AlterOperation {
+    /// Whether SQLite's `ALTER TABLE` can apply the operation without rebuilding the table.
+    fn is_native_on_sqlite(&self) -> bool {
+        match self {
+            AlterOperation::AddColumn(column) => {
+                !column.primary_key
+                    && !column.unique
+                    && column.references.is_none()
+                    && matches!(column.default, None | Some(ColumnDefault::Value(_)))
+                    && !(column.not_null && column.default.is_none())
+            }
+            AlterOperation::DropColumn(_)
+            | AlterOperation::RenameColumn(_, _)
+            | AlterOperation::RenameTable(_) => true,
+            _ => false,
+        }
+    }
+
+    /// The `ALTER TABLE` actions for MySQL and PostgreSQL, each becoming its own statement.
+    fn actions(&self, dialect: Dialect) -> Result<Vec<String>> {
+        let unsupported = |feature| Err(Error::UnsupportedByDialect(feature, dialect));
+        Ok(match self {
+            AlterOperation::AddColumn(column) => {
+                let mut actions = vec![format!("ADD COLUMN {}", column.to_sql(dialect)?)];
+                if let Some(foreign_key) = &column.references {
+                    actions.push(format!("ADD {}", foreign_key.build()?));
+                }
+                actions
+            }
+            AlterOperation::DropColumn(column) => vec![format!("DROP COLUMN {}", column)],
+            AlterOperation::RenameColumn(from, to) => {
+                vec![format!("RENAME COLUMN {} TO {}", from, to)]
+            }
+            AlterOperation::ModifyColumn(column) => match dialect {
+                Dialect::MySql => vec![format!("MODIFY COLUMN {}", column.to_sql(dialect)?)],
+                _ => {
+                    let mut actions = vec![format!(
+                        "ALTER COLUMN {} TYPE {}",
+                        column.name,
+                        column.column_type.to_sql(dialect)
+                    )];
+                    actions.push(if column.not_null {
+                        format!("ALTER COLUMN {} SET NOT NULL", column.name)
+                    } else {
+                        format!("ALTER COLUMN {} DROP NOT NULL", column.name)
+                    });
+                    actions.push(match &column.default {
+                        Some(default) => format!(
+                            "ALTER COLUMN {} SET DEFAULT {}",
+                            column.name,
+                            default.to_sql(dialect)
+                        ),
+                        None => format!("ALTER COLUMN {} DROP DEFAULT", column.name),
+                    });
+                    vec![actions.join(", ")]
+                }
+            },
+            AlterOperation::ChangeColumnType(column, column_type) => {
+                match dialect {
+                    Dialect::MySql => return unsupported(
+                        "changing a column type without its full definition, use 'modify_column'",
+                    ),
+                    _ => vec![format!(
+                        "ALTER COLUMN {} TYPE {}",
+                        column,
+                        column_type.to_sql(dialect)
+                    )],
+                }
+            }
+            AlterOperation::SetDefault(column, default) => vec![format!(
+                "ALTER COLUMN {} SET DEFAULT {}",
+                column,
+                default.to_sql(dialect)
+            )],
+            AlterOperation::DropDefault(column) => {
+                vec![format!("ALTER COLUMN {} DROP DEFAULT", column)]
+            }
+            AlterOperation::SetNotNull(column) => match dialect {
+                Dialect::MySql => return unsupported(
+                    "changing nullability without the full column definition, use 'modify_column'",
+                ),
+                _ => vec![format!("ALTER COLUMN {} SET NOT NULL", column)],
+            },
+            AlterOperation::DropNotNull(column) => match dialect {
+                Dialect::MySql => return unsupported(
+                    "changing nullability without the full column definition, use 'modify_column'",
+                ),
+                _ => vec![format!("ALTER COLUMN {} DROP NOT NULL", column)],
+            },
+            AlterOperation::AddConstraint(name, constraint) => {
+                vec![format!("ADD {}", named_constraint(Some(name), constraint)?)]
+            }
+            AlterOperation::DropConstraint(name) => vec![format!("DROP CONSTRAINT {}", name)],
+            AlterOperation::RenameTable(table) => vec![format!("RENAME TO {}", table)],
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AlterTableQuery {
+    pub table: &'static str,
+    pub operations: Vec<AlterOperation>,
+    pub(crate) current: Option<CreateTableQuery>,
+    pub(crate) indexes: Vec<CreateIndexQuery>,
+    pub(crate) triggers: Vec<String>,
+    dialect: Dialect,
+}
+
+impl AlterTableQuery {
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            table,
+            operations: Vec::new(),
+            current: None,
+            indexes: Vec::new(),
+            triggers: Vec::new(),
+            dialect: Dialect::default(),
+        }
+    }
+
+    pub fn add_column(mut self, column: ColumnDef) -> Self {
+        self.operations.push(AlterOperation::AddColumn(column));
+        self
+    }
+
+    pub fn drop_column(mut self, column: &'static str) -> Self {
+        self.operations.push(AlterOperation::DropColumn(column));
+        self
+    }
+
+    pub fn rename_column(mut self, from: &'static str, to: &'static str) -> Self {
+        self.operations.push(AlterOperation::RenameColumn(from, to));
+        self
+    }
+
+    /// Redefines type, nullability and default of `column.name`,
+    /// MySQL needs this instead of the single property changes.
+    pub fn modify_column(mut self, column: ColumnDef) -> Self {
+        self.operations.push(AlterOperation::ModifyColumn(column));
+        self
+    }
+
+    pub fn change_column_type(mut self, column: &'static str, column_type: ColumnType) -> Self {
+        self.operations
+            .push(AlterOperation::ChangeColumnType(column, column_type));
+        self
+    }
+
+    pub fn set_default(mut self, column: &'static str, default: ColumnDefault) -> Self {
+        self.operations
+            .push(AlterOperation::SetDefault(column, default));
+        self
+    }
+
+    pub fn drop_default(mut self, column: &'static str) -> Self {
+        self.operations.push(AlterOperation::DropDefault(column));
+        self
+    }
+
+    pub fn set_not_null(mut self, column: &'static str) -> Self {
+        self.operations.push(AlterOperation::SetNotNull(column));
+        self
+    }
+
+    pub fn drop_not_null(mut self, column: &'static str) -> Self {
+        self.operations.push(AlterOperation::DropNotNull(column));
+        self
+    }
+
+    pub fn add_constraint(mut self, name: &'static str, constraint: TableConstraint) -> Self {
+        self.operations
+            .push(AlterOperation::AddConstraint(name, constraint));
+        self
+    }
+
+    pub fn drop_constraint(mut self, name: &'static str) -> Self {
+        self.operations.push(AlterOperation::DropConstraint(name));
+        self
+    }
+
+    pub fn rename_to(mut self, table: &'static str) -> Self {
+        self.operations.push(AlterOperation::RenameTable(table));
+        self
+    }
+
+    /// The current definition of the table, needed when SQLite has to rebuild it.
+    pub fn rebuild_from(mut self, current: CreateTableQuery) -> Self {
+        self.current = Some(current);
+        self
+    }
+
+    /// An index of the table, SQLite drops it with the original table so a rebuild
+    /// recreates it. Renamed columns are renamed in it, it is left out when it
+    /// indexes a dropped column.
+    pub fn rebuild_index(mut self, index: CreateIndexQuery) -> Self {
+        self.indexes.push(index);
+        self
+    }
+
+    /// The `CREATE TRIGGER` statement of a trigger on the table, recreated as is
+    /// after a rebuild.
+    pub fn rebuild_trigger(mut self, trigger: String) -> Self {
+        self.triggers.push(trigger);
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Whether the statements are a SQLite table rebuild script rather than `ALTER TABLE`s:
+    /// create the altered table under a temporary name, copy the rows over,
+    /// drop the original table and rename the new one.
+    pub fn requires_rebuild(&self) -> bool {
+        self.dialect == Dialect::Sqlite
+            && !self
+                .operations
+                .iter()
+                .all(AlterOperation::is_native_on_sqlite)
+    }
+
+    pub fn build_statements(&self) -> Result<Vec<String>> {
+        if self.operations.is_empty() {
+            return Err(Error::NoAlterOperations);
+        }
+        if self.requires_rebuild() {
+            return self.rebuild_statements();
+        }
+        let mut table = self.table;
+        let mut statements = Vec::new();
+        for operation in &self.operations {
+            let actions = match (operation, self.dialect) {
+                (AlterOperation::AddColumn(column), Dialect::Sqlite) => {
+                    vec![format!("ADD COLUMN {}", column.to_sql(self.dialect)?)]
+                }
+                (operation, dialect) => operation.actions(dialect)?,
+            };
+            for action in actions {
+                statements.push(format!("ALTER TABLE {} {}", table, action));
+            }
+            if let AlterOperation::RenameTable(to) = operation {
+                table = to;
+            }
+        }
+        Ok(statements)
+    }
+
+    fn rebuild_statements(&self) -> Result<Vec<String>> {
+        let mut definition = match &self.current {
+            Some(current) => current.clone(),
+            None => return Err(Error::SqliteRebuildRequired(self.table)),
+        };
+        // (name in the original table, name in the rebuilt table) of the copied columns
+        let mut copied = definition
+            .columns
+            .iter()
+            .map(|column| (column.name, column.name))
+            .collect::<Vec<(&str, &str)>>();
+        let mut indexes = self.indexes.clone();
+        let mut table = self.table;
+        for operation in &self.operations {
+            match operation {
+                AlterOperation::AddColumn(column) => definition.columns.push(column.clone()),
+                // like PostgreSQL, constraints and indexes using the column go with it
+                AlterOperation::DropColumn(name) => {
+                    self.column(&mut definition, name)?;
+                    definition.columns.retain(|column| column.name != *name);
+                    copied.retain(|(_, new)| new != name);
+                    for column in definition.columns.iter_mut() {
+                        if column
+                            .references
+                            .as_ref()
+                            .is_some_and(|foreign_key| self.uses(foreign_key, name))
+                        {
+                            column.references = None;
+                        }
+                        if column
+                            .check
+                            .as_ref()
+                            .is_some_and(|check| mentions(check, name))
+                        {
+                            column.check = None;
+                        }
+                    }
+                    definition
+                        .constraints
+                        .retain(|(_, constraint)| match constraint {
+                            TableConstraint::PrimaryKey(columns)
+                            | TableConstraint::Unique(columns) => !columns.contains(name),
+                            TableConstraint::ForeignKey(foreign_key) => {
+                                !self.uses(foreign_key, name)
+                            }
+                            TableConstraint::Check(check) => !mentions(check, name),
+                        });
+                    indexes.retain(|index| {
+                        !index
+                            .parts
+                            .iter()
+                            .any(|(part, _)| *part == IndexPart::Column(name))
+                    });
+                }
+                AlterOperation::RenameColumn(from, to) => {
+                    self.column(&mut definition, from)?.name = to;
+                    for (_, new) in copied.iter_mut().filter(|(_, new)| new == from) {
+                        *new = to;
+                    }
+                    for column in definition.columns.iter_mut() {
+                        if let Some(foreign_key) = &mut column.references {
+                            self.rename_in_foreign_key(foreign_key, from, to);
+                        }
+                        if let Some(check) = &mut column.check {
+                            *check = rename(check, from, to);
+                        }
+                    }
+                    for (_, constraint) in definition.constraints.iter_mut() {
+                        match constraint {
+                            TableConstraint::PrimaryKey(columns)
+                            | TableConstraint::Unique(columns) => rename_all(columns, from, to),
+                            TableConstraint::ForeignKey(foreign_key) => {
+                                self.rename_in_foreign_key(foreign_key, from, to)
+                            }
+                            TableConstraint::Check(check) => *check = rename(check, from, to),
+                        }
+                    }
+                    for index in indexes.iter_mut() {
+                        for (part, _) in index.parts.iter_mut() {
+                            if *part == IndexPart::Column(from) {
+                                *part = IndexPart::Column(to);
+                            }
+                        }
+                        index.where_clause = index.where_clause.take().map(|where_clause| {
+                            RenameColumn { from, to }.fold_where_clause(where_clause)
+                        });
+                    }
+                }
+                AlterOperation::ModifyColumn(column) => {
+                    let current = self.column(&mut definition, column.name)?;
+                    current.column_type = column.column_type;
+                    current.not_null = column.not_null;
+                    current.default = column.default.clone();
+                }
+                AlterOperation::ChangeColumnType(name, column_type) => {
+                    self.column(&mut definition, name)?.column_type = *column_type;
+                }
+                AlterOperation::SetDefault(name, default) => {
+                    self.column(&mut definition, name)?.default = Some(default.clone());
+                }
+                AlterOperation::DropDefault(name) => {
+                    self.column(&mut definition, name)?.default = None;
+                }
+                AlterOperation::SetNotNull(name) => {
+                    self.column(&mut definition, name)?.not_null = true;
+                }
+                AlterOperation::DropNotNull(name) => {
+                    self.column(&mut definition, name)?.not_null = false;
+                }
+                AlterOperation::AddConstraint(name, constraint) => {
+                    definition
+                        .constraints
+                        .push((Some(name), constraint.clone()));
+                }
+                AlterOperation::DropConstraint(name) => {
+                    let count = definition.constraints.len();
+                    definition
+                        .constraints
+                        .retain(|(constraint, _)| *constraint != Some(name));
+                    if definition.constraints.len() == count {
+                        return Err(Error::UnknownConstraint(self.table, name));
+                    }
+                }
+                AlterOperation::RenameTable(to) => table = to,
+            }
+        }
+
+        let rebuilt = format!("{}_rebuild", self.table);
+        let mut statements = vec![
+            "PRAGMA foreign_keys = OFF".to_string(),
+            definition.build_as(&rebuilt, Dialect::Sqlite)?,
+        ];
+        if !copied.is_empty() {
+            statements.push(format!(
+                "INSERT INTO {} ({}) SELECT {} FROM {}",
+                rebuilt,
+                copied
+                    .iter()
+                    .map(|(_, new)| *new)
+                    .collect::<Vec<&str>>()
+                    .join(", "),
+                copied
+                    .iter()
+                    .map(|(old, _)| *old)
+                    .collect::<Vec<&str>>()
+                    .join(", "),
+                self.table
+            ));
+        }
+        statements.push(format!("DROP TABLE {}", self.table));
+        statements.push(format!("ALTER TABLE {} RENAME TO {}", rebuilt, table));
+        for mut index in indexes {
+            index.table = table;
+            statements.push(index.dialect(Dialect::Sqlite).build()?);
+        }
+        statements.extend(self.triggers.iter().cloned());
+        statements.push("PRAGMA foreign_keys = ON".to_string());
+        Ok(statements)
+    }
+
+    /// Whether the foreign key uses `column` of the altered table, as a referencing
+    /// column or as a referenced column of a self reference.
+    fn uses(&self, foreign_key: &ForeignKey, column: &str) -> bool {
+        foreign_key.columns.contains(&column)
+            || (foreign_key.table == self.table && foreign_key.references.contains(&column))
+    }
+
+    fn rename_in_foreign_key(
+        &self,
+        foreign_key: &mut ForeignKey,
+        from: &'static str,
+        to: &'static str,
+    ) {
+        rename_all(&mut foreign_key.columns, from, to);
+        if foreign_key.table == self.table {
+            rename_all(&mut foreign_key.references, from, to);
+        }
+    }
+
+    fn column<'a>(
+        &self,
+        definition: &'a mut CreateTableQuery,
+        name: &'static str,
+    ) -> Result<&'a mut ColumnDef> {
+        definition
+            .columns
+            .iter_mut()
+            .find(|column| column.name == name)
+            .ok_or(Error::UnknownColumn(self.table, name))
+    }
+}
+
+fn rename_all(columns: &mut [&'static str], from: &'static str, to: &'static str) {
+    for column in columns.iter_mut().filter(|column| **column == from) {
+        *column = to;
+    }
+}
+
+/// Whether the sql expression mentions the identifier, outside of string literals.
+fn mentions(expr: &str, identifier: &str) -> bool {
+    let mut found = false;
+    map_identifiers(expr, |word| {
+        found |= word == identifier;
+        word.to_string()
+    });
+    found
+}
+
+/// Renames the identifier in a sql expression, outside of string literals.
+fn rename(expr: &str, from: &str, to: &str) -> String {
+    map_identifiers(expr, |word| {
+        if word == from { to } else { word }.to_string()
+    })
+}
+
+fn map_identifiers<F: FnMut(&str) -> String>(expr: &str, mut map: F) -> String {
+    let mut sql = String::new();
+    let mut chars = expr.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c == '\'' {
+            // '' inside a literal reads as the end of one and the start of the next
+            sql.push(c);
+            for (_, c) in chars.by_ref() {
+                sql.push(c);
+                if c == '\'' {
+                    break;
+                }
+            }
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start + c.len_utf8();
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            sql.push_str(&map(&expr[start..end]));
+        } else {
+            sql.push(c);
+        }
+    }
+    sql
+}
+
+/// Renames a column in the where clause of a partial index.
+struct RenameColumn {
+    from: &'static str,
+    to: &'static str,
+}
+
+impl Fold for RenameColumn {
+    fn fold_column(&mut self, column: &'static str) -> &'static str {
+        if column == self.from {
+            self.to
+        } else {
+            column
+        }
+    }
+}
+
+impl SqlBuilder for AlterTableQuery {
+    fn build(&self) -> Result<String> {
+        Ok(self.build_statements()?.join("; "))
+    }
+}
+
+/// Applies the operations to the table's definition in the schema, checking each
+/// against the definition as the previous operations left it.
+impl Validate for AlterTableQuery {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        let mut table: TableDef = schema.require_table(self.table)?.clone();
+        for operation in &self.operations {
+            match operation {
+                AlterOperation::AddColumn(column) => {
+                    if table.get_column(column.name).is_some() {
+                        return Err(Error::ColumnExists(table.name, column.name));
+                    }
+                    table.columns.push(column.clone());
+                    if let Some(foreign_key) = &column.references {
+                        let constraint = TableConstraint::ForeignKey(foreign_key.clone());
+                        schema.validate_constraint(&table, &constraint)?;
+                    }
+                }
+                AlterOperation::DropColumn(name) => {
+                    table.require_column(name)?;
+                    table.columns.retain(|column| column.name != *name);
+                }
+                AlterOperation::RenameColumn(from, to) => {
+                    table.require_column(from)?;
+                    if table.get_column(to).is_some() {
+                        return Err(Error::ColumnExists(table.name, to));
+                    }
+                    for column in &mut table.columns {
+                        if column.name == *from {
+                            column.name = to;
+                        }
+                    }
+                }
+                AlterOperation::ModifyColumn(ColumnDef { name, .. })
+                | AlterOperation::ChangeColumnType(name, _)
+                | AlterOperation::SetDefault(name, _)
+                | AlterOperation::DropDefault(name)
+                | AlterOperation::SetNotNull(name)
+                | AlterOperation::DropNotNull(name) => {
+                    table.require_column(name)?;
+                }
+                AlterOperation::AddConstraint(name, constraint) => {
+                    schema.validate_constraint(&table, constraint)?;
+                    table.constraints.push((Some(name), constraint.clone()));
+                }
+                AlterOperation::DropConstraint(name) => {
+                    // unnamed constraints get names from the database, unknown names may exist
+                    table
+                        .constraints
+                        .retain(|(constraint, _)| *constraint != Some(name));
+                }
+                AlterOperation::RenameTable(to) => {
+                    if schema.get_table(to).is_some() {
+                        return Err(Error::TableExists(to));
+                    }
+                    table.name = to;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::where_clause::WhereClause;
+
+    fn alter_users() -> AlterTableQuery {
+        AlterTableQuery::new("users")
+            .add_column(ColumnDef::new("email", ColumnType::Varchar(255)))
+            .drop_column("nickname")
+            .rename_column("name", "full_name")
+            .change_column_type("age", ColumnType::BigInteger)
+            .set_default("active", ColumnDefault::Value("TRUE".to_string()))
+            .drop_default("age")
+            .set_not_null("email")
+            .drop_not_null("age")
+            .add_constraint("email_unique", TableConstraint::Unique(vec!["email"]))
+            .drop_constraint("adult")
+            .rename_to("accounts")
+    }
+
+    #[test]
+    fn test_alter_table_postgres() {
+        assert_eq!(
+            alter_users()
+                .dialect(Dialect::Postgres)
+                .build_statements()
+                .unwrap(),
+            vec![
+                "ALTER TABLE users ADD COLUMN email VARCHAR(255)",
+                "ALTER TABLE users DROP COLUMN nickname",
+                "ALTER TABLE users RENAME COLUMN name TO full_name",
+                "ALTER TABLE users ALTER COLUMN age TYPE BIGINT",
+                "ALTER TABLE users ALTER COLUMN active SET DEFAULT TRUE",
+                "ALTER TABLE users ALTER COLUMN age DROP DEFAULT",
+                "ALTER TABLE users ALTER COLUMN email SET NOT NULL",
+                "ALTER TABLE users ALTER COLUMN age DROP NOT NULL",
+                "ALTER TABLE users ADD CONSTRAINT email_unique UNIQUE (email)",
+                "ALTER TABLE users DROP CONSTRAINT adult",
+                "ALTER TABLE users RENAME TO accounts",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alter_table_mysql() {
+        assert_eq!(
+            alter_users().build().unwrap_err(),
+            Error::UnsupportedByDialect(
+                "changing a column type without its full definition, use 'modify_column'",
+                Dialect::MySql
+            )
+        );
+        let alter = AlterTableQuery::new("users")
+            .add_column(
+                ColumnDef::new("team_id", ColumnType::BigInteger).references("teams", "id", None),
+            )
+            .modify_column(
+                ColumnDef::new("age", ColumnType::Integer)
+                    .not_null()
+                    .default(&0),
+            )
+            .rename_to("accounts")
+            .drop_default("age");
+        assert_eq!(
+            alter.build().unwrap(),
+            "ALTER TABLE users ADD COLUMN team_id BIGINT; ALTER TABLE users ADD FOREIGN KEY (team_id) REFERENCES teams (id); ALTER TABLE users MODIFY COLUMN age INT NOT NULL DEFAULT 0; ALTER TABLE users RENAME TO accounts; ALTER TABLE accounts ALTER COLUMN age DROP DEFAULT"
+        );
+    }
+
+    #[test]
+    fn test_alter_table_sqlite_native() {
+        let alter = AlterTableQuery::new("users")
+            .add_column(ColumnDef::new("email", ColumnType::Text))
+            .rename_column("name", "full_name")
+            .drop_column("nickname")
+            .dialect(Dialect::Sqlite);
+        assert!(!alter.requires_rebuild());
+        assert_eq!(
+            alter.build().unwrap(),
+            "ALTER TABLE users ADD COLUMN email TEXT; ALTER TABLE users RENAME COLUMN name TO full_name; ALTER TABLE users DROP COLUMN nickname"
+        );
+    }
+
+    #[test]
+    fn test_alter_table_sqlite_rebuild() {
+        let current = CreateTableQuery::new("users")
+            .column(ColumnDef::new("id", ColumnType::Integer).primary_key())
+            .column(ColumnDef::new("name", ColumnType::Text))
+            .column(ColumnDef::new("nickname", ColumnType::Text))
+            .column(ColumnDef::new("age", ColumnType::Integer))
+            .unique(&["name"])
+            .constraint("adult", TableConstraint::Check("age >= 18".to_string()));
+        let alter = AlterTableQuery::new("users")
+            .rename_column("name", "full_name")
+            .drop_column("nickname")
+            .set_not_null("age")
+            .drop_constraint("adult")
+            .add_column(ColumnDef::new("created_at", ColumnType::Timestamp).default_now())
+            .dialect(Dialect::Sqlite);
+        assert!(alter.requires_rebuild());
+        assert_eq!(
+            alter.build().unwrap_err(),
+            Error::SqliteRebuildRequired("users")
+        );
+        assert_eq!(
+            alter.rebuild_from(current).build_statements().unwrap(),
+            vec![
+                "PRAGMA foreign_keys = OFF",
+                "CREATE TABLE users_rebuild (id INTEGER PRIMARY KEY, full_name TEXT, age INTEGER NOT NULL, created_at TEXT DEFAULT CURRENT_TIMESTAMP, UNIQUE (full_name))",
+                "INSERT INTO users_rebuild (id, full_name, age) SELECT id, name, age FROM users",
+                "DROP TABLE users",
+                "ALTER TABLE users_rebuild RENAME TO users",
+                "PRAGMA foreign_keys = ON",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alter_table_sqlite_rebuild_rename_column() {
+        let current = CreateTableQuery::new("employees")
+            .column(ColumnDef::new("id", ColumnType::Integer).primary_key())
+            .column(
+                ColumnDef::new("team", ColumnType::Integer)
+                    .references("teams", "id", None)
+                    .check("team > 0".to_string()),
+            )
+            .column(ColumnDef::new("manager", ColumnType::Integer).references(
+                "employees",
+                "id",
+                None,
+            ));
+        let alter = AlterTableQuery::new("employees")
+            .rename_column("team", "team_id")
+            .rename_column("id", "employee_id")
+            .set_not_null("team_id")
+            .dialect(Dialect::Sqlite)
+            .rebuild_from(current);
+        assert_eq!(
+            alter.build_statements().unwrap()[1],
+            "CREATE TABLE employees_rebuild (employee_id INTEGER PRIMARY KEY, team_id INTEGER NOT NULL CHECK (team_id > 0), manager INTEGER, FOREIGN KEY (team_id) REFERENCES teams (id), FOREIGN KEY (manager) REFERENCES employees (employee_id))"
+        );
+    }
+
+    #[test]
+    fn test_alter_table_sqlite_rebuild_drop_column() {
+        let current = CreateTableQuery::new("users")
+            .column(ColumnDef::new("id", ColumnType::Integer).primary_key())
+            .column(ColumnDef::new("team_id", ColumnType::Integer).references("teams", "id", None))
+            .column(ColumnDef::new("email", ColumnType::Text))
+            .column(ColumnDef::new("nickname", ColumnType::Text).check("email != 'x'".to_string()))
+            .unique(&["team_id", "email"])
+            .check("length(email) > 3".to_string())
+            .constraint("nickname_unique", TableConstraint::Unique(vec!["nickname"]));
+        let alter = AlterTableQuery::new("users")
+            .drop_column("email")
+            .drop_column("team_id")
+            .set_not_null("nickname")
+            .dialect(Dialect::Sqlite)
+            .rebuild_from(current);
+        assert_eq!(
+            alter.build_statements().unwrap()[1..3],
+            [
+                "CREATE TABLE users_rebuild (id INTEGER PRIMARY KEY, nickname TEXT NOT NULL, CONSTRAINT nickname_unique UNIQUE (nickname))",
+                "INSERT INTO users_rebuild (id, nickname) SELECT id, nickname FROM users",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alter_table_sqlite_rebuild_indexes() {
+        let current = CreateTableQuery::new("users")
+            .column(ColumnDef::new("id", ColumnType::Integer).primary_key())
+            .column(ColumnDef::new("name", ColumnType::Text))
+            .column(ColumnDef::new("email", ColumnType::Text))
+            .column(ColumnDef::new("deleted", ColumnType::Boolean));
+        let trigger = "CREATE TRIGGER users_audit AFTER DELETE ON users BEGIN INSERT INTO log (user_id) VALUES (old.id); END";
+        let alter = AlterTableQuery::new("users")
+            .rename_column("deleted", "archived")
+            .drop_column("email")
+            .set_not_null("name")
+            .rebuild_index(
+                CreateIndexQuery::new("users_name", "users")
+                    .column("name")
+                    .where_clause(WhereClause::equals("deleted", false)),
+            )
+            .rebuild_index(
+                CreateIndexQuery::new("users_email", "users")
+                    .unique()
+                    .column("email"),
+            )
+            .rebuild_trigger(trigger.to_string())
+            .dialect(Dialect::Sqlite)
+            .rebuild_from(current);
+        let statements = alter.build_statements().unwrap();
+        assert_eq!(
+            statements[4..],
+            [
+                "ALTER TABLE users_rebuild RENAME TO users",
+                "CREATE INDEX users_name ON users (name) WHERE archived = FALSE",
+                trigger,
+                "PRAGMA foreign_keys = ON",
+            ]
+        );
+
+        let connection = rusqlite::Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE log (user_id INTEGER);
+                CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, email TEXT, deleted BOOLEAN);
+                CREATE INDEX users_name ON users (name) WHERE deleted = FALSE;
+                CREATE UNIQUE INDEX users_email ON users (email);",
+            )
+            .unwrap();
+        connection.execute_batch(trigger).unwrap();
+        connection
+            .execute_batch("INSERT INTO users VALUES (1, 'Jack', 'jack@example.com', FALSE)")
+            .unwrap();
+        connection
+            .execute_batch(&(statements.join(";\n") + ";"))
+            .unwrap();
+        let objects = connection
+            .prepare("SELECT name FROM sqlite_master WHERE tbl_name = 'users' AND type != 'table' ORDER BY name")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<String>>>()
+            .unwrap();
+        assert_eq!(objects, vec!["users_audit", "users_name"]);
+        connection.execute_batch("DELETE FROM users").unwrap();
+        let logged: i64 = connection
+            .query_row("SELECT COUNT(*) FROM log", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(logged, 1);
+    }
+
+    #[test]
+    fn test_alter_table_errors() {
+        assert_eq!(
+            AlterTableQuery::new("users").build().unwrap_err(),
+            Error::NoAlterOperations
+        );
+        let current =
+            CreateTableQuery::new("users").column(ColumnDef::new("id", ColumnType::Integer));
+        let alter = AlterTableQuery::new("users")
+            .dialect(Dialect::Sqlite)
+            .rebuild_from(current);
+        assert_eq!(
+            alter.clone().set_not_null("name").build().unwrap_err(),
+            Error::UnknownColumn("users", "name")
+        );
+        assert_eq!(
+            alter.drop_constraint("adult").build().unwrap_err(),
+            Error::UnknownConstraint("users", "adult")
+        );
+    }
+}
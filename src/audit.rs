@@ -0,0 +1,417 @@
+use crate::delete::DeleteQuery;
+use crate::dialect::Dialect;
+use crate::insert::InsertQuery;
+use crate::parser::Statement;
+use crate::repr::ToSqlRepr;
+use crate::tenant::table_ref;
+use crate::update::{Assignment, UpdateQuery};
+use crate::Result;
+use crate::SqlBuilder;
+use chrono::{DateTime, Local};
+
+/// Stamps timestamp and actor columns into inserts and updates of audited tables.
+///
+/// Inserts get `created_at` and `updated_at`, and `created_by` and `updated_by` when
+/// an actor is set. Updates and soft deletes get `updated_at` and `updated_by`.
+/// Columns the statement already sets are left alone, qualified or not. Timestamps
+/// are the dialect's `NOW()` expression unless the audit uses another clock.
+#[derive(Debug, Clone)]
+pub struct Audit {
+    created_at: &'static str,
+    updated_at: &'static str,
+    created_by: &'static str,
+    updated_by: &'static str,
+    actor: Option<String>,
+    clock: Clock,
+    dialect: Dialect,
+    tables: Vec<&'static str>,
+}
+
+/// Where an `Audit` takes its timestamps from.
+#[derive(Debug, Clone)]
+enum Clock {
+    Database,
+    Fixed(String),
+    /// Read each time a statement is stamped.
+    Local,
+}
+
+impl Default for Audit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Audit {
+    pub fn new() -> Self {
+        Self {
+            created_at: "created_at",
+            updated_at: "updated_at",
+            created_by: "created_by",
+            updated_by: "updated_by",
+            actor: None,
+            clock: Clock::Database,
+            dialect: Dialect::default(),
+            tables: Vec::new(),
+        }
+    }
+
+    pub fn table(mut self, table: &'static str) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    pub fn tables(mut self, tables: &[&'static str]) -> Self {
+        self.tables.extend(tables);
+        self
+    }
+
+    pub fn created_at(mut self, column: &'static str) -> Self {
+        self.created_at = column;
+        self
+    }
+
+    pub fn updated_at(mut self, column: &'static str) -> Self {
+        self.updated_at = column;
+        self
+    }
+
+    pub fn created_by(mut self, column: &'static str) -> Self {
+        self.created_by = column;
+        self
+    }
+
+    pub fn updated_by(mut self, column: &'static str) -> Self {
+        self.updated_by = column;
+        self
+    }
+
+    /// The user or process making the changes, stamped into the `*_by` columns.
+    pub fn actor<T: ToSqlRepr>(mut self, actor: T) -> Self {
+        self.actor = Some(actor.to_sql_repr());
+        self
+    }
+
+    /// Stamps `time` instead of the database's current time.
+    pub fn at(mut self, time: DateTime<Local>) -> Self {
+        self.clock = Clock::Fixed(time.to_sql_repr());
+        self
+    }
+
+    /// Stamps the local time of stamping instead of the database's current time.
+    pub fn local_time(mut self) -> Self {
+        self.clock = Clock::Local;
+        self
+    }
+
+    /// The dialect of the `NOW()` expression in inserts, updates and deletes use their own.
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn is_audited(&self, table: &str) -> bool {
+        let (name, _) = table_ref(table);
+        self.tables
+            .iter()
+            .any(|audited| audited.eq_ignore_ascii_case(name))
+    }
+
+    pub fn stamp<Q: Stamp>(&self, query: Q) -> Q {
+        query.stamp(self)
+    }
+
+    /// Wraps the query so it is stamped when built.
+    pub fn stamped<Q: Stamp>(&self, query: Q) -> Stamped<'_, Q> {
+        Stamped { audit: self, query }
+    }
+
+    /// The time to stamp, `None` for the database's current time.
+    fn timestamp(&self) -> Option<String> {
+        match &self.clock {
+            Clock::Database => None,
+            Clock::Fixed(time) => Some(time.clone()),
+            Clock::Local => Some(Local::now().to_sql_repr()),
+        }
+    }
+
+    /// The columns an update of an audited table sets.
+    fn update_stamps(&self) -> Vec<(&'static str, Assignment)> {
+        let timestamp = self.timestamp().map_or(Assignment::Now, Assignment::Value);
+        let mut stamps = vec![(self.updated_at, timestamp)];
+        if let Some(actor) = &self.actor {
+            stamps.push((self.updated_by, Assignment::Value(actor.clone())));
+        }
+        stamps
+    }
+}
+
+/// Whether `columns` has `column`, qualified by a table name or not.
+fn sets(columns: &[&'static str], column: &str) -> bool {
+    columns
+        .iter()
+        .any(|set| set.rsplit('.').next() == Some(column))
+}
+
+/// Statements an `Audit` can be applied to.
+pub trait Stamp: Sized {
+    fn stamp(self, audit: &Audit) -> Self;
+}
+
+impl Stamp for InsertQuery {
+    fn stamp(mut self, audit: &Audit) -> Self {
+        // without columns the insert fails to build anyway
+        if !audit.is_audited(self.table) || self.columns.is_empty() {
+            return self;
+        }
+        let timestamp = audit
+            .timestamp()
+            .unwrap_or_else(|| audit.dialect.now().to_string());
+        let mut stamps = vec![
+            (audit.created_at, timestamp.clone()),
+            (audit.updated_at, timestamp),
+        ];
+        if let Some(actor) = &audit.actor {
+            stamps.push((audit.created_by, actor.clone()));
+            stamps.push((audit.updated_by, actor.clone()));
+        }
+        for (column, value) in stamps {
+            if !sets(&self.columns, column) {
+                self.columns.push(column);
+                for record in &mut self.values {
+                    record.push(value.clone());
+                }
+            }
+        }
+        self
+    }
+}
+
+impl Stamp for UpdateQuery {
+    fn stamp(mut self, audit: &Audit) -> Self {
+        // stamping must not turn an update without fields into a valid one
+        if !audit.is_audited(self.table) || self.fields.is_empty() && self.assignments.is_empty() {
+            return self;
+        }
+        for (column, assignment) in audit.update_stamps() {
+            let columns = self
+                .assignments()
+                .into_iter()
+                .map(|(field, _)| field)
+                .collect::<Vec<_>>();
+            if !sets(&columns, column) {
+                self.assign(column, assignment);
+            }
+        }
+        self
+    }
+}
+
+/// Stamps the update a soft delete is built as, hard deletes are left as they are.
+impl Stamp for DeleteQuery {
+    fn stamp(mut self, audit: &Audit) -> Self {
+        if !audit.is_audited(self.table) {
+            return self;
+        }
+        for (column, assignment) in audit.update_stamps() {
+            let columns = self
+                .soft_delete_fields
+                .iter()
+                .map(|(field, _)| *field)
+                .collect::<Vec<_>>();
+            if !sets(&columns, column) {
+                self.soft_delete_fields.push((column, assignment));
+            }
+        }
+        self
+    }
+}
+
+impl Stamp for Statement {
+    fn stamp(self, audit: &Audit) -> Self {
+        match self {
+            Statement::Insert(insert) => Statement::Insert(insert.stamp(audit)),
+            Statement::Update(update) => Statement::Update(update.stamp(audit)),
+            Statement::Delete(delete) => Statement::Delete(delete.stamp(audit)),
+            statement => statement,
+        }
+    }
+}
+
+/// A query stamped when built, see `Audit::stamped`.
+#[derive(Debug, Clone)]
+pub struct Stamped<'a, Q> {
+    audit: &'a Audit,
+    query: Q,
+}
+
+impl<Q: Stamp + SqlBuilder + Clone> SqlBuilder for Stamped<'_, Q> {
+    fn build(&self) -> Result<String> {
+        self.query.clone().stamp(self.audit).build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::where_clause::WhereClause;
+    use chrono::TimeZone;
+
+    fn audit() -> Audit {
+        Audit::new().tables(&["users", "orders"]).actor(42)
+    }
+
+    #[test]
+    fn test_stamp_insert() {
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_columns(["name"].into_iter())
+            .add_record_raw(&["'Jack'".to_string()])
+            .unwrap();
+        assert_eq!(
+            audit().stamp(insert.clone()).build(),
+            Ok("INSERT INTO users (name, created_at, updated_at, created_by, updated_by) VALUES ('Jack', NOW(), NOW(), 42, 42)".to_string())
+        );
+        assert_eq!(
+            Audit::new()
+                .table("users")
+                .dialect(Dialect::Sqlite)
+                .stamp(insert)
+                .build(),
+            Ok("INSERT INTO users (name, created_at, updated_at) VALUES ('Jack', CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)".to_string())
+        );
+        let mut insert = InsertQuery::new("countries");
+        insert
+            .add_columns(["name"].into_iter())
+            .add_record_raw(&["'Peru'".to_string()])
+            .unwrap();
+        assert_eq!(
+            audit().stamp(insert).build(),
+            Ok("INSERT INTO countries (name) VALUES ('Peru')".to_string())
+        );
+    }
+
+    #[test]
+    fn test_stamp_insert_explicit() {
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_columns(["name", "created_at"].into_iter())
+            .add_record_raw(&["'Jack'".to_string(), "'2020-01-01 00:00:00'".to_string()])
+            .unwrap();
+        let time = Local.with_ymd_and_hms(2024, 5, 6, 7, 8, 9).unwrap();
+        assert_eq!(
+            audit().at(time).stamp(insert).build(),
+            Ok("INSERT INTO users (name, created_at, updated_at, created_by, updated_by) VALUES ('Jack', '2020-01-01 00:00:00', '2024-05-06 07:08:09', 42, 42)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_stamp_update() {
+        let mut update = UpdateQuery::new("users");
+        update
+            .set_field("name", &"Jack")
+            .add_where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            audit().stamped(update.clone()).build(),
+            Ok(
+                "UPDATE users SET name = 'Jack', updated_by = 42, updated_at = NOW() WHERE id = 1"
+                    .to_string()
+            )
+        );
+        update.set_field("updated_by", &7).dialect(Dialect::Sqlite);
+        assert_eq!(
+            audit().stamp(update).build(),
+            Ok("UPDATE users SET name = 'Jack', updated_by = 7, updated_at = CURRENT_TIMESTAMP WHERE id = 1".to_string())
+        );
+        let update = UpdateQuery::new("users");
+        assert_eq!(audit().stamp(update).build(), Err(Error::NoUpdateFields));
+    }
+
+    #[test]
+    fn test_stamp_table_references() {
+        assert!(audit().is_audited("public.users"));
+        assert!(audit().is_audited("users u"));
+        assert!(audit().is_audited("`users`"));
+        assert!(!audit().is_audited("users_archive u"));
+        let mut update = UpdateQuery::new("public.users");
+        update
+            .set_field("name", &"Jack")
+            .add_where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            audit().stamp(update).build(),
+            Ok(
+                "UPDATE public.users SET name = 'Jack', updated_by = 42, updated_at = NOW() WHERE id = 1"
+                    .to_string()
+            )
+        );
+        let delete = DeleteQuery::new("users u")
+            .soft_delete("deleted_at")
+            .where_clause(WhereClause::equals("u.id", 1));
+        assert_eq!(
+            audit().stamp(delete).build(),
+            Ok("UPDATE users u SET updated_by = 42, deleted_at = NOW(), updated_at = NOW() WHERE u.id = 1 AND deleted_at IS NULL".to_string())
+        );
+    }
+
+    #[test]
+    fn test_stamp_insert_qualified() {
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_columns(["name", "users.created_at"].into_iter())
+            .add_record_raw(&["'Jack'".to_string(), "'2020-01-01 00:00:00'".to_string()])
+            .unwrap();
+        assert_eq!(
+            Audit::new().table("users").stamp(insert).build(),
+            Ok("INSERT INTO users (name, users.created_at, updated_at) VALUES ('Jack', '2020-01-01 00:00:00', NOW())".to_string())
+        );
+    }
+
+    #[test]
+    fn test_stamp_soft_delete() {
+        let delete = DeleteQuery::new("users")
+            .where_clause(WhereClause::equals("id", 1))
+            .soft_delete("deleted_at");
+        assert_eq!(
+            audit().stamp(delete.clone()).build(),
+            Ok("UPDATE users SET updated_by = 42, deleted_at = NOW(), updated_at = NOW() WHERE id = 1 AND deleted_at IS NULL".to_string())
+        );
+        assert_eq!(
+            audit().stamp(delete.force_delete()).build(),
+            Ok("DELETE FROM users WHERE id = 1".to_string())
+        );
+        let statement = Statement::Delete(
+            DeleteQuery::new("orders")
+                .where_clause(WhereClause::equals("id", 2))
+                .soft_delete("deleted_at"),
+        );
+        let Statement::Delete(delete) = audit().stamp(statement) else {
+            panic!("a delete stays a delete");
+        };
+        assert_eq!(
+            delete.build(),
+            Ok("UPDATE orders SET updated_by = 42, deleted_at = NOW(), updated_at = NOW() WHERE id = 2 AND deleted_at IS NULL".to_string())
+        );
+    }
+
+    #[test]
+    fn test_stamp_local_time() {
+        let audit = Audit::new().table("users").local_time();
+        let mut update = UpdateQuery::new("users");
+        update
+            .set_field("name", &"Jack")
+            .add_where_clause(WhereClause::equals("id", 1));
+        // timestamps have a resolution of seconds
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        let before = Local::now().to_sql_repr();
+        let sql = audit.stamp(update).build().unwrap();
+        let stamped = sql
+            .split("updated_at = ")
+            .nth(1)
+            .and_then(|rest| rest.split(" WHERE").next())
+            .unwrap()
+            .to_string();
+        // stamped when the statement is, not when the audit was configured
+        assert!(stamped >= before, "{} < {}", stamped, before);
+    }
+}
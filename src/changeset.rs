@@ -0,0 +1,125 @@
+use crate::update::{AsUpdateRecord, UpdateQuery};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Changeset {
+    table: &'static str,
+    changes: Vec<FieldChange>,
+}
+
+impl Changeset {
+    /// Compares two versions of the same record column by column and keeps
+    /// only the columns whose sql representation differs.
+    /// Returns `None` when nothing changed.
+    pub fn diff<T: AsUpdateRecord>(table: &'static str, old: &T, new: &T) -> Option<Changeset> {
+        let old = old.as_update_record();
+        let changes = new
+            .as_update_record()
+            .into_iter()
+            .filter_map(|(field, new)| {
+                let old = old
+                    .iter()
+                    .find(|(f, _)| *f == field)
+                    .map(|(_, v)| v.clone())
+                    .unwrap_or_else(|| "NULL".to_string());
+                if old == new {
+                    None
+                } else {
+                    Some(FieldChange { field, old, new })
+                }
+            })
+            .collect::<Vec<FieldChange>>();
+        if changes.is_empty() {
+            None
+        } else {
+            Some(Changeset { table, changes })
+        }
+    }
+
+    pub fn table(&self) -> &'static str {
+        self.table
+    }
+
+    pub fn changes(&self) -> &[FieldChange] {
+        &self.changes
+    }
+
+    pub fn update_query(&self) -> UpdateQuery {
+        let mut update_query = UpdateQuery::new(self.table);
+        update_query.fields = self
+            .changes
+            .iter()
+            .map(|change| (change.field, change.new.clone()))
+            .collect();
+        update_query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repr::ToSqlRepr;
+    use crate::where_clause::WhereClause;
+    use crate::SqlBuilder;
+
+    #[derive(Debug, Clone)]
+    struct User {
+        id: i32,
+        name: &'static str,
+        age: i32,
+    }
+
+    impl AsUpdateRecord for User {
+        fn as_update_record(&self) -> Vec<(&'static str, String)> {
+            vec![
+                ("id", self.id.to_sql_repr()),
+                ("name", self.name.to_sql_repr()),
+                ("age", self.age.to_sql_repr()),
+            ]
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = User {
+            id: 1,
+            name: "John",
+            age: 30,
+        };
+        let new = User {
+            age: 31,
+            ..old.clone()
+        };
+        let changeset = Changeset::diff("users", &old, &new).unwrap();
+        assert_eq!(
+            changeset.changes(),
+            &[FieldChange {
+                field: "age",
+                old: "30".to_string(),
+                new: "31".to_string(),
+            }]
+        );
+        let mut update_query = changeset.update_query();
+        update_query.add_where_clause(WhereClause::equals("id", old.id));
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE users SET age = 31 WHERE id = 1"
+        );
+    }
+
+    #[test]
+    fn test_diff_no_changes() {
+        let user = User {
+            id: 1,
+            name: "John",
+            age: 30,
+        };
+        assert!(Changeset::diff("users", &user, &user.clone()).is_none());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::dialect::Dialect;
+use crate::error::Error;
+use crate::schema::{Schema, Validate};
+use crate::where_clause::{where_sql, WhereClause};
+use crate::Result;
+use crate::SqlBuilder;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexPart {
+    Column(&'static str),
+    Expr(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateIndexQuery {
+    pub index: &'static str,
+    pub table: &'static str,
+    pub parts: Vec<(IndexPart, bool)>,
+    unique: bool,
+    if_not_exists: bool,
+    concurrently: bool,
+    pub(crate) where_clause: Option<WhereClause>,
+    dialect: Dialect,
+}
+
+impl CreateIndexQuery {
+    pub fn new(index: &'static str, table: &'static str) -> Self {
+        Self {
+            index,
+            table,
+            parts: Vec::new(),
+            unique: false,
+            if_not_exists: false,
+            concurrently: false,
+            where_clause: None,
+            dialect: Dialect::default(),
+        }
+    }
+
+    pub fn column(mut self, column: &'static str) -> Self {
+        self.parts.push((IndexPart::Column(column), false));
+        self
+    }
+
+    pub fn column_desc(mut self, column: &'static str) -> Self {
+        self.parts.push((IndexPart::Column(column), true));
+        self
+    }
+
+    pub fn columns(mut self, columns: &[&'static str]) -> Self {
+        self.parts.extend(
+            columns
+                .iter()
+                .map(|column| (IndexPart::Column(column), false)),
+        );
+        self
+    }
+
+    /// Indexes the result of an expression such as `LOWER(email)`.
+    pub fn expr(mut self, expr: String) -> Self {
+        self.parts.push((IndexPart::Expr(expr), false));
+        self
+    }
+
+    pub fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+
+    /// Builds the index without locking out writes, PostgreSQL only.
+    pub fn concurrently(mut self) -> Self {
+        self.concurrently = true;
+        self
+    }
+
+    /// Only indexes the rows matching `where_clause`, PostgreSQL and SQLite only.
+    pub fn where_clause(mut self, where_clause: WhereClause) -> Self {
+        self.where_clause = Some(where_clause);
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+}
+
+impl SqlBuilder for CreateIndexQuery {
+    fn build(&self) -> Result<String> {
+        if self.parts.is_empty() {
+            return Err(Error::NoIndexColumns);
+        }
+        if self.concurrently && self.dialect != Dialect::Postgres {
+            return Err(Error::UnsupportedByDialect(
+                "CREATE INDEX CONCURRENTLY",
+                self.dialect,
+            ));
+        }
+        if self.where_clause.is_some() && self.dialect == Dialect::MySql {
+            return Err(Error::UnsupportedByDialect("partial indexes", self.dialect));
+        }
+        if self.if_not_exists && self.dialect == Dialect::MySql {
+            return Err(Error::UnsupportedByDialect(
+                "CREATE INDEX IF NOT EXISTS",
+                self.dialect,
+            ));
+        }
+
+        let mut sql = String::from("CREATE ");
+        if self.unique {
+            sql.push_str("UNIQUE ");
+        }
+        sql.push_str("INDEX ");
+        if self.concurrently {
+            sql.push_str("CONCURRENTLY ");
+        }
+        if self.if_not_exists {
+            sql.push_str("IF NOT EXISTS ");
+        }
+        sql.push_str(&format!("{} ON {} (", self.index, self.table));
+        sql.push_str(
+            &self
+                .parts
+                .iter()
+                .map(|(part, desc)| {
+                    let part = match part {
+                        IndexPart::Column(column) => column.to_string(),
+                        // MySQL wants functional key parts in their own parentheses
+                        IndexPart::Expr(expr) => format!("({})", expr),
+                    };
+                    if *desc {
+                        format!("{} DESC", part)
+                    } else {
+                        part
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(", "),
+        );
+        sql.push(')');
+        sql.push_str(&where_sql(self.where_clause.as_ref())?);
+        Ok(sql)
+    }
+}
+
+impl Validate for CreateIndexQuery {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        let table = schema.require_table(self.table)?;
+        for (part, _) in &self.parts {
+            if let IndexPart::Column(column) = part {
+                table.require_column(column)?;
+            }
+        }
+        if let Some(where_clause) = &self.where_clause {
+            schema.validate_where_clause(&[self.table], where_clause)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_index() {
+        let index = CreateIndexQuery::new("idx_events_user_created", "events")
+            .column("user_id")
+            .column_desc("created_at");
+        assert_eq!(
+            index.build().unwrap(),
+            "CREATE INDEX idx_events_user_created ON events (user_id, created_at DESC)"
+        );
+    }
+
+    #[test]
+    fn test_create_unique_expression_index() {
+        let index = CreateIndexQuery::new("idx_users_email", "users")
+            .unique()
+            .expr("LOWER(email)".to_string());
+        assert_eq!(
+            index.build().unwrap(),
+            "CREATE UNIQUE INDEX idx_users_email ON users ((LOWER(email)))"
+        );
+    }
+
+    #[test]
+    fn test_create_partial_index() {
+        let index = CreateIndexQuery::new("idx_users_active_email", "users")
+            .columns(&["email", "name"])
+            .if_not_exists()
+            .concurrently()
+            .where_clause(WhereClause::is_null("deleted_at"))
+            .dialect(Dialect::Postgres);
+        assert_eq!(
+            index.build().unwrap(),
+            "CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_users_active_email ON users (email, name) WHERE deleted_at IS NULL"
+        );
+    }
+
+    #[test]
+    fn test_create_index_unsupported() {
+        let index = CreateIndexQuery::new("idx_users_email", "users").column("email");
+        assert_eq!(
+            index
+                .clone()
+                .where_clause(WhereClause::is_null("deleted_at"))
+                .build()
+                .unwrap_err(),
+            Error::UnsupportedByDialect("partial indexes", Dialect::MySql)
+        );
+        assert_eq!(
+            index
+                .clone()
+                .concurrently()
+                .dialect(Dialect::Sqlite)
+                .build()
+                .unwrap_err(),
+            Error::UnsupportedByDialect("CREATE INDEX CONCURRENTLY", Dialect::Sqlite)
+        );
+        assert_eq!(
+            index.if_not_exists().build().unwrap_err(),
+            Error::UnsupportedByDialect("CREATE INDEX IF NOT EXISTS", Dialect::MySql)
+        );
+        assert_eq!(
+            CreateIndexQuery::new("idx_users_email", "users")
+                .build()
+                .unwrap_err(),
+            Error::NoIndexColumns
+        );
+    }
+}
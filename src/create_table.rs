@@ -0,0 +1,566 @@
+use crate::dialect::Dialect;
+use crate::error::Error;
+use crate::repr::ToSqlRepr;
+use crate::schema::{Schema, TableDef, Validate};
+use crate::Result;
+use crate::SqlBuilder;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnType {
+    Integer,
+    BigInteger,
+    Float,
+    Text,
+    Varchar(u32),
+    Timestamp,
+    Json,
+    Uuid,
+    Boolean,
+    Decimal(u8, u8),
+}
+
+impl ColumnType {
+    pub fn is_integer(&self) -> bool {
+        matches!(self, ColumnType::Integer | ColumnType::BigInteger)
+    }
+
+    pub fn is_numeric(&self) -> bool {
+        self.is_integer() || matches!(self, ColumnType::Float | ColumnType::Decimal(_, _))
+    }
+
+    /// The native type for `dialect`. Uuid maps to `BINARY(16)` on MySQL
+    /// to hold the output of `UUID_TO_BIN` used by `ToSqlRepr for Uuid`.
+    pub fn to_sql(&self, dialect: Dialect) -> String {
+        match (self, dialect) {
+            (ColumnType::Integer, Dialect::MySql) => "INT".to_string(),
+            (ColumnType::Integer, _) => "INTEGER".to_string(),
+            (ColumnType::BigInteger, Dialect::Sqlite) => "INTEGER".to_string(),
+            (ColumnType::BigInteger, _) => "BIGINT".to_string(),
+            (ColumnType::Float, Dialect::MySql) => "DOUBLE".to_string(),
+            (ColumnType::Float, Dialect::Postgres) => "DOUBLE PRECISION".to_string(),
+            (ColumnType::Float, Dialect::Sqlite) => "REAL".to_string(),
+            (ColumnType::Text, _) => "TEXT".to_string(),
+            (ColumnType::Varchar(length), _) => format!("VARCHAR({})", length),
+            (ColumnType::Timestamp, Dialect::MySql) => "DATETIME".to_string(),
+            (ColumnType::Timestamp, Dialect::Postgres) => "TIMESTAMP".to_string(),
+            (ColumnType::Timestamp, Dialect::Sqlite) => "TEXT".to_string(),
+            (ColumnType::Json, Dialect::MySql) => "JSON".to_string(),
+            (ColumnType::Json, Dialect::Postgres) => "JSONB".to_string(),
+            (ColumnType::Json, Dialect::Sqlite) => "TEXT".to_string(),
+            (ColumnType::Uuid, Dialect::MySql) => "BINARY(16)".to_string(),
+            (ColumnType::Uuid, Dialect::Postgres) => "UUID".to_string(),
+            (ColumnType::Uuid, Dialect::Sqlite) => "BLOB".to_string(),
+            (ColumnType::Boolean, Dialect::Sqlite) => "INTEGER".to_string(),
+            (ColumnType::Boolean, _) => "BOOLEAN".to_string(),
+            (ColumnType::Decimal(precision, scale), _) => {
+                format!("DECIMAL({}, {})", precision, scale)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnDefault {
+    Value(String),
+    Expr(String),
+    Now,
+}
+
+impl ColumnDefault {
+    /// Uuid values are rendered as `UUID_TO_BIN`, which is only a function on MySQL
+    /// and needs parentheses to be a default there. PostgreSQL gets the uuid literal
+    /// and SQLite the blob `UUID_TO_BIN` would produce.
+    pub fn to_sql(&self, dialect: Dialect) -> String {
+        match self {
+            ColumnDefault::Value(value) => match uuid_value(value) {
+                Some(uuid) => match dialect {
+                    Dialect::MySql => format!("({})", value),
+                    Dialect::Postgres => format!("'{}'", uuid),
+                    Dialect::Sqlite => format!("X'{}'", uuid.simple()),
+                },
+                None => value.clone(),
+            },
+            ColumnDefault::Expr(expr) => format!("({})", expr),
+            ColumnDefault::Now => dialect.now().to_string(),
+        }
+    }
+}
+
+fn uuid_value(value: &str) -> Option<Uuid> {
+    let text = value.strip_prefix("UUID_TO_BIN('")?.strip_suffix("')")?;
+    Uuid::parse_str(text).ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForeignKeyAction {
+    Cascade,
+    SetNull,
+    SetDefault,
+    Restrict,
+    NoAction,
+}
+
+impl ForeignKeyAction {
+    pub fn to_sql(&self) -> &'static str {
+        match self {
+            ForeignKeyAction::Cascade => "CASCADE",
+            ForeignKeyAction::SetNull => "SET NULL",
+            ForeignKeyAction::SetDefault => "SET DEFAULT",
+            ForeignKeyAction::Restrict => "RESTRICT",
+            ForeignKeyAction::NoAction => "NO ACTION",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignKey {
+    pub columns: Vec<&'static str>,
+    pub table: &'static str,
+    pub references: Vec<&'static str>,
+    pub on_delete: Option<ForeignKeyAction>,
+    pub on_update: Option<ForeignKeyAction>,
+}
+
+impl ForeignKey {
+    pub fn new(columns: &[&'static str], table: &'static str, references: &[&'static str]) -> Self {
+        Self {
+            columns: columns.to_vec(),
+            table,
+            references: references.to_vec(),
+            on_delete: None,
+            on_update: None,
+        }
+    }
+
+    pub fn on_delete(mut self, action: ForeignKeyAction) -> Self {
+        self.on_delete = Some(action);
+        self
+    }
+
+    pub fn on_update(mut self, action: ForeignKeyAction) -> Self {
+        self.on_update = Some(action);
+        self
+    }
+}
+
+impl SqlBuilder for ForeignKey {
+    fn build(&self) -> Result<String> {
+        let mut sql = format!(
+            "FOREIGN KEY ({}) REFERENCES {}",
+            self.columns.join(", "),
+            self.table
+        );
+        // without referenced columns the primary key of the other table is used
+        if !self.references.is_empty() {
+            sql.push_str(&format!(" ({})", self.references.join(", ")));
+        }
+        if let Some(action) = self.on_delete {
+            sql.push_str(" ON DELETE ");
+            sql.push_str(action.to_sql());
+        }
+        if let Some(action) = self.on_update {
+            sql.push_str(" ON UPDATE ");
+            sql.push_str(action.to_sql());
+        }
+        Ok(sql)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TableConstraint {
+    PrimaryKey(Vec<&'static str>),
+    Unique(Vec<&'static str>),
+    Check(String),
+    ForeignKey(ForeignKey),
+}
+
+impl SqlBuilder for TableConstraint {
+    fn build(&self) -> Result<String> {
+        match self {
+            TableConstraint::PrimaryKey(columns) => {
+                Ok(format!("PRIMARY KEY ({})", columns.join(", ")))
+            }
+            TableConstraint::Unique(columns) => Ok(format!("UNIQUE ({})", columns.join(", "))),
+            TableConstraint::Check(expr) => Ok(format!("CHECK ({})", expr)),
+            TableConstraint::ForeignKey(foreign_key) => foreign_key.build(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnDef {
+    pub name: &'static str,
+    pub column_type: ColumnType,
+    pub not_null: bool,
+    pub default: Option<ColumnDefault>,
+    pub primary_key: bool,
+    pub auto_increment: bool,
+    pub unique: bool,
+    pub check: Option<String>,
+    pub references: Option<ForeignKey>,
+}
+
+impl ColumnDef {
+    pub fn new(name: &'static str, column_type: ColumnType) -> Self {
+        Self {
+            name,
+            column_type,
+            not_null: false,
+            default: None,
+            primary_key: false,
+            auto_increment: false,
+            unique: false,
+            check: None,
+            references: None,
+        }
+    }
+
+    pub fn not_null(mut self) -> Self {
+        self.not_null = true;
+        self
+    }
+
+    pub fn default<T: ToSqlRepr>(mut self, value: &T) -> Self {
+        self.default = Some(ColumnDefault::Value(value.to_sql_repr()));
+        self
+    }
+
+    pub fn default_expr(mut self, expr: String) -> Self {
+        self.default = Some(ColumnDefault::Expr(expr));
+        self
+    }
+
+    pub fn default_now(mut self) -> Self {
+        self.default = Some(ColumnDefault::Now);
+        self
+    }
+
+    pub fn primary_key(mut self) -> Self {
+        self.primary_key = true;
+        self
+    }
+
+    pub fn auto_increment(mut self) -> Self {
+        self.auto_increment = true;
+        self
+    }
+
+    pub fn unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+
+    pub fn check(mut self, expr: String) -> Self {
+        self.check = Some(expr);
+        self
+    }
+
+    /// References a column of another table, rendered as a table level
+    /// `FOREIGN KEY` since MySQL ignores inline `REFERENCES`.
+    pub fn references(
+        mut self,
+        table: &'static str,
+        column: &'static str,
+        on_delete: Option<ForeignKeyAction>,
+    ) -> Self {
+        let mut foreign_key = ForeignKey::new(&[self.name], table, &[column]);
+        foreign_key.on_delete = on_delete;
+        self.references = Some(foreign_key);
+        self
+    }
+
+    pub fn to_sql(&self, dialect: Dialect) -> Result<String> {
+        if self.auto_increment && !self.column_type.is_integer() {
+            return Err(Error::AutoIncrementNotInteger(self.name));
+        }
+        // SQLite only increments the `INTEGER PRIMARY KEY` rowid alias
+        if self.auto_increment && !self.primary_key && dialect == Dialect::Sqlite {
+            return Err(Error::AutoIncrementNotPrimaryKey(self.name));
+        }
+        let mut sql = format!("{} {}", self.name, self.column_type.to_sql(dialect));
+        if self.auto_increment && dialect == Dialect::Postgres {
+            sql.push_str(" GENERATED BY DEFAULT AS IDENTITY");
+        }
+        if self.not_null {
+            sql.push_str(" NOT NULL");
+        }
+        if let Some(default) = &self.default {
+            sql.push_str(" DEFAULT ");
+            sql.push_str(&default.to_sql(dialect));
+        }
+        if self.auto_increment && dialect == Dialect::MySql {
+            sql.push_str(" AUTO_INCREMENT");
+        }
+        if self.primary_key {
+            sql.push_str(" PRIMARY KEY");
+            if self.auto_increment && dialect == Dialect::Sqlite {
+                sql.push_str(" AUTOINCREMENT");
+            }
+        }
+        if self.unique {
+            sql.push_str(" UNIQUE");
+        }
+        if let Some(check) = &self.check {
+            sql.push_str(&format!(" CHECK ({})", check));
+        }
+        Ok(sql)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateTableQuery {
+    pub table: &'static str,
+    pub columns: Vec<ColumnDef>,
+    pub constraints: Vec<(Option<&'static str>, TableConstraint)>,
+    if_not_exists: bool,
+    dialect: Dialect,
+}
+
+impl CreateTableQuery {
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            table,
+            columns: Vec::new(),
+            constraints: Vec::new(),
+            if_not_exists: false,
+            dialect: Dialect::default(),
+        }
+    }
+
+    pub fn column(mut self, column: ColumnDef) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    pub fn primary_key(mut self, columns: &[&'static str]) -> Self {
+        self.constraints
+            .push((None, TableConstraint::PrimaryKey(columns.to_vec())));
+        self
+    }
+
+    pub fn unique(mut self, columns: &[&'static str]) -> Self {
+        self.constraints
+            .push((None, TableConstraint::Unique(columns.to_vec())));
+        self
+    }
+
+    pub fn check(mut self, expr: String) -> Self {
+        self.constraints.push((None, TableConstraint::Check(expr)));
+        self
+    }
+
+    pub fn foreign_key(mut self, foreign_key: ForeignKey) -> Self {
+        self.constraints
+            .push((None, TableConstraint::ForeignKey(foreign_key)));
+        self
+    }
+
+    /// A named constraint, rendered as `CONSTRAINT name ...` so it can be dropped later.
+    pub fn constraint(mut self, name: &'static str, constraint: TableConstraint) -> Self {
+        self.constraints.push((Some(name), constraint));
+        self
+    }
+
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+}
+
+impl CreateTableQuery {
+    /// Renders the statement under another table name, used by table rebuilds.
+    pub(crate) fn build_as(&self, table: &str, dialect: Dialect) -> Result<String> {
+        if self.columns.is_empty() {
+            return Err(Error::NoTableColumns);
+        }
+        let mut definitions = self
+            .columns
+            .iter()
+            .map(|column| column.to_sql(dialect))
+            .collect::<Result<Vec<String>>>()?;
+        for (name, constraint) in &self.constraints {
+            definitions.push(named_constraint(*name, constraint)?);
+        }
+        for column in &self.columns {
+            if let Some(foreign_key) = &column.references {
+                definitions.push(foreign_key.build()?);
+            }
+        }
+        let mut sql = String::from("CREATE TABLE ");
+        if self.if_not_exists {
+            sql.push_str("IF NOT EXISTS ");
+        }
+        sql.push_str(table);
+        sql.push_str(" (");
+        sql.push_str(&definitions.join(", "));
+        sql.push(')');
+        Ok(sql)
+    }
+}
+
+impl SqlBuilder for CreateTableQuery {
+    fn build(&self) -> Result<String> {
+        self.build_as(self.table, self.dialect)
+    }
+}
+
+/// `[CONSTRAINT name] ...`
+pub(crate) fn named_constraint(
+    name: Option<&'static str>,
+    constraint: &TableConstraint,
+) -> Result<String> {
+    match name {
+        Some(name) => Ok(format!("CONSTRAINT {} {}", name, constraint.build()?)),
+        None => constraint.build(),
+    }
+}
+
+impl Validate for CreateTableQuery {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        if schema.get_table(self.table).is_some() {
+            return if self.if_not_exists {
+                Ok(())
+            } else {
+                Err(Error::TableExists(self.table))
+            };
+        }
+        schema.validate_table_def(&TableDef::from(self.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn users() -> CreateTableQuery {
+        CreateTableQuery::new("users")
+            .if_not_exists()
+            .column(
+                ColumnDef::new("id", ColumnType::BigInteger)
+                    .primary_key()
+                    .auto_increment(),
+            )
+            .column(ColumnDef::new("uuid", ColumnType::Uuid).not_null().unique())
+            .column(ColumnDef::new("name", ColumnType::Varchar(64)).not_null())
+            .column(
+                ColumnDef::new("age", ColumnType::Integer)
+                    .default(&0)
+                    .check("age >= 0".to_string()),
+            )
+            .column(ColumnDef::new("active", ColumnType::Boolean).default(&true))
+            .column(ColumnDef::new("balance", ColumnType::Decimal(10, 2)))
+            .column(ColumnDef::new("profile", ColumnType::Json))
+            .column(ColumnDef::new("bio", ColumnType::Text))
+            .column(ColumnDef::new("score", ColumnType::Float))
+            .column(
+                ColumnDef::new("team_id", ColumnType::BigInteger).references(
+                    "teams",
+                    "id",
+                    Some(ForeignKeyAction::SetNull),
+                ),
+            )
+            .column(
+                ColumnDef::new("created_at", ColumnType::Timestamp)
+                    .not_null()
+                    .default_now(),
+            )
+            .unique(&["name", "team_id"])
+            .constraint("adult", TableConstraint::Check("age < 150".to_string()))
+    }
+
+    #[test]
+    fn test_create_table_mysql() {
+        assert_eq!(
+            users().build().unwrap(),
+            "CREATE TABLE IF NOT EXISTS users (id BIGINT AUTO_INCREMENT PRIMARY KEY, uuid BINARY(16) NOT NULL UNIQUE, name VARCHAR(64) NOT NULL, age INT DEFAULT 0 CHECK (age >= 0), active BOOLEAN DEFAULT TRUE, balance DECIMAL(10, 2), profile JSON, bio TEXT, score DOUBLE, team_id BIGINT, created_at DATETIME NOT NULL DEFAULT NOW(), UNIQUE (name, team_id), CONSTRAINT adult CHECK (age < 150), FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE SET NULL)"
+        );
+    }
+
+    #[test]
+    fn test_create_table_postgres() {
+        assert_eq!(
+            users().dialect(Dialect::Postgres).build().unwrap(),
+            "CREATE TABLE IF NOT EXISTS users (id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY, uuid UUID NOT NULL UNIQUE, name VARCHAR(64) NOT NULL, age INTEGER DEFAULT 0 CHECK (age >= 0), active BOOLEAN DEFAULT TRUE, balance DECIMAL(10, 2), profile JSONB, bio TEXT, score DOUBLE PRECISION, team_id BIGINT, created_at TIMESTAMP NOT NULL DEFAULT NOW(), UNIQUE (name, team_id), CONSTRAINT adult CHECK (age < 150), FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE SET NULL)"
+        );
+    }
+
+    #[test]
+    fn test_create_table_sqlite() {
+        assert_eq!(
+            users().dialect(Dialect::Sqlite).build().unwrap(),
+            "CREATE TABLE IF NOT EXISTS users (id INTEGER PRIMARY KEY AUTOINCREMENT, uuid BLOB NOT NULL UNIQUE, name VARCHAR(64) NOT NULL, age INTEGER DEFAULT 0 CHECK (age >= 0), active INTEGER DEFAULT TRUE, balance DECIMAL(10, 2), profile TEXT, bio TEXT, score REAL, team_id INTEGER, created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP, UNIQUE (name, team_id), CONSTRAINT adult CHECK (age < 150), FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE SET NULL)"
+        );
+    }
+
+    #[test]
+    fn test_create_table_composite_primary_key() {
+        let query = CreateTableQuery::new("memberships")
+            .column(ColumnDef::new("user_id", ColumnType::BigInteger).not_null())
+            .column(ColumnDef::new("team_id", ColumnType::BigInteger).not_null())
+            .primary_key(&["user_id", "team_id"])
+            .foreign_key(
+                ForeignKey::new(&["team_id"], "teams", &["id"])
+                    .on_delete(ForeignKeyAction::Cascade)
+                    .on_update(ForeignKeyAction::Restrict),
+            );
+        assert_eq!(
+            query.build().unwrap(),
+            "CREATE TABLE memberships (user_id BIGINT NOT NULL, team_id BIGINT NOT NULL, PRIMARY KEY (user_id, team_id), FOREIGN KEY (team_id) REFERENCES teams (id) ON DELETE CASCADE ON UPDATE RESTRICT)"
+        );
+    }
+
+    #[test]
+    fn test_create_table_errors() {
+        assert_eq!(
+            CreateTableQuery::new("users").build().unwrap_err(),
+            Error::NoTableColumns
+        );
+        assert_eq!(
+            CreateTableQuery::new("users")
+                .column(ColumnDef::new("id", ColumnType::Text).auto_increment())
+                .build()
+                .unwrap_err(),
+            Error::AutoIncrementNotInteger("id")
+        );
+        let sequence = ColumnDef::new("seq", ColumnType::Integer).auto_increment();
+        assert_eq!(
+            CreateTableQuery::new("events")
+                .column(sequence.clone())
+                .dialect(Dialect::Sqlite)
+                .build()
+                .unwrap_err(),
+            Error::AutoIncrementNotPrimaryKey("seq")
+        );
+        assert_eq!(
+            sequence.to_sql(Dialect::MySql),
+            Ok("seq INT AUTO_INCREMENT".to_string())
+        );
+    }
+
+    #[test]
+    fn test_uuid_default() {
+        let uuid = Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        let column = ColumnDef::new("token", ColumnType::Uuid).default(&uuid);
+        assert_eq!(
+            column.to_sql(Dialect::MySql),
+            Ok(
+                "token BINARY(16) DEFAULT (UUID_TO_BIN('67e55044-10b1-426f-9247-bb680e5fe0c8'))"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            column.to_sql(Dialect::Postgres),
+            Ok("token UUID DEFAULT '67e55044-10b1-426f-9247-bb680e5fe0c8'".to_string())
+        );
+        assert_eq!(
+            column.to_sql(Dialect::Sqlite),
+            Ok("token BLOB DEFAULT X'67e5504410b1426f9247bb680e5fe0c8'".to_string())
+        );
+    }
+}
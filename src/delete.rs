@@ -1,12 +1,28 @@
+use crate::dialect::Dialect;
 use crate::error::Error;
-use crate::where_clause::WhereClause;
+use crate::join::{join_clauses, join_conditions, join_tables, Join};
+use crate::lexer::intern;
+use crate::schema::{Schema, Validate};
+use crate::select::{key_subquery, limit_key, order_by_limit, OrderBy};
+use crate::update::{Assignment, UpdateQuery};
+use crate::where_clause::{where_sql, WhereClause, FULL_TABLE_AUDIT_COMMENT};
 use crate::Result;
 use crate::SqlBuilder;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DeleteQuery {
     pub table: &'static str,
     pub where_clause: Option<WhereClause>,
+    pub(crate) joins: Vec<Join>,
+    pub(crate) order_by: Vec<OrderBy>,
+    limit: Option<u64>,
+    pub(crate) limit_key: Option<&'static str>,
+    pub(crate) allow_full_table: bool,
+    dialect: Dialect,
+    pub(crate) soft_delete: Option<&'static str>,
+    /// Further assignments of a soft delete, e.g. audit columns.
+    pub(crate) soft_delete_fields: Vec<(&'static str, Assignment)>,
+    force: bool,
 }
 
 impl DeleteQuery {
@@ -14,6 +30,15 @@ impl DeleteQuery {
         Self {
             table,
             where_clause: None,
+            joins: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            limit_key: None,
+            allow_full_table: false,
+            dialect: Dialect::default(),
+            soft_delete: None,
+            soft_delete_fields: Vec::new(),
+            force: false,
         }
     }
 
@@ -21,19 +46,211 @@ impl DeleteQuery {
         self.where_clause = Some(where_clause);
         self
     }
+
+    /// Deletes rows of `table` matching a join. Rendered as `DELETE a FROM a JOIN b ON ...`
+    /// on MySQL, `DELETE FROM a USING b WHERE ...` on PostgreSQL and as a `rowid IN (SELECT ...)`
+    /// subquery on SQLite, which has neither form.
+    pub fn join(mut self, table: &'static str, on: String) -> Self {
+        self.joins.push(Join::new(table, on));
+        self
+    }
+
+    pub fn order_by(mut self, column: &'static str) -> Self {
+        self.order_by.push(OrderBy::new(column));
+        self
+    }
+
+    pub fn desc(mut self) -> Result<Self> {
+        match self.order_by.last_mut() {
+            Some(order_by) => order_by.desc(),
+            None => return Err(Error::NoOrderByClause),
+        };
+        Ok(self)
+    }
+
+    pub fn asc(mut self) -> Result<Self> {
+        match self.order_by.last_mut() {
+            Some(order_by) => order_by.asc(),
+            None => return Err(Error::NoOrderByClause),
+        };
+        Ok(self)
+    }
+
+    /// Deletes at most `limit` rows. Dialects without `DELETE ... LIMIT` get
+    /// `DELETE FROM t WHERE key IN (SELECT t.key FROM t WHERE ... LIMIT n)` instead.
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The column identifying rows in the emulated `LIMIT` subquery,
+    /// defaults to `ctid` on PostgreSQL and `rowid` on SQLite.
+    pub fn limit_key(mut self, column: &'static str) -> Self {
+        self.limit_key = Some(column);
+        self
+    }
+
+    /// Explicitly allows deleting every row, without a where clause or with one that
+    /// matches everything such as `1=1`. The built statement is prefixed with
+    /// [`FULL_TABLE_AUDIT_COMMENT`].
+    pub fn allow_full_table(mut self) -> Self {
+        self.allow_full_table = true;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Soft-deletes through `column` instead of removing rows, built as
+    /// `UPDATE t SET column = NOW() WHERE ... AND column IS NULL` so already
+    /// deleted rows keep their timestamp.
+    pub fn soft_delete(mut self, column: &'static str) -> Self {
+        self.soft_delete = Some(column);
+        self
+    }
+
+    /// Physically deletes the rows of a soft-deleted table.
+    pub fn force_delete(mut self) -> Self {
+        self.force = true;
+        self
+    }
+
+    fn soft_delete_query(&self, column: &'static str) -> UpdateQuery {
+        let mut update = UpdateQuery::new(self.table);
+        // joined tables may have the column too, only MySQL allows qualifying SET though
+        let qualified = match self.joins.is_empty() {
+            true => column,
+            false => intern(&format!("{}.{}", self.table, column)),
+        };
+        update.set_now(match self.dialect {
+            Dialect::MySql => qualified,
+            Dialect::Postgres | Dialect::Sqlite => column,
+        });
+        for (field, assignment) in &self.soft_delete_fields {
+            if *field != column {
+                update.assign(field, assignment.clone());
+            }
+        }
+        update.where_clause = Some(match &self.where_clause {
+            Some(where_clause) => where_clause.clone().and_is_null(qualified),
+            None => WhereClause::is_null(qualified),
+        });
+        update.joins = self.joins.clone();
+        update.order_by = self.order_by.clone();
+        update.limit = self.limit;
+        update.limit_key = self.limit_key;
+        update.allow_full_table = self.allow_full_table;
+        update.dialect = self.dialect;
+        update
+    }
+}
+
+impl DeleteQuery {
+    /// `DELETE FROM t WHERE key IN (SELECT t.key FROM t ...)`, for limits and joins
+    /// the dialect can not express directly.
+    fn key_delete(&self, where_clause: Option<&WhereClause>) -> Result<String> {
+        let key = limit_key(self.limit_key, self.dialect)?;
+        Ok(format!(
+            "DELETE FROM {} WHERE {} IN ({})",
+            self.table,
+            key,
+            key_subquery(
+                self.table,
+                key,
+                &self.joins,
+                where_clause,
+                &self.order_by,
+                self.limit
+            )?
+        ))
+    }
 }
 
 impl SqlBuilder for DeleteQuery {
     fn build(self: &DeleteQuery) -> Result<String> {
-        if let Some(where_clause) = &self.where_clause {
-            Ok(format!(
-                "DELETE FROM {} WHERE {}",
-                self.table,
-                where_clause.build()?
-            ))
+        let where_clause = self.where_clause.as_ref();
+        if !self.allow_full_table && where_clause.is_none_or(WhereClause::is_tautology) {
+            return Err(Error::NoDeleteConditions);
+        }
+        if self.dialect != Dialect::MySql && self.limit.is_none() && !self.order_by.is_empty() {
+            return Err(Error::UnsupportedByDialect(
+                "ORDER BY without LIMIT on a DELETE",
+                self.dialect,
+            ));
+        }
+        if let Some(column) = self.soft_delete.filter(|_| !self.force) {
+            return self.soft_delete_query(column).build();
+        }
+        let mut sql = if self.allow_full_table {
+            format!("{} ", FULL_TABLE_AUDIT_COMMENT)
         } else {
-            Err(Error::NoDeleteConditions)
+            String::new()
+        };
+        let native_limit = self.dialect.supports_update_delete_limit();
+        if self.joins.is_empty() {
+            if self.limit.is_some() && !native_limit {
+                sql.push_str(&self.key_delete(where_clause)?);
+            } else {
+                sql.push_str(&format!(
+                    "DELETE FROM {}{}",
+                    self.table,
+                    where_sql(where_clause)?
+                ));
+                sql.push_str(&order_by_limit(&self.order_by, self.limit)?);
+            }
+            return Ok(sql);
+        }
+        match self.dialect {
+            Dialect::MySql => {
+                if !self.order_by.is_empty() || self.limit.is_some() {
+                    return Err(Error::UnsupportedByDialect(
+                        "ORDER BY / LIMIT on a multi-table DELETE",
+                        self.dialect,
+                    ));
+                }
+                sql.push_str(&format!(
+                    "DELETE {} FROM {} {}{}",
+                    self.table,
+                    self.table,
+                    join_clauses(&self.joins)?,
+                    where_sql(where_clause)?
+                ));
+            }
+            Dialect::Postgres if self.limit.is_none() => sql.push_str(&format!(
+                "DELETE FROM {} USING {}{}",
+                self.table,
+                join_tables(&self.joins),
+                where_sql(join_conditions(&self.joins, where_clause.cloned()).as_ref())?
+            )),
+            // SQLite has no multi-table DELETE
+            Dialect::Postgres | Dialect::Sqlite => sql.push_str(&self.key_delete(where_clause)?),
+        }
+        Ok(sql)
+    }
+}
+
+impl Validate for DeleteQuery {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        let tables = std::iter::once(self.table)
+            .chain(self.joins.iter().map(|join| join.table))
+            .collect::<Vec<_>>();
+        for table in &tables {
+            schema.require_table(table)?;
+        }
+        if let Some(where_clause) = &self.where_clause {
+            schema.validate_where_clause(&tables, where_clause)?;
+        }
+        for order_by in &self.order_by {
+            for column in &order_by.columns {
+                schema.resolve_column(&tables, column)?;
+            }
+        }
+        if let Some(column) = self.soft_delete {
+            schema.resolve_column(&[self.table], column)?;
         }
+        Ok(())
     }
 }
 
@@ -55,4 +272,177 @@ mod tests {
             .build();
         assert_eq!(delete, Ok("DELETE FROM users WHERE id = 1".to_string()));
     }
+
+    #[test]
+    fn test_delete_join() {
+        let delete = DeleteQuery::new("sessions")
+            .join("users", "users.id = sessions.user_id".to_string())
+            .where_clause(WhereClause::equals("users.active", false));
+        assert_eq!(
+            delete.build(),
+            Ok("DELETE sessions FROM sessions JOIN users ON users.id = sessions.user_id WHERE users.active = FALSE".to_string())
+        );
+        assert_eq!(
+            delete.clone().dialect(Dialect::Postgres).build(),
+            Ok("DELETE FROM sessions USING users WHERE users.id = sessions.user_id AND users.active = FALSE".to_string())
+        );
+        assert_eq!(
+            delete.dialect(Dialect::Sqlite).build(),
+            Ok("DELETE FROM sessions WHERE rowid IN (SELECT sessions.rowid FROM sessions JOIN users ON users.id = sessions.user_id WHERE users.active = FALSE)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_delete_order_by_limit() {
+        let delete = DeleteQuery::new("events")
+            .where_clause(WhereClause::less_than("created_at", "2023-01-01"))
+            .order_by("id")
+            .limit(10000);
+        assert_eq!(
+            delete.build(),
+            Ok(
+                "DELETE FROM events WHERE created_at < '2023-01-01' ORDER BY id ASC LIMIT 10000"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            delete.clone().dialect(Dialect::Postgres).build(),
+            Ok("DELETE FROM events WHERE ctid IN (SELECT events.ctid FROM events WHERE created_at < '2023-01-01' ORDER BY id ASC LIMIT 10000)".to_string())
+        );
+        assert_eq!(
+            delete.dialect(Dialect::Postgres).limit_key("id").build(),
+            Ok("DELETE FROM events WHERE id IN (SELECT events.id FROM events WHERE created_at < '2023-01-01' ORDER BY id ASC LIMIT 10000)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_delete_join_limit() {
+        let delete = DeleteQuery::new("sessions")
+            .join("users", "users.id = sessions.user_id".to_string())
+            .where_clause(WhereClause::equals("users.active", false))
+            .limit(100);
+        assert_eq!(
+            delete.build(),
+            Err(Error::UnsupportedByDialect(
+                "ORDER BY / LIMIT on a multi-table DELETE",
+                Dialect::MySql
+            ))
+        );
+        assert_eq!(
+            delete.dialect(Dialect::Postgres).build(),
+            Ok("DELETE FROM sessions WHERE ctid IN (SELECT sessions.ctid FROM sessions JOIN users ON users.id = sessions.user_id WHERE users.active = FALSE LIMIT 100)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_delete_order_by_without_limit() {
+        let delete = DeleteQuery::new("events")
+            .where_clause(WhereClause::less_than("created_at", "2023-01-01"))
+            .order_by("id");
+        assert_eq!(
+            delete.build(),
+            Ok("DELETE FROM events WHERE created_at < '2023-01-01' ORDER BY id ASC".to_string())
+        );
+        assert_eq!(
+            delete.dialect(Dialect::Sqlite).build(),
+            Err(Error::UnsupportedByDialect(
+                "ORDER BY without LIMIT on a DELETE",
+                Dialect::Sqlite
+            ))
+        );
+    }
+
+    #[test]
+    fn test_desc_without_order_by() {
+        let delete = DeleteQuery::new("events").desc();
+        assert_eq!(delete.unwrap_err(), Error::NoOrderByClause);
+    }
+
+    #[test]
+    fn test_delete_tautology() {
+        let delete = DeleteQuery::new("users")
+            .where_clause(WhereClause::raw_condition("1=1".to_string()))
+            .build();
+        assert_eq!(delete, Err(Error::NoDeleteConditions));
+        let delete = DeleteQuery::new("users")
+            .where_clause(WhereClause::equals("id", 1).or_raw_condition("TRUE".to_string()))
+            .build();
+        assert_eq!(delete, Err(Error::NoDeleteConditions));
+    }
+
+    #[test]
+    fn test_delete_allow_full_table() {
+        let delete = DeleteQuery::new("users").allow_full_table().build();
+        assert_eq!(
+            delete,
+            Ok("/* allow_full_table */ DELETE FROM users".to_string())
+        );
+        let delete = DeleteQuery::new("users")
+            .where_clause(WhereClause::raw_condition("1=1".to_string()))
+            .allow_full_table()
+            .build();
+        assert_eq!(
+            delete,
+            Ok("/* allow_full_table */ DELETE FROM users WHERE 1=1".to_string())
+        );
+    }
+
+    #[test]
+    fn test_soft_delete() {
+        let delete = DeleteQuery::new("users")
+            .where_clause(WhereClause::equals("id", 1))
+            .soft_delete("deleted_at");
+        assert_eq!(
+            delete.build(),
+            Ok(
+                "UPDATE users SET deleted_at = NOW() WHERE id = 1 AND deleted_at IS NULL"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            delete.clone().dialect(Dialect::Sqlite).build(),
+            Ok(
+                "UPDATE users SET deleted_at = CURRENT_TIMESTAMP WHERE id = 1 AND deleted_at IS NULL"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            delete.force_delete().build(),
+            Ok("DELETE FROM users WHERE id = 1".to_string())
+        );
+        let delete = DeleteQuery::new("users").soft_delete("deleted_at");
+        assert_eq!(delete.clone().build(), Err(Error::NoDeleteConditions));
+        assert_eq!(
+            delete.allow_full_table().build(),
+            Ok(
+                "/* allow_full_table */ UPDATE users SET deleted_at = NOW() WHERE deleted_at IS NULL"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_soft_delete_joins() {
+        let delete = DeleteQuery::new("users")
+            .join("teams", "teams.id = users.team_id".to_string())
+            .where_clause(WhereClause::equals("teams.name", "core"))
+            .soft_delete("deleted_at");
+        assert_eq!(
+            delete.clone().dialect(Dialect::MySql).build(),
+            Ok("UPDATE users JOIN teams ON teams.id = users.team_id SET users.deleted_at = NOW() WHERE teams.name = 'core' AND users.deleted_at IS NULL".to_string())
+        );
+        assert_eq!(
+            delete.clone().dialect(Dialect::Postgres).build(),
+            Ok("UPDATE users SET deleted_at = NOW() FROM teams WHERE teams.id = users.team_id AND teams.name = 'core' AND users.deleted_at IS NULL".to_string())
+        );
+        let delete = delete
+            .order_by("users.id")
+            .limit(10)
+            .dialect(Dialect::Postgres);
+        assert!(delete.clone().force_delete().build().is_ok());
+        assert_eq!(
+            delete.build(),
+            Ok("UPDATE users SET deleted_at = NOW() FROM teams WHERE teams.id = users.team_id AND teams.name = 'core' AND users.deleted_at IS NULL AND users.ctid IN (SELECT users.ctid FROM users JOIN teams ON teams.id = users.team_id WHERE teams.name = 'core' AND users.deleted_at IS NULL ORDER BY users.id ASC LIMIT 10)".to_string())
+        );
+    }
 }
@@ -0,0 +1,49 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    MySql,
+    Postgres,
+    Sqlite,
+}
+
+impl Dialect {
+    pub fn now(&self) -> &'static str {
+        match self {
+            Dialect::MySql | Dialect::Postgres => "NOW()",
+            Dialect::Sqlite => "CURRENT_TIMESTAMP",
+        }
+    }
+
+    /// Whether `UPDATE`/`DELETE` accept `ORDER BY` and `LIMIT` natively.
+    /// SQLite only does when built with `SQLITE_ENABLE_UPDATE_DELETE_LIMIT`,
+    /// enable the `sqlite-update-delete-limit` feature to rely on it.
+    pub fn supports_update_delete_limit(&self) -> bool {
+        match self {
+            Dialect::MySql => true,
+            Dialect::Postgres => false,
+            Dialect::Sqlite => cfg!(feature = "sqlite-update-delete-limit"),
+        }
+    }
+
+    /// The implicit row identifier used when `UPDATE`/`DELETE ... LIMIT` has to be emulated,
+    /// MySQL has none.
+    pub fn row_key(&self) -> Option<&'static str> {
+        match self {
+            Dialect::Postgres => Some("ctid"),
+            Dialect::Sqlite => Some("rowid"),
+            Dialect::MySql => None,
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Dialect::MySql => "MySQL",
+            Dialect::Postgres => "PostgreSQL",
+            Dialect::Sqlite => "SQLite",
+        })
+    }
+}
@@ -0,0 +1,208 @@
+use crate::dialect::Dialect;
+use crate::error::Error;
+use crate::schema::{Schema, Validate};
+use crate::Result;
+use crate::SqlBuilder;
+
+#[derive(Debug, Clone)]
+pub struct DropTable {
+    pub table: &'static str,
+    if_exists: bool,
+    cascade: bool,
+    confirmed: bool,
+    dialect: Dialect,
+}
+
+impl DropTable {
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            table,
+            if_exists: false,
+            cascade: false,
+            confirmed: false,
+            dialect: Dialect::default(),
+        }
+    }
+
+    pub fn if_exists(mut self) -> Self {
+        self.if_exists = true;
+        self
+    }
+
+    pub fn cascade(mut self) -> Self {
+        self.cascade = true;
+        self
+    }
+
+    /// Dropping a table loses its data, it must be asked for explicitly.
+    pub fn confirm(mut self) -> Self {
+        self.confirmed = true;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+}
+
+impl SqlBuilder for DropTable {
+    fn build(&self) -> Result<String> {
+        if !self.confirmed {
+            return Err(Error::DropNotConfirmed);
+        }
+        if self.cascade && self.dialect == Dialect::Sqlite {
+            return Err(Error::UnsupportedByDialect(
+                "DROP TABLE ... CASCADE",
+                self.dialect,
+            ));
+        }
+        let mut sql = String::from("DROP TABLE ");
+        if self.if_exists {
+            sql.push_str("IF EXISTS ");
+        }
+        sql.push_str(self.table);
+        if self.cascade {
+            sql.push_str(" CASCADE");
+        }
+        Ok(sql)
+    }
+}
+
+impl Validate for DropTable {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        if self.if_exists {
+            return Ok(());
+        }
+        schema.require_table(self.table).map(|_| ())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DropIndex {
+    pub index: &'static str,
+    pub(crate) table: Option<&'static str>,
+    if_exists: bool,
+    confirmed: bool,
+    dialect: Dialect,
+}
+
+impl DropIndex {
+    pub fn new(index: &'static str) -> Self {
+        Self {
+            index,
+            table: None,
+            if_exists: false,
+            confirmed: false,
+            dialect: Dialect::default(),
+        }
+    }
+
+    /// The table the index belongs to, required on MySQL.
+    pub fn on(mut self, table: &'static str) -> Self {
+        self.table = Some(table);
+        self
+    }
+
+    pub fn if_exists(mut self) -> Self {
+        self.if_exists = true;
+        self
+    }
+
+    pub fn confirm(mut self) -> Self {
+        self.confirmed = true;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+}
+
+impl SqlBuilder for DropIndex {
+    fn build(&self) -> Result<String> {
+        if !self.confirmed {
+            return Err(Error::DropNotConfirmed);
+        }
+        match self.dialect {
+            Dialect::MySql => {
+                if self.if_exists {
+                    return Err(Error::UnsupportedByDialect(
+                        "DROP INDEX IF EXISTS",
+                        self.dialect,
+                    ));
+                }
+                match self.table {
+                    Some(table) => Ok(format!("DROP INDEX {} ON {}", self.index, table)),
+                    None => Err(Error::NoIndexTable),
+                }
+            }
+            Dialect::Postgres | Dialect::Sqlite => {
+                let mut sql = String::from("DROP INDEX ");
+                if self.if_exists {
+                    sql.push_str("IF EXISTS ");
+                }
+                sql.push_str(self.index);
+                Ok(sql)
+            }
+        }
+    }
+}
+
+impl Validate for DropIndex {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        match self.table {
+            Some(table) if !self.if_exists => schema.require_table(table).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_table_not_confirmed() {
+        let drop = DropTable::new("users").if_exists().build();
+        assert_eq!(drop, Err(Error::DropNotConfirmed));
+    }
+
+    #[test]
+    fn test_drop_table() {
+        let drop = DropTable::new("users").if_exists().confirm();
+        assert_eq!(drop.build(), Ok("DROP TABLE IF EXISTS users".to_string()));
+        assert_eq!(
+            drop.clone().cascade().dialect(Dialect::Postgres).build(),
+            Ok("DROP TABLE IF EXISTS users CASCADE".to_string())
+        );
+        assert_eq!(
+            drop.cascade().dialect(Dialect::Sqlite).build(),
+            Err(Error::UnsupportedByDialect(
+                "DROP TABLE ... CASCADE",
+                Dialect::Sqlite
+            ))
+        );
+    }
+
+    #[test]
+    fn test_drop_index() {
+        let drop = DropIndex::new("idx_users_email").confirm();
+        assert_eq!(drop.build(), Err(Error::NoIndexTable));
+        assert_eq!(
+            drop.clone().on("users").build(),
+            Ok("DROP INDEX idx_users_email ON users".to_string())
+        );
+        assert_eq!(
+            drop.if_exists().dialect(Dialect::Postgres).build(),
+            Ok("DROP INDEX IF EXISTS idx_users_email".to_string())
+        );
+    }
+
+    #[test]
+    fn test_drop_index_not_confirmed() {
+        let drop = DropIndex::new("idx_users_email").on("users").build();
+        assert_eq!(drop, Err(Error::DropNotConfirmed));
+    }
+}
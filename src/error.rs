@@ -1,5 +1,8 @@
 use thiserror::Error;
 
+use crate::create_table::ColumnType;
+use crate::dialect::Dialect;
+
 #[derive(Error, Debug, Clone, Copy, PartialEq)]
 pub enum Error {
     // error(s) in update statement
@@ -20,4 +23,94 @@ pub enum Error {
     // error(s) in select statement
     #[error("you should provide field(s) before you call 'desc' or 'asc'.")]
     NoOrderByClause,
+    // error(s) in truncate and drop statements
+    #[error("truncating a table is not allowed, you should call 'confirm' first.")]
+    TruncateNotConfirmed,
+    #[error("dropping a table or an index is not allowed, you should call 'confirm' first.")]
+    DropNotConfirmed,
+    #[error("you should provide the table the index belongs to.")]
+    NoIndexTable,
+    // error(s) in create table statement
+    #[error("you should provide column(s) before you create a table.")]
+    NoTableColumns,
+    #[error("column '{0}' is auto incremented, it should be an integer column.")]
+    AutoIncrementNotInteger(&'static str),
+    #[error("column '{0}' is auto incremented, it should be the primary key on SQLite.")]
+    AutoIncrementNotPrimaryKey(&'static str),
+    // error(s) in create index statement
+    #[error("you should provide column(s) or expression(s) before you create an index.")]
+    NoIndexColumns,
+    // error(s) in alter table statement
+    #[error("you should provide operation(s) before you alter a table.")]
+    NoAlterOperations,
+    #[error("altering table '{0}' needs a table rebuild on SQLite, you should provide its current definition with 'rebuild_from'.")]
+    SqliteRebuildRequired(&'static str),
+    #[error("constraint '{1}' does not exist in table '{0}'.")]
+    UnknownConstraint(&'static str, &'static str),
+    // error(s) in schema validation
+    #[error("table '{0}' does not exist.")]
+    UnknownTable(&'static str),
+    #[error("column '{1}' does not exist in table '{0}'.")]
+    UnknownColumn(&'static str, &'static str),
+    #[error("table '{0}' already exists.")]
+    TableExists(&'static str),
+    #[error("column '{1}' already exists in table '{0}'.")]
+    ColumnExists(&'static str, &'static str),
+    #[error("view '{0}' does not exist.")]
+    UnknownView(&'static str),
+    #[error("column '{0}.{1}' is NOT NULL without a default, you should provide a value.")]
+    MissingNotNullColumn(&'static str, &'static str),
+    #[error("column '{0}.{1}' is NOT NULL, it can not be set to NULL.")]
+    NullInNotNullColumn(&'static str, &'static str),
+    #[error("the value of column '{0}.{1}' does not match its type {2:?}.")]
+    ValueTypeMismatch(&'static str, &'static str, ColumnType),
+    #[error("the value of column '{0}.{1}' is longer than {2} characters.")]
+    ValueTooLong(&'static str, &'static str, u32),
+    // error(s) in schema migration
+    #[error("migrating table '{0}' drops or rewrites data, you should allow it with 'allow_destructive'.")]
+    DestructiveMigration(&'static str),
+    #[error("unnamed constraints of table '{0}' changed, you should name them to migrate them.")]
+    UnnamedConstraintChange(&'static str),
+    #[error(
+        "column '{0}.{1}' changes its key, check or identity, which can not be migrated in place."
+    )]
+    UnsupportedColumnChange(&'static str, &'static str),
+    // error(s) in running migrations
+    #[error("migration {0} is defined more than once.")]
+    DuplicateMigration(u64),
+    #[error("migration {0} was applied but is not defined anymore.")]
+    UnknownMigration(u64),
+    #[error("migration {0} changed since it was applied, its checksum does not match.")]
+    ChecksumMismatch(u64),
+    #[error("migration {0} has no down statements, it can not be reverted.")]
+    NoDownMigration(u64),
+    #[error("migration {0} violates foreign key constraints.")]
+    ForeignKeyViolation(u64),
+    // error(s) in evaluating where clauses
+    #[error("column '{0}' does not exist in the row.")]
+    MissingRowColumn(&'static str),
+    #[error("{0} can not be evaluated in memory.")]
+    NotEvaluable(&'static str),
+    // error(s) in tenant scoping
+    #[error("table '{0}' is tenant scoped but a sub query reads it without the tenant column.")]
+    UnscopedTable(&'static str),
+    #[error("table '{0}' is tenant scoped, writing another tenant's rows is not allowed.")]
+    CrossTenantWrite(&'static str),
+    // error(s) in keyset pagination
+    #[error("the cursor is malformed.")]
+    InvalidCursor,
+    #[error("the cursor has {0} value(s) but the query orders by {1} column(s).")]
+    CursorMismatch(usize, usize),
+    #[error("the cursor was created for another ORDER BY.")]
+    CursorOrderMismatch,
+    // error(s) in parsing sql text
+    #[error("could not parse sql, {0} at offset {1}.")]
+    InvalidSql(&'static str, usize),
+    #[error("column type '{0}' is not supported.")]
+    UnsupportedColumnType(&'static str),
+    #[error("decimal precision or scale {0} is out of range.")]
+    DecimalOutOfRange(u32),
+    // error(s) in dialect specific rendering
+    #[error("{0} is not supported by {1}.")]
+    UnsupportedByDialect(&'static str, Dialect),
 }
@@ -0,0 +1,534 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+use crate::dialect::Dialect;
+use crate::error::Error;
+use crate::where_clause::{WhereClause, WhereCondition};
+use crate::Result;
+
+/// A row of in-memory data a where clause can be evaluated against.
+pub trait Row {
+    /// The value of the column, `None` when the row has no such column.
+    fn get(&self, column: &str) -> Option<&Value>;
+}
+
+impl Row for Map<String, Value> {
+    fn get(&self, column: &str) -> Option<&Value> {
+        Map::get(self, column)
+    }
+}
+
+/// Only objects have columns.
+impl Row for Value {
+    fn get(&self, column: &str) -> Option<&Value> {
+        self.as_object().and_then(|object| object.get(column))
+    }
+}
+
+impl Row for HashMap<&str, Value> {
+    fn get(&self, column: &str) -> Option<&Value> {
+        HashMap::get(self, column)
+    }
+}
+
+impl Row for HashMap<String, Value> {
+    fn get(&self, column: &str) -> Option<&Value> {
+        HashMap::get(self, column)
+    }
+}
+
+/// Applies a `WhereClause` to in-memory rows the way the database applies it to a table.
+///
+/// Conditions follow sql's three-valued logic, a comparison with NULL is unknown
+/// and a row only matches when the clause is true. Text comparisons and `LIKE`
+/// follow the dialect's defaults: case insensitive on MySQL, `LIKE` only on SQLite,
+/// and `\` escapes in `LIKE` patterns except on SQLite.
+/// Strings holding numbers compare as numbers with numbers.
+/// Values are the literals rendered by `ToSqlRepr` or names of other columns,
+/// placeholders, expressions and raw conditions can not be evaluated.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Evaluator {
+    dialect: Dialect,
+}
+
+impl Evaluator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Whether the row matches, an unknown result does not.
+    pub fn matches<R: Row>(&self, where_clause: &WhereClause, row: &R) -> Result<bool> {
+        Ok(self.evaluate(where_clause, row)? == Some(true))
+    }
+
+    /// The rows matching the where clause, in their original order.
+    pub fn filter<'a, R: Row>(
+        &self,
+        where_clause: &WhereClause,
+        rows: impl IntoIterator<Item = &'a R>,
+    ) -> Result<Vec<&'a R>> {
+        let mut matched = Vec::new();
+        for row in rows {
+            if self.matches(where_clause, row)? {
+                matched.push(row);
+            }
+        }
+        Ok(matched)
+    }
+
+    /// The truth value of the clause, `None` when it is unknown.
+    pub fn evaluate<R: Row>(&self, where_clause: &WhereClause, row: &R) -> Result<Option<bool>> {
+        match where_clause {
+            WhereClause::And(clauses) => {
+                let mut result = Some(true);
+                for clause in clauses {
+                    result = and(result, self.evaluate(clause, row)?);
+                }
+                Ok(result)
+            }
+            WhereClause::Or(clauses) => {
+                let mut result = Some(false);
+                for clause in clauses {
+                    result = or(result, self.evaluate(clause, row)?);
+                }
+                Ok(result)
+            }
+            WhereClause::Not(clause) => Ok(self.evaluate(clause, row)?.map(|value| !value)),
+            WhereClause::Condition(condition) => self.evaluate_condition(condition, row),
+        }
+    }
+
+    fn evaluate_condition<R: Row>(
+        &self,
+        condition: &WhereCondition,
+        row: &R,
+    ) -> Result<Option<bool>> {
+        let compare = |field, value: &str, check: fn(Ordering) -> bool| -> Result<Option<bool>> {
+            let ordering = self.compare(column(row, field)?, &literal(value, row)?);
+            Ok(ordering.map(check))
+        };
+        Ok(match condition {
+            WhereCondition::Equals(field, value) => compare(field, value, Ordering::is_eq)?,
+            WhereCondition::NotEquals(field, value) => compare(field, value, Ordering::is_ne)?,
+            WhereCondition::GreaterThan(field, value) => compare(field, value, Ordering::is_gt)?,
+            WhereCondition::GreaterThanOrEquals(field, value) => {
+                compare(field, value, Ordering::is_ge)?
+            }
+            WhereCondition::LessThan(field, value) => compare(field, value, Ordering::is_lt)?,
+            WhereCondition::LessThanOrEquals(field, value) => {
+                compare(field, value, Ordering::is_le)?
+            }
+            WhereCondition::Like(field, pattern) => self.like(column(row, field)?, pattern, row)?,
+            WhereCondition::NotLike(field, pattern) => self
+                .like(column(row, field)?, pattern, row)?
+                .map(|value| !value),
+            WhereCondition::In(field, values) => self.in_(column(row, field)?, values, row)?,
+            WhereCondition::NotIn(field, values) => self
+                .in_(column(row, field)?, values, row)?
+                .map(|value| !value),
+            WhereCondition::Between(field, min, max) => {
+                self.between(column(row, field)?, min, max, row)?
+            }
+            WhereCondition::NotBetween(field, min, max) => self
+                .between(column(row, field)?, min, max, row)?
+                .map(|value| !value),
+            WhereCondition::IsNull(field) => Some(column(row, field)?.is_null()),
+            WhereCondition::IsNotNull(field) => Some(!column(row, field)?.is_null()),
+            WhereCondition::RawCondition(_) => return Err(Error::NotEvaluable("raw conditions")),
+        })
+    }
+
+    /// `value IN (...)` is true when one of the values is equal, otherwise
+    /// unknown when one of them is NULL.
+    fn in_<R: Row>(&self, value: &Value, values: &[String], row: &R) -> Result<Option<bool>> {
+        let mut result = Some(false);
+        for other in values {
+            let equals = self
+                .compare(value, &literal(other, row)?)
+                .map(Ordering::is_eq);
+            result = or(result, equals);
+        }
+        Ok(result)
+    }
+
+    fn between<R: Row>(
+        &self,
+        value: &Value,
+        min: &str,
+        max: &str,
+        row: &R,
+    ) -> Result<Option<bool>> {
+        let above = self
+            .compare(value, &literal(min, row)?)
+            .map(Ordering::is_ge);
+        let below = self
+            .compare(value, &literal(max, row)?)
+            .map(Ordering::is_le);
+        Ok(and(above, below))
+    }
+
+    fn like<R: Row>(&self, value: &Value, pattern: &str, row: &R) -> Result<Option<bool>> {
+        let pattern = literal(pattern, row)?;
+        if value.is_null() || pattern.is_null() {
+            return Ok(None);
+        }
+        let fold = |text: String| match self.dialect {
+            Dialect::MySql => text.to_lowercase(),
+            Dialect::Sqlite => text.to_ascii_lowercase(),
+            Dialect::Postgres => text,
+        };
+        let escape = match self.dialect {
+            Dialect::MySql | Dialect::Postgres => Some('\\'),
+            Dialect::Sqlite => None,
+        };
+        let text = fold(self.text(value)).chars().collect::<Vec<char>>();
+        let pattern = fold(self.text(&pattern));
+        Ok(Some(like(&text, &tokenize_pattern(&pattern, escape))))
+    }
+
+    /// The sql ordering of two values, `None` when either is NULL.
+    fn compare(&self, left: &Value, right: &Value) -> Option<Ordering> {
+        if left.is_null() || right.is_null() {
+            return None;
+        }
+        // two strings compare as text even when they hold numbers
+        let text = left.is_string() && right.is_string();
+        if let (false, Some(left), Some(right)) = (text, number(left), number(right)) {
+            return left.partial_cmp(&right);
+        }
+        let (left, right) = (self.text(left), self.text(right));
+        Some(match self.dialect {
+            Dialect::MySql => left.to_lowercase().cmp(&right.to_lowercase()),
+            Dialect::Postgres | Dialect::Sqlite => left.cmp(&right),
+        })
+    }
+
+    fn text(&self, value: &Value) -> String {
+        match value {
+            Value::String(text) => text.clone(),
+            Value::Bool(value) if self.dialect == Dialect::Postgres => value.to_string(),
+            Value::Bool(value) => (*value as u8).to_string(),
+            value => value.to_string(),
+        }
+    }
+}
+
+fn and(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (Some(true), Some(true)) => Some(true),
+        _ => None,
+    }
+}
+
+fn or(left: Option<bool>, right: Option<bool>) -> Option<bool> {
+    match (left, right) {
+        (Some(true), _) | (_, Some(true)) => Some(true),
+        (Some(false), Some(false)) => Some(false),
+        _ => None,
+    }
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::Bool(value) => Some(*value as u8 as f64),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// The value of a column, a qualified name falls back to the bare column name.
+pub(crate) fn column<'a, R: Row>(row: &'a R, field: &'static str) -> Result<&'a Value> {
+    lookup(row, field).ok_or(Error::MissingRowColumn(field))
+}
+
+fn lookup<'a, R: Row>(row: &'a R, field: &str) -> Option<&'a Value> {
+    let unquote = |name: &str| name.trim_matches(['`', '"']).to_string();
+    let field = unquote(field.trim());
+    row.get(&field).or_else(|| {
+        let (_, name) = field.rsplit_once('.')?;
+        row.get(&unquote(name))
+    })
+}
+
+/// The value of a literal rendered by `ToSqlRepr`, or of the column it names.
+fn literal<R: Row>(value: &str, row: &R) -> Result<Value> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("NULL") {
+        Ok(Value::Null)
+    } else if value.eq_ignore_ascii_case("TRUE") || value.eq_ignore_ascii_case("FALSE") {
+        Ok(Value::Bool(value.eq_ignore_ascii_case("TRUE")))
+    } else if let Ok(integer) = value.parse::<i64>() {
+        Ok(Value::from(integer))
+    } else if let Ok(float) = value.parse::<f64>() {
+        Ok(Value::from(float))
+    } else if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        Ok(Value::String(value[1..value.len() - 1].replace("''", "'")))
+    } else if value == "?" || value.starts_with('$') {
+        Err(Error::NotEvaluable("placeholders"))
+    } else {
+        lookup(row, value)
+            .cloned()
+            .ok_or(Error::NotEvaluable("expressions"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PatternToken {
+    /// `%`, any sequence of characters.
+    Any,
+    /// `_`, exactly one character.
+    One,
+    Char(char),
+}
+
+fn tokenize_pattern(pattern: &str, escape: Option<char>) -> Vec<PatternToken> {
+    let mut tokens = Vec::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        tokens.push(match c {
+            '%' => PatternToken::Any,
+            '_' => PatternToken::One,
+            // a trailing escape character stands for itself
+            c if Some(c) == escape => PatternToken::Char(chars.next().unwrap_or(c)),
+            c => PatternToken::Char(c),
+        });
+    }
+    tokens
+}
+
+/// Matches `text` against the pattern, backtracking to the last `%` on a mismatch.
+fn like(text: &[char], pattern: &[PatternToken]) -> bool {
+    let (mut t, mut p) = (0, 0);
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some(PatternToken::Any) => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(PatternToken::One) => {
+                t += 1;
+                p += 1;
+            }
+            Some(PatternToken::Char(c)) if *c == text[t] => {
+                t += 1;
+                p += 1;
+            }
+            _ => match backtrack {
+                // let the last `%` swallow one more character
+                Some((any, start)) => {
+                    backtrack = Some((any, start + 1));
+                    p = any + 1;
+                    t = start + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|token| *token == PatternToken::Any)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use rusqlite::Connection;
+    use serde_json::json;
+
+    use crate::repr::ToSqlRepr;
+    use crate::SqlBuilder;
+
+    #[test]
+    fn test_null_logic() {
+        let evaluator = Evaluator::new();
+        let row = json!({"id": 1, "name": null});
+        let unknown = WhereClause::equals("name", "Jack");
+        assert_eq!(evaluator.evaluate(&unknown, &row), Ok(None));
+        assert_eq!(evaluator.evaluate(&!unknown.clone(), &row), Ok(None));
+        assert_eq!(
+            evaluator.evaluate(&unknown.clone().and_equals("id", 2), &row),
+            Ok(Some(false))
+        );
+        assert_eq!(
+            evaluator.evaluate(&unknown.clone().or_equals("id", 1), &row),
+            Ok(Some(true))
+        );
+        assert_eq!(
+            evaluator.matches(&unknown.or_is_null("name"), &row),
+            Ok(true)
+        );
+        let not_in = WhereClause::not_in("id", vec![Some(2), None]);
+        assert_eq!(evaluator.evaluate(&not_in, &row), Ok(None));
+        let not_in = WhereClause::not_in("id", vec![Some(1), None]);
+        assert_eq!(evaluator.evaluate(&not_in, &row), Ok(Some(false)));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let evaluator = Evaluator::new();
+        let mut row = HashMap::new();
+        row.insert("age", json!(18));
+        row.insert("height", json!(180.5));
+        row.insert("name", json!("O'Brien"));
+        row.insert("active", json!(true));
+        let matches = |clause: WhereClause| evaluator.matches(&clause, &row).unwrap();
+        assert!(matches(WhereClause::between("age", 18, 30)));
+        assert!(!matches(WhereClause::not_between("age", 10, 20)));
+        assert!(matches(WhereClause::in_("users.age", &vec![17, 18])));
+        assert!(matches(WhereClause::greater_than("height", 180)));
+        assert!(matches(WhereClause::equals("name", "o''brien")));
+        assert!(matches(WhereClause::equals("active", true)));
+        assert!(matches(WhereClause::equals("active", 1)));
+        assert!(matches(WhereClause::less_than("age", "20")));
+        assert!(matches(WhereClause::Condition(WhereCondition::LessThan(
+            "age",
+            "height".to_string()
+        ))));
+        assert_eq!(
+            evaluator.matches(&WhereClause::equals("missing", 1), &row),
+            Err(Error::MissingRowColumn("missing"))
+        );
+        assert_eq!(
+            evaluator.matches(&WhereClause::raw_condition("1 = 1".to_string()), &row),
+            Err(Error::NotEvaluable("raw conditions"))
+        );
+        assert_eq!(
+            evaluator.matches(
+                &WhereClause::Condition(WhereCondition::Equals("age", "?".to_string())),
+                &row
+            ),
+            Err(Error::NotEvaluable("placeholders"))
+        );
+    }
+
+    #[test]
+    fn test_like() {
+        let row = json!({"name": "Jack_Smith", "path": "50%\\off"});
+        let like = |dialect, field, pattern| {
+            Evaluator::new()
+                .dialect(dialect)
+                .matches(&WhereClause::like(field, pattern), &row)
+                .unwrap()
+        };
+        assert!(like(Dialect::MySql, "name", "j%"));
+        assert!(like(Dialect::Sqlite, "name", "j%"));
+        assert!(!like(Dialect::Postgres, "name", "j%"));
+        assert!(like(Dialect::Postgres, "name", "J%_S%h"));
+        assert!(like(Dialect::Postgres, "name", "Jack\\_%"));
+        assert!(!like(Dialect::Postgres, "name", "Jack\\_"));
+        assert!(like(Dialect::Postgres, "name", "%%a%"));
+        assert!(!like(Dialect::Postgres, "name", "_"));
+        assert!(like(Dialect::MySql, "path", "50\\%%"));
+        assert!(like(Dialect::Sqlite, "path", "50%\\off"));
+        assert!(like(Dialect::MySql, "path", "50\\%\\\\off"));
+        assert!(!like(Dialect::Sqlite, "path", "50\\%\\\\off"));
+    }
+
+    #[test]
+    fn test_filter() {
+        let rows = vec![
+            json!({"id": 1, "name": "Jack"}),
+            json!({"id": 2, "name": "jill"}),
+            json!({"id": 3, "name": null}),
+        ];
+        let where_clause = WhereClause::like("name", "J%").or_greater_than("id", 2);
+        let ids = |dialect| {
+            Evaluator::new()
+                .dialect(dialect)
+                .filter(&where_clause, &rows)
+                .unwrap()
+                .into_iter()
+                .map(|row| row["id"].as_i64().unwrap())
+                .collect::<Vec<i64>>()
+        };
+        assert_eq!(ids(Dialect::MySql), vec![1, 2, 3]);
+        assert_eq!(ids(Dialect::Postgres), vec![1, 3]);
+    }
+
+    fn number() -> impl Strategy<Value = Option<i64>> {
+        prop::option::of(-3..3i64)
+    }
+
+    fn text() -> impl Strategy<Value = Option<String>> {
+        prop::option::of("[abAB]{0,3}")
+    }
+
+    fn condition() -> impl Strategy<Value = WhereClause> {
+        prop_oneof![
+            number().prop_map(|n| WhereClause::equals("n", n)),
+            number().prop_map(|n| WhereClause::not_equals("n", n)),
+            number().prop_map(|n| WhereClause::greater_than("n", n)),
+            number().prop_map(|n| WhereClause::less_than_or_equals("n", n)),
+            prop::collection::vec(number(), 0..3).prop_map(|n| WhereClause::in_("n", &n)),
+            prop::collection::vec(number(), 0..3).prop_map(|n| WhereClause::not_in("n", n)),
+            (number(), number()).prop_map(|(min, max)| WhereClause::between("n", min, max)),
+            Just(WhereClause::is_null("n")),
+            Just(WhereClause::is_not_null("s")),
+            text().prop_map(|s| WhereClause::equals("s", s)),
+            text().prop_map(|s| WhereClause::greater_than("s", s)),
+            "[abAB%_]{0,4}".prop_map(|s| WhereClause::like("s", s)),
+            "[abAB%_]{0,4}".prop_map(|s| WhereClause::not_like("s", s)),
+        ]
+    }
+
+    fn where_clause() -> impl Strategy<Value = WhereClause> {
+        condition().prop_recursive(3, 16, 3, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 0..3).prop_map(WhereClause::And),
+                prop::collection::vec(inner.clone(), 0..3).prop_map(WhereClause::Or),
+                inner.prop_map(|clause| !clause),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_matches_sqlite(
+            rows in prop::collection::vec((number(), text()), 0..8),
+            where_clause in where_clause(),
+        ) {
+            let connection = Connection::open_in_memory().unwrap();
+            connection
+                .execute("CREATE TABLE t (id INTEGER PRIMARY KEY, n INTEGER, s TEXT)", ())
+                .unwrap();
+            for (id, (n, s)) in rows.iter().enumerate() {
+                connection
+                    .execute(
+                        &format!("INSERT INTO t VALUES ({}, {}, {})", id, n.to_sql_repr(), s.to_sql_repr()),
+                        (),
+                    )
+                    .unwrap();
+            }
+            let sql = format!("SELECT id FROM t WHERE {} ORDER BY id", where_clause.build().unwrap());
+            let mut statement = connection.prepare(&sql).unwrap();
+            let expected = statement
+                .query_map((), |row| row.get::<_, i64>(0))
+                .unwrap()
+                .collect::<rusqlite::Result<Vec<i64>>>()
+                .unwrap();
+
+            let rows = rows
+                .into_iter()
+                .enumerate()
+                .map(|(id, (n, s))| json!({"id": id, "n": n, "s": s}))
+                .collect::<Vec<Value>>();
+            let evaluator = Evaluator::new().dialect(Dialect::Sqlite);
+            let matched = evaluator
+                .filter(&where_clause, &rows)
+                .unwrap()
+                .into_iter()
+                .map(|row| row["id"].as_i64().unwrap())
+                .collect::<Vec<i64>>();
+            prop_assert_eq!(matched, expected, "{}", sql);
+        }
+    }
+}
@@ -0,0 +1,610 @@
+//! Rewriting of the builders, the owning counterpart of `visit`.
+//!
+//! Every `fold_*` method defaults to the matching `fold_*` function, which rebuilds
+//! the node from its folded children. Override a method to replace a node, e.g.
+//! `fold_column` to rename columns or `fold_where_clause` to add a predicate.
+
+use crate::alter_table::{AlterOperation, AlterTableQuery};
+use crate::create_index::{CreateIndexQuery, IndexPart};
+use crate::create_table::{ColumnDef, CreateTableQuery, ForeignKey, TableConstraint};
+use crate::delete::DeleteQuery;
+use crate::drop::{DropIndex, DropTable};
+use crate::insert::InsertQuery;
+use crate::join::Join;
+use crate::parser::Statement;
+use crate::select::{OrderBy, SelectQuery};
+use crate::truncate::TruncateQuery;
+use crate::update::{Assignment, OptimisticLock, UpdateQuery};
+use crate::view::{CreateViewQuery, RefreshMaterializedViewQuery};
+use crate::where_clause::{WhereClause, WhereCondition};
+
+pub trait Fold {
+    fn fold_statement(&mut self, statement: Statement) -> Statement {
+        fold_statement(self, statement)
+    }
+
+    fn fold_select(&mut self, select: SelectQuery) -> SelectQuery {
+        fold_select(self, select)
+    }
+
+    fn fold_insert(&mut self, insert: InsertQuery) -> InsertQuery {
+        fold_insert(self, insert)
+    }
+
+    fn fold_update(&mut self, update: UpdateQuery) -> UpdateQuery {
+        fold_update(self, update)
+    }
+
+    fn fold_delete(&mut self, delete: DeleteQuery) -> DeleteQuery {
+        fold_delete(self, delete)
+    }
+
+    fn fold_create_table(&mut self, create_table: CreateTableQuery) -> CreateTableQuery {
+        fold_create_table(self, create_table)
+    }
+
+    fn fold_alter_table(&mut self, alter_table: AlterTableQuery) -> AlterTableQuery {
+        fold_alter_table(self, alter_table)
+    }
+
+    fn fold_create_index(&mut self, create_index: CreateIndexQuery) -> CreateIndexQuery {
+        fold_create_index(self, create_index)
+    }
+
+    fn fold_create_view(&mut self, create_view: CreateViewQuery) -> CreateViewQuery {
+        fold_create_view(self, create_view)
+    }
+
+    fn fold_refresh_materialized_view(
+        &mut self,
+        mut refresh: RefreshMaterializedViewQuery,
+    ) -> RefreshMaterializedViewQuery {
+        refresh.view = self.fold_table(refresh.view);
+        refresh
+    }
+
+    fn fold_truncate(&mut self, mut truncate: TruncateQuery) -> TruncateQuery {
+        truncate.table = self.fold_table(truncate.table);
+        truncate
+    }
+
+    fn fold_drop_table(&mut self, mut drop_table: DropTable) -> DropTable {
+        drop_table.table = self.fold_table(drop_table.table);
+        drop_table
+    }
+
+    fn fold_drop_index(&mut self, mut drop_index: DropIndex) -> DropIndex {
+        drop_index.table = drop_index.table.map(|table| self.fold_table(table));
+        drop_index
+    }
+
+    fn fold_join(&mut self, join: Join) -> Join {
+        Join::new(self.fold_table(join.table), self.fold_value(join.on))
+    }
+
+    fn fold_assignment(
+        &mut self,
+        column: &'static str,
+        assignment: Assignment,
+    ) -> (&'static str, Assignment) {
+        fold_assignment(self, column, assignment)
+    }
+
+    fn fold_where_clause(&mut self, where_clause: WhereClause) -> WhereClause {
+        fold_where_clause(self, where_clause)
+    }
+
+    fn fold_where_condition(&mut self, condition: WhereCondition) -> WhereCondition {
+        fold_where_condition(self, condition)
+    }
+
+    fn fold_order_by(&mut self, mut order_by: OrderBy) -> OrderBy {
+        order_by.columns = order_by
+            .columns
+            .into_iter()
+            .map(|column| self.fold_column(column))
+            .collect();
+        order_by
+    }
+
+    /// Tables, views and joined tables.
+    fn fold_table(&mut self, table: &'static str) -> &'static str {
+        table
+    }
+
+    /// Selected, inserted, assigned, filtered and ordered columns.
+    fn fold_column(&mut self, column: &'static str) -> &'static str {
+        column
+    }
+
+    /// Literals and expressions compared with or assigned to columns,
+    /// raw conditions, join conditions, check constraints and triggers.
+    fn fold_value(&mut self, value: String) -> String {
+        value
+    }
+}
+
+pub fn fold_statement<F: Fold + ?Sized>(folder: &mut F, statement: Statement) -> Statement {
+    match statement {
+        Statement::Select(select) => Statement::Select(folder.fold_select(select)),
+        Statement::Insert(insert) => Statement::Insert(folder.fold_insert(insert)),
+        Statement::Update(update) => Statement::Update(folder.fold_update(update)),
+        Statement::Delete(delete) => Statement::Delete(folder.fold_delete(delete)),
+    }
+}
+
+pub fn fold_select<F: Fold + ?Sized>(folder: &mut F, mut select: SelectQuery) -> SelectQuery {
+    select.table = folder.fold_table(select.table);
+    select.columns = select
+        .columns
+        .into_iter()
+        .map(|column| folder.fold_column(column))
+        .collect();
+    select.where_clause = select
+        .where_clause
+        .map(|where_clause| folder.fold_where_clause(where_clause));
+    select.order_by = select
+        .order_by
+        .into_iter()
+        .map(|order_by| folder.fold_order_by(order_by))
+        .collect();
+    select.soft_delete = select.soft_delete.map(|column| folder.fold_column(column));
+    select
+}
+
+pub fn fold_insert<F: Fold + ?Sized>(folder: &mut F, mut insert: InsertQuery) -> InsertQuery {
+    insert.table = folder.fold_table(insert.table);
+    insert.columns = insert
+        .columns
+        .into_iter()
+        .map(|column| folder.fold_column(column))
+        .collect();
+    insert.values = insert
+        .values
+        .into_iter()
+        .map(|record| {
+            record
+                .into_iter()
+                .map(|value| folder.fold_value(value))
+                .collect()
+        })
+        .collect();
+    insert
+}
+
+pub fn fold_update<F: Fold + ?Sized>(folder: &mut F, mut update: UpdateQuery) -> UpdateQuery {
+    update.table = folder.fold_table(update.table);
+    update.joins = update
+        .joins
+        .into_iter()
+        .map(|join| folder.fold_join(join))
+        .collect();
+    for (column, assignment) in std::mem::take(&mut update.fields)
+        .into_iter()
+        .map(|(column, value)| (column, Assignment::Value(value)))
+        .chain(std::mem::take(&mut update.assignments))
+    {
+        let (column, assignment) = folder.fold_assignment(column, assignment);
+        update.assign(column, assignment);
+    }
+    update.where_clause = update
+        .where_clause
+        .map(|where_clause| folder.fold_where_clause(where_clause));
+    update.order_by = update
+        .order_by
+        .into_iter()
+        .map(|order_by| folder.fold_order_by(order_by))
+        .collect();
+    update.limit_key = update.limit_key.map(|key| folder.fold_column(key));
+    update.lock = update.lock.map(|lock| match lock {
+        OptimisticLock::Counter { column, expected } => OptimisticLock::Counter {
+            column: folder.fold_column(column),
+            expected,
+        },
+        OptimisticLock::Timestamp {
+            column,
+            expected,
+            next,
+        } => OptimisticLock::Timestamp {
+            column: folder.fold_column(column),
+            expected: folder.fold_value(expected),
+            next: folder.fold_value(next),
+        },
+    });
+    update
+}
+
+pub fn fold_delete<F: Fold + ?Sized>(folder: &mut F, mut delete: DeleteQuery) -> DeleteQuery {
+    delete.table = folder.fold_table(delete.table);
+    delete.joins = delete
+        .joins
+        .into_iter()
+        .map(|join| folder.fold_join(join))
+        .collect();
+    delete.where_clause = delete
+        .where_clause
+        .map(|where_clause| folder.fold_where_clause(where_clause));
+    delete.order_by = delete
+        .order_by
+        .into_iter()
+        .map(|order_by| folder.fold_order_by(order_by))
+        .collect();
+    delete.limit_key = delete.limit_key.map(|key| folder.fold_column(key));
+    delete.soft_delete = delete.soft_delete.map(|column| folder.fold_column(column));
+    delete.soft_delete_fields = delete
+        .soft_delete_fields
+        .into_iter()
+        .map(|(column, assignment)| folder.fold_assignment(column, assignment))
+        .collect();
+    delete
+}
+
+pub fn fold_create_table<F: Fold + ?Sized>(
+    folder: &mut F,
+    mut create_table: CreateTableQuery,
+) -> CreateTableQuery {
+    create_table.table = folder.fold_table(create_table.table);
+    create_table.columns = create_table
+        .columns
+        .into_iter()
+        .map(|column| fold_column_def(folder, column))
+        .collect();
+    create_table.constraints = create_table
+        .constraints
+        .into_iter()
+        .map(|(name, constraint)| (name, fold_table_constraint(folder, constraint)))
+        .collect();
+    create_table
+}
+
+pub fn fold_alter_table<F: Fold + ?Sized>(
+    folder: &mut F,
+    mut alter_table: AlterTableQuery,
+) -> AlterTableQuery {
+    alter_table.table = folder.fold_table(alter_table.table);
+    alter_table.operations = alter_table
+        .operations
+        .into_iter()
+        .map(|operation| match operation {
+            AlterOperation::AddColumn(column) => {
+                AlterOperation::AddColumn(fold_column_def(folder, column))
+            }
+            AlterOperation::DropColumn(column) => {
+                AlterOperation::DropColumn(folder.fold_column(column))
+            }
+            AlterOperation::RenameColumn(from, to) => {
+                AlterOperation::RenameColumn(folder.fold_column(from), folder.fold_column(to))
+            }
+            AlterOperation::ModifyColumn(column) => {
+                AlterOperation::ModifyColumn(fold_column_def(folder, column))
+            }
+            AlterOperation::ChangeColumnType(column, column_type) => {
+                AlterOperation::ChangeColumnType(folder.fold_column(column), column_type)
+            }
+            AlterOperation::SetDefault(column, default) => {
+                AlterOperation::SetDefault(folder.fold_column(column), default)
+            }
+            AlterOperation::DropDefault(column) => {
+                AlterOperation::DropDefault(folder.fold_column(column))
+            }
+            AlterOperation::SetNotNull(column) => {
+                AlterOperation::SetNotNull(folder.fold_column(column))
+            }
+            AlterOperation::DropNotNull(column) => {
+                AlterOperation::DropNotNull(folder.fold_column(column))
+            }
+            AlterOperation::AddConstraint(name, constraint) => {
+                AlterOperation::AddConstraint(name, fold_table_constraint(folder, constraint))
+            }
+            AlterOperation::DropConstraint(name) => AlterOperation::DropConstraint(name),
+            AlterOperation::RenameTable(table) => {
+                AlterOperation::RenameTable(folder.fold_table(table))
+            }
+        })
+        .collect();
+    alter_table.current = alter_table
+        .current
+        .map(|current| folder.fold_create_table(current));
+    alter_table.indexes = alter_table
+        .indexes
+        .into_iter()
+        .map(|index| folder.fold_create_index(index))
+        .collect();
+    alter_table.triggers = alter_table
+        .triggers
+        .into_iter()
+        .map(|trigger| folder.fold_value(trigger))
+        .collect();
+    alter_table
+}
+
+pub fn fold_column_def<F: Fold + ?Sized>(folder: &mut F, mut column: ColumnDef) -> ColumnDef {
+    column.name = folder.fold_column(column.name);
+    column.check = column.check.map(|check| folder.fold_value(check));
+    column.references = column
+        .references
+        .map(|foreign_key| fold_foreign_key(folder, foreign_key));
+    column
+}
+
+pub fn fold_table_constraint<F: Fold + ?Sized>(
+    folder: &mut F,
+    constraint: TableConstraint,
+) -> TableConstraint {
+    let mut fold_columns = |columns: Vec<&'static str>| {
+        columns
+            .into_iter()
+            .map(|column| folder.fold_column(column))
+            .collect()
+    };
+    match constraint {
+        TableConstraint::PrimaryKey(columns) => TableConstraint::PrimaryKey(fold_columns(columns)),
+        TableConstraint::Unique(columns) => TableConstraint::Unique(fold_columns(columns)),
+        TableConstraint::Check(check) => TableConstraint::Check(folder.fold_value(check)),
+        TableConstraint::ForeignKey(foreign_key) => {
+            TableConstraint::ForeignKey(fold_foreign_key(folder, foreign_key))
+        }
+    }
+}
+
+/// The referencing columns, then the referenced table and its columns.
+pub fn fold_foreign_key<F: Fold + ?Sized>(
+    folder: &mut F,
+    mut foreign_key: ForeignKey,
+) -> ForeignKey {
+    foreign_key.columns = foreign_key
+        .columns
+        .into_iter()
+        .map(|column| folder.fold_column(column))
+        .collect();
+    foreign_key.table = folder.fold_table(foreign_key.table);
+    foreign_key.references = foreign_key
+        .references
+        .into_iter()
+        .map(|column| folder.fold_column(column))
+        .collect();
+    foreign_key
+}
+
+pub fn fold_create_index<F: Fold + ?Sized>(
+    folder: &mut F,
+    mut create_index: CreateIndexQuery,
+) -> CreateIndexQuery {
+    create_index.table = folder.fold_table(create_index.table);
+    create_index.parts = create_index
+        .parts
+        .into_iter()
+        .map(|(part, desc)| match part {
+            IndexPart::Column(column) => (IndexPart::Column(folder.fold_column(column)), desc),
+            part => (part, desc),
+        })
+        .collect();
+    create_index.where_clause = create_index
+        .where_clause
+        .map(|where_clause| folder.fold_where_clause(where_clause));
+    create_index
+}
+
+pub fn fold_create_view<F: Fold + ?Sized>(
+    folder: &mut F,
+    mut create_view: CreateViewQuery,
+) -> CreateViewQuery {
+    create_view.view = folder.fold_table(create_view.view);
+    create_view.select = folder.fold_select(create_view.select);
+    create_view
+}
+
+pub fn fold_assignment<F: Fold + ?Sized>(
+    folder: &mut F,
+    column: &'static str,
+    assignment: Assignment,
+) -> (&'static str, Assignment) {
+    let assignment = match assignment {
+        Assignment::Value(value) => Assignment::Value(folder.fold_value(value)),
+        Assignment::Expr(expr) => Assignment::Expr(folder.fold_value(expr)),
+        Assignment::Increment(by) => Assignment::Increment(folder.fold_value(by)),
+        Assignment::Decrement(by) => Assignment::Decrement(folder.fold_value(by)),
+        Assignment::Column(other) => Assignment::Column(folder.fold_column(other)),
+        assignment => assignment,
+    };
+    (folder.fold_column(column), assignment)
+}
+
+/// Folded groups are flattened again, so a condition folded into an `And` inside
+/// an `And` does not nest.
+pub fn fold_where_clause<F: Fold + ?Sized>(
+    folder: &mut F,
+    where_clause: WhereClause,
+) -> WhereClause {
+    match where_clause {
+        WhereClause::And(clauses) => clauses
+            .into_iter()
+            .map(|clause| folder.fold_where_clause(clause))
+            .fold(WhereClause::And(Vec::new()), WhereClause::and),
+        WhereClause::Or(clauses) => clauses
+            .into_iter()
+            .map(|clause| folder.fold_where_clause(clause))
+            .fold(WhereClause::Or(Vec::new()), WhereClause::or),
+        WhereClause::Not(clause) => !folder.fold_where_clause(*clause),
+        WhereClause::Condition(condition) => {
+            WhereClause::Condition(folder.fold_where_condition(condition))
+        }
+    }
+}
+
+pub fn fold_where_condition<F: Fold + ?Sized>(
+    folder: &mut F,
+    condition: WhereCondition,
+) -> WhereCondition {
+    match condition {
+        WhereCondition::Equals(field, value) => {
+            WhereCondition::Equals(folder.fold_column(field), folder.fold_value(value))
+        }
+        WhereCondition::NotEquals(field, value) => {
+            WhereCondition::NotEquals(folder.fold_column(field), folder.fold_value(value))
+        }
+        WhereCondition::GreaterThan(field, value) => {
+            WhereCondition::GreaterThan(folder.fold_column(field), folder.fold_value(value))
+        }
+        WhereCondition::GreaterThanOrEquals(field, value) => {
+            WhereCondition::GreaterThanOrEquals(folder.fold_column(field), folder.fold_value(value))
+        }
+        WhereCondition::LessThan(field, value) => {
+            WhereCondition::LessThan(folder.fold_column(field), folder.fold_value(value))
+        }
+        WhereCondition::LessThanOrEquals(field, value) => {
+            WhereCondition::LessThanOrEquals(folder.fold_column(field), folder.fold_value(value))
+        }
+        WhereCondition::Like(field, value) => {
+            WhereCondition::Like(folder.fold_column(field), folder.fold_value(value))
+        }
+        WhereCondition::NotLike(field, value) => {
+            WhereCondition::NotLike(folder.fold_column(field), folder.fold_value(value))
+        }
+        WhereCondition::In(field, values) => WhereCondition::In(
+            folder.fold_column(field),
+            values
+                .into_iter()
+                .map(|value| folder.fold_value(value))
+                .collect(),
+        ),
+        WhereCondition::NotIn(field, values) => WhereCondition::NotIn(
+            folder.fold_column(field),
+            values
+                .into_iter()
+                .map(|value| folder.fold_value(value))
+                .collect(),
+        ),
+        WhereCondition::Between(field, min, max) => WhereCondition::Between(
+            folder.fold_column(field),
+            folder.fold_value(min),
+            folder.fold_value(max),
+        ),
+        WhereCondition::NotBetween(field, min, max) => WhereCondition::NotBetween(
+            folder.fold_column(field),
+            folder.fold_value(min),
+            folder.fold_value(max),
+        ),
+        WhereCondition::IsNull(field) => WhereCondition::IsNull(folder.fold_column(field)),
+        WhereCondition::IsNotNull(field) => WhereCondition::IsNotNull(folder.fold_column(field)),
+        WhereCondition::RawCondition(condition) => {
+            WhereCondition::RawCondition(folder.fold_value(condition))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_table::ColumnType;
+    use crate::SqlBuilder;
+
+    /// Renames `name` to `full_name` and masks string literals.
+    struct Redact;
+
+    impl Fold for Redact {
+        fn fold_column(&mut self, column: &'static str) -> &'static str {
+            match column {
+                "name" => "full_name",
+                column => column,
+            }
+        }
+
+        fn fold_value(&mut self, value: String) -> String {
+            match value.starts_with('\'') {
+                true => "'***'".to_string(),
+                false => value,
+            }
+        }
+    }
+
+    #[test]
+    fn test_fold_select() {
+        let mut select = SelectQuery::new("users");
+        select
+            .add_columns(&["id", "name"])
+            .where_clause(WhereClause::equals("name", "Jack").or_in("id", vec![1, 2]))
+            .order_by("name");
+        assert_eq!(
+            Redact.fold_select(select).build().unwrap(),
+            "SELECT id, full_name FROM users WHERE full_name = '***' OR id IN (1, 2) ORDER BY full_name ASC"
+        );
+    }
+
+    #[test]
+    fn test_fold_insert_update() {
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_column("id")
+            .add_column("name")
+            .add_record_raw(&["1".to_string(), "'Jack'".to_string()])
+            .unwrap();
+        assert_eq!(
+            Redact.fold_insert(insert).build().unwrap(),
+            "INSERT INTO users (id, full_name) VALUES (1, '***')"
+        );
+        let mut update = UpdateQuery::new("users");
+        update
+            .set_field("name", &"Jack")
+            .add_where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            Redact.fold_update(update).build().unwrap(),
+            "UPDATE users SET full_name = '***' WHERE id = 1"
+        );
+    }
+
+    /// Scopes every where clause to a tenant.
+    struct Tenant;
+
+    impl Fold for Tenant {
+        fn fold_where_clause(&mut self, where_clause: WhereClause) -> WhereClause {
+            where_clause.and_equals("tenant_id", 7)
+        }
+    }
+
+    #[test]
+    fn test_fold_where_clause() {
+        let delete = DeleteQuery::new("users").where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            Tenant.fold_delete(delete).build().unwrap(),
+            "DELETE FROM users WHERE id = 1 AND tenant_id = 7"
+        );
+    }
+
+    /// Moves the tables into an `app_` namespace.
+    struct Namespace;
+
+    impl Fold for Namespace {
+        fn fold_table(&mut self, table: &'static str) -> &'static str {
+            match table {
+                "users" => "app_users",
+                "teams" => "app_teams",
+                table => table,
+            }
+        }
+    }
+
+    #[test]
+    fn test_fold_create_and_alter_table() {
+        let create_table = CreateTableQuery::new("users")
+            .column(ColumnDef::new("id", ColumnType::BigInteger).primary_key())
+            .column(ColumnDef::new("name", ColumnType::Text))
+            .column(
+                ColumnDef::new("team_id", ColumnType::BigInteger).references("teams", "id", None),
+            );
+        assert_eq!(
+            Namespace.fold_create_table(create_table).build().unwrap(),
+            "CREATE TABLE app_users (id BIGINT PRIMARY KEY, name TEXT, team_id BIGINT, FOREIGN KEY (team_id) REFERENCES app_teams (id))"
+        );
+        let alter_table = AlterTableQuery::new("users")
+            .rename_column("name", "nickname")
+            .add_constraint(
+                "users_team",
+                TableConstraint::ForeignKey(ForeignKey::new(&["team_id"], "teams", &["id"])),
+            );
+        assert_eq!(
+            Redact.fold_alter_table(Namespace.fold_alter_table(alter_table)).build().unwrap(),
+            "ALTER TABLE app_users RENAME COLUMN full_name TO nickname; ALTER TABLE app_users ADD CONSTRAINT users_team FOREIGN KEY (team_id) REFERENCES app_teams (id)"
+        );
+    }
+}
@@ -1,11 +1,12 @@
+use crate::schema::{validate_value, Schema, Validate};
 use crate::Result;
 use crate::{error, SqlBuilder};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct InsertQuery {
-    table: &'static str,
-    columns: Vec<&'static str>,
-    values: Vec<String>,
+    pub(crate) table: &'static str,
+    pub(crate) columns: Vec<&'static str>,
+    pub(crate) values: Vec<Vec<String>>,
 }
 
 impl InsertQuery {
@@ -40,8 +41,7 @@ impl InsertQuery {
                 record.len(),
             ));
         }
-        let values = record.join(", ");
-        self.values.push(format!("({})", values));
+        self.values.push(record.to_vec());
         Ok(self)
     }
 
@@ -53,8 +53,7 @@ impl InsertQuery {
                 vec.len(),
             ));
         }
-        let values = record.as_insert_record().join(", ");
-        self.values.push(format!("({})", values));
+        self.values.push(vec);
         Ok(self)
     }
 }
@@ -69,7 +68,12 @@ impl SqlBuilder for InsertQuery {
             return Err(error::Error::NoInsertValues);
         }
         let columns = self.columns.join(", ");
-        let values = self.values.join(", ");
+        let values = self
+            .values
+            .iter()
+            .map(|record| format!("({})", record.join(", ")))
+            .collect::<Vec<String>>()
+            .join(", ");
         Ok(format!(
             "INSERT INTO {} ({}) VALUES {}",
             self.table, columns, values
@@ -81,7 +85,31 @@ pub trait AsInsertRecord {
     fn as_insert_record(&self) -> Vec<String>;
 }
 
+impl Validate for InsertQuery {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        let table = schema.require_table(self.table)?;
+        let columns = self
+            .columns
+            .iter()
+            .map(|column| table.require_column(column))
+            .collect::<Result<Vec<_>>>()?;
+        for column in &table.columns {
+            let generated = column.default.is_some() || column.auto_increment;
+            if column.not_null && !generated && !self.columns.contains(&column.name) {
+                return Err(error::Error::MissingNotNullColumn(self.table, column.name));
+            }
+        }
+        for record in &self.values {
+            for (column, value) in columns.iter().zip(record) {
+                validate_value(self.table, column, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::useless_vec)]
 mod test {
     use super::super::repr::ToSqlRepr;
     use super::*;
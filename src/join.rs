@@ -0,0 +1,52 @@
+use crate::where_clause::WhereClause;
+use crate::Result;
+use crate::SqlBuilder;
+
+/// An inner join used by multi-table statements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Join {
+    pub table: &'static str,
+    pub on: String,
+}
+
+impl Join {
+    pub fn new(table: &'static str, on: String) -> Self {
+        Self { table, on }
+    }
+}
+
+impl SqlBuilder for Join {
+    fn build(&self) -> Result<String> {
+        Ok(format!("JOIN {} ON {}", self.table, self.on))
+    }
+}
+
+/// `JOIN b ON ... JOIN c ON ...`, used by the MySQL forms.
+pub(crate) fn join_clauses(joins: &[Join]) -> Result<String> {
+    Ok(joins
+        .iter()
+        .map(|join| join.build())
+        .collect::<Result<Vec<String>>>()?
+        .join(" "))
+}
+
+/// `b, c`, used by the `FROM` / `USING` forms.
+pub(crate) fn join_tables(joins: &[Join]) -> String {
+    joins
+        .iter()
+        .map(|join| join.table)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+/// Moves the join conditions into the where clause, used by the `FROM` / `USING` forms.
+pub(crate) fn join_conditions(
+    joins: &[Join],
+    where_clause: Option<WhereClause>,
+) -> Option<WhereClause> {
+    joins
+        .iter()
+        .map(|join| WhereClause::raw_condition(join.on.clone()))
+        .chain(where_clause)
+        .reduce(|left, right| left.and(right))
+}
@@ -0,0 +1,164 @@
+use crate::error::Error;
+use crate::evaluate::{column, Row};
+use crate::select::{Order, OrderBy};
+use crate::where_clause::{WhereClause, WhereCondition};
+use crate::Result;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde_json::Value;
+
+/// The ordering values of the last row of a page, the next page starts after it.
+///
+/// Cursors are handed to clients as opaque strings, `encode` and `decode` convert
+/// them. They record the `ORDER BY` they were created for and only apply to it.
+/// Cursors are not signed, a client can forge one, so their values are treated as
+/// untrusted input.
+/// Only numbers, strings and booleans are allowed, keyset pagination needs NOT NULL
+/// ordering columns, the last of them unique. Strings with backslashes or control
+/// characters are rejected, MySQL would read the backslashes as escapes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+    columns: Vec<String>,
+    values: Vec<Value>,
+}
+
+impl Cursor {
+    /// A cursor of `values`, one for each column of `order_by`.
+    pub fn new(order_by: &[OrderBy], values: Vec<Value>) -> Result<Self> {
+        Self::with_order(order_signature(order_by), values)
+    }
+
+    /// The values of the ordering columns in `row`.
+    pub fn from_row<R: Row>(order_by: &[OrderBy], row: &R) -> Result<Self> {
+        let values = keys(order_by)
+            .map(|(field, _)| column(row, field).cloned())
+            .collect::<Result<Vec<Value>>>()?;
+        Self::new(order_by, values)
+    }
+
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    pub fn encode(&self) -> String {
+        let json = serde_json::json!([self.columns, self.values]);
+        URL_SAFE_NO_PAD.encode(json.to_string())
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let json = URL_SAFE_NO_PAD
+            .decode(cursor)
+            .map_err(|_| Error::InvalidCursor)?;
+        match serde_json::from_slice(&json) {
+            Ok((columns, values)) => Self::with_order(columns, values),
+            Err(_) => Err(Error::InvalidCursor),
+        }
+    }
+
+    fn with_order(columns: Vec<String>, values: Vec<Value>) -> Result<Self> {
+        let safe = |value: &Value| match value {
+            Value::String(text) => !text.chars().any(|c| c == '\\' || c.is_control()),
+            Value::Number(_) | Value::Bool(_) => true,
+            _ => false,
+        };
+        if !values.iter().all(safe) {
+            return Err(Error::InvalidCursor);
+        }
+        if columns.len() != values.len() {
+            return Err(Error::CursorMismatch(values.len(), columns.len()));
+        }
+        Ok(Self { columns, values })
+    }
+}
+
+/// The ordering columns and their directions, e.g. `created_at DESC`.
+fn order_signature(order_by: &[OrderBy]) -> Vec<String> {
+    keys(order_by)
+        .map(|(field, order)| match order {
+            Order::Asc => format!("{} ASC", field),
+            Order::Desc => format!("{} DESC", field),
+        })
+        .collect()
+}
+
+/// The condition selecting rows after `cursor` in the order of `order_by`:
+/// `(a, b) > (1, 2)` when all columns share a direction, otherwise
+/// `a > 1 OR (a = 1 AND b < 2)`.
+pub(crate) fn keyset_condition(order_by: &[OrderBy], cursor: &Cursor) -> Result<WhereClause> {
+    let keys = keys(order_by).collect::<Vec<_>>();
+    if keys.is_empty() {
+        return Err(Error::NoOrderByClause);
+    }
+    if keys.len() != cursor.values.len() {
+        return Err(Error::CursorMismatch(cursor.values.len(), keys.len()));
+    }
+    if order_signature(order_by) != cursor.columns {
+        return Err(Error::CursorOrderMismatch);
+    }
+    let values = cursor.values.iter().map(literal).collect::<Vec<String>>();
+    let order = keys[0].1;
+    if keys.len() > 1 && keys.iter().all(|(_, other)| *other == order) {
+        let columns = keys.iter().map(|(field, _)| *field).collect::<Vec<_>>();
+        return Ok(WhereClause::raw_condition(format!(
+            "({}) {} ({})",
+            columns.join(", "),
+            operator(order),
+            values.join(", ")
+        )));
+    }
+    let clause = (0..keys.len())
+        .map(|i| {
+            let (field, order) = keys[i];
+            let after = match order {
+                Order::Asc => WhereCondition::GreaterThan(field, values[i].clone()),
+                Order::Desc => WhereCondition::LessThan(field, values[i].clone()),
+            };
+            keys[..i]
+                .iter()
+                .zip(&values)
+                .map(|((field, _), value)| {
+                    WhereClause::Condition(WhereCondition::Equals(field, value.clone()))
+                })
+                .chain(std::iter::once(WhereClause::Condition(after)))
+                .reduce(WhereClause::and)
+                .unwrap()
+        })
+        .reduce(WhereClause::or)
+        .unwrap();
+    Ok(clause)
+}
+
+/// The ordering columns with the direction the database sorts them in. A group's
+/// direction is rendered after its last column only, `a, b DESC`, so the columns
+/// before it ascend.
+fn keys(order_by: &[OrderBy]) -> impl Iterator<Item = (&'static str, Order)> + '_ {
+    order_by.iter().flat_map(|order_by| {
+        let last = order_by.columns.len().saturating_sub(1);
+        order_by.columns.iter().enumerate().map(move |(i, column)| {
+            let order = if i == last {
+                order_by.order
+            } else {
+                Order::Asc
+            };
+            (*column, order)
+        })
+    })
+}
+
+fn operator(order: Order) -> &'static str {
+    match order {
+        Order::Asc => ">",
+        Order::Desc => "<",
+    }
+}
+
+/// Cursors come from clients, strings are escaped rather than trusted. Backslashes
+/// never get here, see `Cursor`.
+fn literal(value: &Value) -> String {
+    match value {
+        Value::String(text) => format!("'{}'", text.replace('\'', "''")),
+        Value::Bool(true) => "TRUE".to_string(),
+        Value::Bool(false) => "FALSE".to_string(),
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::HashSet;
+use std::sync::{Mutex, OnceLock};
+
+use crate::error::Error;
+use crate::Result;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TokenKind {
+    Word,
+    QuotedIdent,
+    Number,
+    String,
+    Symbol,
+}
+
+/// A token borrowing from the sql text. Tokens of `'static` text can be used
+/// directly as the `&'static str` names the builders expect, others are interned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub offset: usize,
+}
+
+impl<'a> Token<'a> {
+    pub fn is_keyword(&self, keyword: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(keyword)
+    }
+
+    pub fn is_symbol(&self, symbol: &str) -> bool {
+        self.kind == TokenKind::Symbol && self.text == symbol
+    }
+
+    pub fn ident(&self) -> Option<&'a str> {
+        match self.kind {
+            TokenKind::Word | TokenKind::QuotedIdent => Some(self.text),
+            _ => None,
+        }
+    }
+}
+
+const SYMBOLS: [&str; 22] = [
+    "<=", ">=", "!=", "<>", "||", "::", "(", ")", ",", ";", ".", "=", "<", ">", "+", "-", "*", "/",
+    "%", "?", "[", "]",
+];
+
+pub(crate) fn tokenize(sql: &str) -> Result<Vec<Token<'_>>> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c.is_ascii_whitespace() {
+            i += 1;
+        } else if sql[i..].starts_with("--") {
+            i = sql[i..].find('\n').map_or(sql.len(), |end| i + end);
+        } else if sql[i..].starts_with("/*") {
+            i = match sql[i + 2..].find("*/") {
+                Some(end) => i + 2 + end + 2,
+                None => return Err(Error::InvalidSql("unterminated comment", i)),
+            };
+        } else if c == b'\'' {
+            let start = i;
+            i += 1;
+            loop {
+                match bytes.get(i) {
+                    Some(b'\'') if bytes.get(i + 1) == Some(&b'\'') => i += 2,
+                    Some(b'\'') => break,
+                    Some(_) => i += 1,
+                    None => return Err(Error::InvalidSql("unterminated string", start)),
+                }
+            }
+            i += 1;
+            tokens.push(token(TokenKind::String, sql, start, i));
+        } else if c == b'"' || c == b'`' {
+            let start = i + 1;
+            i = match sql[start..].find(c as char) {
+                Some(end) => start + end,
+                None => {
+                    return Err(Error::InvalidSql(
+                        "unterminated quoted identifier",
+                        start - 1,
+                    ))
+                }
+            };
+            tokens.push(token(TokenKind::QuotedIdent, sql, start, i));
+            i += 1;
+        } else if c.is_ascii_digit() {
+            let start = i;
+            while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+                i += 1;
+            }
+            tokens.push(token(TokenKind::Number, sql, start, i));
+        } else if c.is_ascii_alphabetic() || c == b'_' || c == b'$' || c >= 0x80 {
+            let start = i;
+            while i < bytes.len()
+                && (bytes[i].is_ascii_alphanumeric()
+                    || bytes[i] == b'_'
+                    || bytes[i] == b'$'
+                    || bytes[i] >= 0x80)
+            {
+                i += 1;
+            }
+            tokens.push(token(TokenKind::Word, sql, start, i));
+        } else {
+            match SYMBOLS.iter().find(|symbol| sql[i..].starts_with(*symbol)) {
+                Some(symbol) => {
+                    tokens.push(token(TokenKind::Symbol, sql, i, i + symbol.len()));
+                    i += symbol.len();
+                }
+                None => return Err(Error::InvalidSql("unexpected character", i)),
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn token(kind: TokenKind, sql: &str, start: usize, end: usize) -> Token<'_> {
+    Token {
+        kind,
+        text: &sql[start..end],
+        offset: start,
+    }
+}
+
+/// A `'static` copy of a name or excerpt of sql text, each distinct text is
+/// allocated once so parsing the same text again does not grow memory.
+pub(crate) fn intern(text: &str) -> &'static str {
+    static INTERNED: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+    let mut interned = INTERNED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    match interned.get(text) {
+        Some(text) => text,
+        None => {
+            let text: &'static str = Box::leak(text.into());
+            interned.insert(text);
+            text
+        }
+    }
+}
+
+/// Walks a token stream, shared by the sql text parsers.
+pub(crate) struct Cursor<'a> {
+    pub sql: &'a str,
+    pub tokens: Vec<Token<'a>>,
+    pub pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(sql: &'a str) -> Result<Self> {
+        Ok(Self {
+            sql,
+            tokens: tokenize(sql)?,
+            pos: 0,
+        })
+    }
+
+    pub fn peek(&self) -> Option<Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    pub fn peek_nth(&self, n: usize) -> Option<Token<'a>> {
+        self.tokens.get(self.pos + n).copied()
+    }
+
+    pub fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.peek();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    /// The offset of the next token, the length of the sql at the end.
+    pub fn offset(&self) -> usize {
+        self.peek().map_or(self.sql.len(), |token| token.offset)
+    }
+
+    /// Parse errors carry a fixed message, excerpts of the sql would have to be
+    /// leaked for the `&'static str`.
+    pub fn error(&self) -> Error {
+        match self.peek() {
+            Some(_) => Error::InvalidSql("unexpected token", self.offset()),
+            None => Error::InvalidSql("unexpected end of input", self.offset()),
+        }
+    }
+
+    pub fn peek_keyword(&self, keyword: &str) -> bool {
+        self.peek().is_some_and(|token| token.is_keyword(keyword))
+    }
+
+    pub fn peek_symbol(&self, symbol: &str) -> bool {
+        self.peek().is_some_and(|token| token.is_symbol(symbol))
+    }
+
+    /// Consumes the keywords if they all come next.
+    pub fn eat_keywords(&mut self, keywords: &[&str]) -> bool {
+        let matches = keywords.iter().enumerate().all(|(n, keyword)| {
+            self.peek_nth(n)
+                .is_some_and(|token| token.is_keyword(keyword))
+        });
+        if matches {
+            self.pos += keywords.len();
+        }
+        matches
+    }
+
+    pub fn eat_symbol(&mut self, symbol: &str) -> bool {
+        let matches = self.peek_symbol(symbol);
+        if matches {
+            self.pos += 1;
+        }
+        matches
+    }
+
+    pub fn expect_keywords(&mut self, keywords: &[&str]) -> Result<()> {
+        if self.eat_keywords(keywords) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    pub fn expect_symbol(&mut self, symbol: &str) -> Result<()> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    pub fn expect_ident(&mut self) -> Result<&'a str> {
+        match self.peek().and_then(|token| token.ident()) {
+            Some(ident) => {
+                self.pos += 1;
+                Ok(ident)
+            }
+            None => Err(self.error()),
+        }
+    }
+
+    /// The source text from token `start` up to, excluding, the current token.
+    pub fn text_since(&self, start: usize) -> &'a str {
+        self.text_between(start, self.pos)
+    }
+
+    /// The source text of tokens `start..end`.
+    pub fn text_between(&self, start: usize, end: usize) -> &'a str {
+        if start >= end {
+            return "";
+        }
+        let first = self.tokens[start];
+        let last = self.tokens[end - 1];
+        let end = last.offset + last.text.len();
+        // quoted identifiers do not include their closing quote
+        let end = match last.kind {
+            TokenKind::QuotedIdent => end + 1,
+            _ => end,
+        };
+        let begin = match first.kind {
+            TokenKind::QuotedIdent => first.offset - 1,
+            _ => first.offset,
+        };
+        &self.sql[begin..end]
+    }
+
+    /// The text inside a balanced parenthesized group, the cursor must be on its `(`.
+    pub fn group_text(&mut self) -> Result<&'a str> {
+        self.expect_symbol("(")?;
+        let start = self.pos;
+        let mut depth = 1;
+        loop {
+            match self.peek() {
+                Some(token) if token.is_symbol("(") => depth += 1,
+                Some(token) if token.is_symbol(")") => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                Some(_) => {}
+                None => return Err(self.error()),
+            }
+            self.pos += 1;
+        }
+        let text = self.text_since(start);
+        self.pos += 1;
+        Ok(text)
+    }
+
+    /// Skips a balanced parenthesized group, the cursor must be on its `(`.
+    pub fn skip_group(&mut self) -> Result<()> {
+        self.expect_symbol("(")?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(token) if token.is_symbol("(") => depth += 1,
+                Some(token) if token.is_symbol(")") => depth -= 1,
+                Some(_) => {}
+                None => return Err(self.error()),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = tokenize("SELECT `id`, name FROM users -- comment\nWHERE name = 'O''Brien' AND age >= 18.5 /* done */")
+            .unwrap();
+        let texts = tokens.iter().map(|token| token.text).collect::<Vec<&str>>();
+        assert_eq!(
+            texts,
+            vec![
+                "SELECT",
+                "id",
+                ",",
+                "name",
+                "FROM",
+                "users",
+                "WHERE",
+                "name",
+                "=",
+                "'O''Brien'",
+                "AND",
+                "age",
+                ">=",
+                "18.5"
+            ]
+        );
+        assert_eq!(tokens[1].kind, TokenKind::QuotedIdent);
+        assert_eq!(tokens[9].kind, TokenKind::String);
+    }
+
+    #[test]
+    fn test_tokenize_error() {
+        assert_eq!(
+            tokenize("SELECT 'unterminated").unwrap_err(),
+            Error::InvalidSql("unterminated string", 7)
+        );
+    }
+}
@@ -1,13 +1,32 @@
+pub mod alter_table;
+pub mod audit;
+pub mod changeset;
+pub mod create_index;
+pub mod create_table;
 pub mod delete;
+pub mod dialect;
+pub mod drop;
 pub mod error;
+pub mod evaluate;
+pub mod fold;
 pub mod insert;
+pub mod join;
+pub mod keyset;
+mod lexer;
+pub mod migration;
+pub mod parser;
 pub mod repr;
+pub mod schema;
+pub mod schema_diff;
 pub mod select;
+pub mod soft_delete;
+pub mod tenant;
+pub mod truncate;
 pub mod update;
+pub mod view;
+pub mod visit;
 pub mod where_clause;
 
-
-use anyhow;
 type Result<T> = anyhow::Result<T, crate::error::Error>;
 
 pub trait SqlBuilder {
@@ -0,0 +1,725 @@
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::create_table::{ColumnDef, ColumnType, CreateTableQuery};
+use crate::delete::DeleteQuery;
+use crate::dialect::Dialect;
+use crate::error::Error;
+use crate::insert::InsertQuery;
+use crate::repr::ToSqlRepr;
+use crate::select::SelectQuery;
+use crate::where_clause::WhereClause;
+use crate::SqlBuilder;
+
+const MIGRATIONS_TABLE: &str = "schema_migrations";
+const FOREIGN_KEYS_OFF: &str = "PRAGMA foreign_keys = OFF";
+const FOREIGN_KEYS_ON: &str = "PRAGMA foreign_keys = ON";
+// `PRAGMA foreign_key_check` as a count, it returns a row per violation
+const FOREIGN_KEY_CHECK: &str = "SELECT COUNT(*) FROM pragma_foreign_key_check";
+
+/// A numbered schema change with the statements to apply and to revert it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub version: u64,
+    pub name: String,
+    pub up: Vec<String>,
+    pub down: Vec<String>,
+}
+
+impl Migration {
+    pub fn new(version: u64, name: &str) -> Self {
+        Self {
+            version,
+            name: name.to_string(),
+            up: Vec::new(),
+            down: Vec::new(),
+        }
+    }
+
+    pub fn up<T: SqlBuilder>(mut self, statement: &T) -> crate::Result<Self> {
+        self.up.push(statement.build()?);
+        Ok(self)
+    }
+
+    pub fn down<T: SqlBuilder>(mut self, statement: &T) -> crate::Result<Self> {
+        self.down.push(statement.build()?);
+        Ok(self)
+    }
+
+    pub fn up_sql(mut self, sql: &str) -> Self {
+        self.up.push(sql.trim().to_string());
+        self
+    }
+
+    pub fn down_sql(mut self, sql: &str) -> Self {
+        self.down.push(sql.trim().to_string());
+        self
+    }
+
+    /// Reads `<version>_<name>.up.sql` and the optional `<version>_<name>.down.sql` files of `dir`.
+    pub fn from_dir<P: AsRef<Path>>(dir: P) -> io::Result<Vec<Migration>> {
+        let mut migrations = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let file_name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("");
+            let Some(stem) = file_name.strip_suffix(".up.sql") else {
+                continue;
+            };
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "migration file '{}' should be named <version>_<name>.up.sql",
+                        file_name
+                    ),
+                )
+            };
+            let (version, name) = stem.split_once('_').ok_or_else(invalid)?;
+            let version = version.parse().map_err(|_| invalid())?;
+            let mut migration = Migration::new(version, name).up_sql(&fs::read_to_string(&path)?);
+            let down = path.with_file_name(format!("{}.down.sql", stem));
+            if down.exists() {
+                migration = migration.down_sql(&fs::read_to_string(down)?);
+            }
+            migrations.push(migration);
+        }
+        Ok(migrations)
+    }
+
+    /// FNV-1a hash of the up statements, stored when the migration is applied
+    /// to detect migrations edited afterwards. Each statement is prefixed by its
+    /// length so moving text between statements changes the hash.
+    pub fn checksum(&self) -> String {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for statement in &self.up {
+            let length = (statement.len() as u64).to_le_bytes();
+            for byte in length.iter().chain(statement.as_bytes()) {
+                hash ^= *byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+        }
+        format!("{:016x}", hash)
+    }
+}
+
+/// Connection the migrations are run with. A statement passed to `execute`
+/// may hold several `;` separated statements, e.g. a SQLite table rebuild.
+pub trait MigrationExecutor {
+    type Error;
+
+    fn execute(&mut self, sql: &str) -> Result<(), Self::Error>;
+
+    fn table_exists(&mut self, table: &str) -> Result<bool, Self::Error>;
+
+    /// Runs `sql`, a select of the version and checksum columns, returning its rows.
+    fn query_applied(&mut self, sql: &str) -> Result<Vec<(u64, String)>, Self::Error>;
+
+    /// Runs `sql`, a query returning a single integer, e.g. `PRAGMA foreign_keys`.
+    fn query_integer(&mut self, sql: &str) -> Result<i64, Self::Error>;
+}
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum MigrationError<E: Display> {
+    #[error(transparent)]
+    Migration(#[from] Error),
+    #[error("executing migration statement failed: {0}")]
+    Executor(E),
+}
+
+type MigrationResult<T, E> = Result<T, MigrationError<E>>;
+
+/// Applies and reverts migrations, tracking the applied versions in a
+/// `schema_migrations` table. Each migration runs in its own transaction,
+/// MySQL commits DDL statements implicitly though.
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    migrations: Vec<Migration>,
+    dry_run: bool,
+    dialect: Dialect,
+}
+
+impl Migrator {
+    pub fn new(mut migrations: Vec<Migration>) -> crate::Result<Self> {
+        migrations.sort_by_key(|migration| migration.version);
+        if let Some(pair) = migrations
+            .windows(2)
+            .find(|pair| pair[0].version == pair[1].version)
+        {
+            return Err(Error::DuplicateMigration(pair[0].version));
+        }
+        Ok(Self {
+            migrations,
+            dry_run: false,
+            dialect: Dialect::default(),
+        })
+    }
+
+    /// Only returns the statements that would run, nothing is written to the database.
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Applies all pending migrations, returning the statements run.
+    pub fn up<E: MigrationExecutor>(
+        &self,
+        executor: &mut E,
+    ) -> MigrationResult<Vec<String>, E::Error>
+    where
+        E::Error: Display,
+    {
+        let applied = self.verify(executor)?;
+        let mut statements = Vec::new();
+        if !executor
+            .table_exists(MIGRATIONS_TABLE)
+            .map_err(MigrationError::Executor)?
+        {
+            let create_table = self.create_table()?;
+            if !self.dry_run {
+                run(executor, &create_table)?;
+            }
+            statements.push(create_table);
+        }
+        for migration in &self.migrations {
+            if applied.contains(&migration.version) {
+                continue;
+            }
+            let mut insert = InsertQuery::new(MIGRATIONS_TABLE);
+            insert
+                .add_columns(["version", "name", "checksum"].into_iter())
+                .add_record_raw(&[
+                    migration.version.to_sql_repr(),
+                    migration.name.to_sql_repr(),
+                    migration.checksum().to_sql_repr(),
+                ])?;
+            statements.extend(self.transaction(
+                executor,
+                migration.version,
+                &migration.up,
+                insert.build()?,
+            )?);
+        }
+        Ok(statements)
+    }
+
+    /// Reverts the applied migrations above `version`, newest first, returning the statements run.
+    pub fn down_to<E: MigrationExecutor>(
+        &self,
+        executor: &mut E,
+        version: u64,
+    ) -> MigrationResult<Vec<String>, E::Error>
+    where
+        E::Error: Display,
+    {
+        let applied = self.verify(executor)?;
+        let mut statements = Vec::new();
+        for migration in self.migrations.iter().rev() {
+            if migration.version <= version || !applied.contains(&migration.version) {
+                continue;
+            }
+            if migration.down.is_empty() {
+                return Err(Error::NoDownMigration(migration.version).into());
+            }
+            let delete = DeleteQuery::new(MIGRATIONS_TABLE)
+                .where_clause(WhereClause::equals("version", migration.version));
+            statements.extend(self.transaction(
+                executor,
+                migration.version,
+                &migration.down,
+                delete.build()?,
+            )?);
+        }
+        Ok(statements)
+    }
+
+    /// The versions not applied yet.
+    pub fn pending<E: MigrationExecutor>(
+        &self,
+        executor: &mut E,
+    ) -> MigrationResult<Vec<u64>, E::Error>
+    where
+        E::Error: Display,
+    {
+        let applied = self.verify(executor)?;
+        Ok(self
+            .migrations
+            .iter()
+            .map(|migration| migration.version)
+            .filter(|version| !applied.contains(version))
+            .collect())
+    }
+
+    /// Checks every applied migration is still defined with the same checksum,
+    /// returning the applied versions.
+    pub fn verify<E: MigrationExecutor>(
+        &self,
+        executor: &mut E,
+    ) -> MigrationResult<Vec<u64>, E::Error>
+    where
+        E::Error: Display,
+    {
+        if !executor
+            .table_exists(MIGRATIONS_TABLE)
+            .map_err(MigrationError::Executor)?
+        {
+            return Ok(Vec::new());
+        }
+        let mut select = SelectQuery::new(MIGRATIONS_TABLE);
+        select
+            .add_columns(&["version", "checksum"])
+            .order_by("version");
+        let applied = executor
+            .query_applied(&select.build()?)
+            .map_err(MigrationError::Executor)?;
+        for (version, checksum) in &applied {
+            match self
+                .migrations
+                .iter()
+                .find(|migration| migration.version == *version)
+            {
+                Some(migration) if migration.checksum() == *checksum => {}
+                Some(_) => return Err(Error::ChecksumMismatch(*version).into()),
+                None => return Err(Error::UnknownMigration(*version).into()),
+            }
+        }
+        Ok(applied.into_iter().map(|(version, _)| version).collect())
+    }
+
+    fn create_table(&self) -> crate::Result<String> {
+        CreateTableQuery::new(MIGRATIONS_TABLE)
+            .column(ColumnDef::new("version", ColumnType::BigInteger).primary_key())
+            .column(ColumnDef::new("name", ColumnType::Varchar(255)).not_null())
+            .column(ColumnDef::new("checksum", ColumnType::Varchar(16)).not_null())
+            .column(
+                ColumnDef::new("applied_at", ColumnType::Timestamp)
+                    .not_null()
+                    .default_now(),
+            )
+            .if_not_exists()
+            .dialect(self.dialect)
+            .build()
+    }
+
+    /// Runs `statements` and the tracking statement in one transaction.
+    ///
+    /// SQLite ignores `PRAGMA foreign_keys` inside a transaction, so when a statement
+    /// turns enabled foreign keys off, e.g. a table rebuild, they are turned off around
+    /// the transaction instead. Otherwise dropping the old table would cascade. The
+    /// foreign keys are checked before committing and turned back on afterwards.
+    fn transaction<E: MigrationExecutor>(
+        &self,
+        executor: &mut E,
+        version: u64,
+        statements: &[String],
+        tracking: String,
+    ) -> MigrationResult<Vec<String>, E::Error>
+    where
+        E::Error: Display,
+    {
+        let begin = match self.dialect {
+            Dialect::MySql => "START TRANSACTION",
+            Dialect::Postgres | Dialect::Sqlite => "BEGIN",
+        };
+        let foreign_keys_off = self.dialect == Dialect::Sqlite
+            && statements
+                .iter()
+                .any(|statement| statement.contains(FOREIGN_KEYS_OFF))
+            && executor
+                .query_integer("PRAGMA foreign_keys")
+                .map_err(MigrationError::Executor)?
+                == 1;
+        let mut script = Vec::new();
+        if foreign_keys_off {
+            script.push(FOREIGN_KEYS_OFF.to_string());
+        }
+        script.push(begin.to_string());
+        script.extend(statements.iter().cloned());
+        script.push(tracking);
+        if foreign_keys_off {
+            script.push(FOREIGN_KEY_CHECK.to_string());
+        }
+        script.push("COMMIT".to_string());
+        if foreign_keys_off {
+            script.push(FOREIGN_KEYS_ON.to_string());
+        }
+        if self.dry_run {
+            return Ok(script);
+        }
+        for statement in &script {
+            let result = match statement.as_str() {
+                FOREIGN_KEY_CHECK => check_foreign_keys(executor, version),
+                statement => run(executor, statement),
+            };
+            if let Err(error) = result {
+                // best effort, the original error is what matters
+                let _ = executor.execute("ROLLBACK");
+                if foreign_keys_off {
+                    let _ = executor.execute(FOREIGN_KEYS_ON);
+                }
+                return Err(error);
+            }
+        }
+        Ok(script)
+    }
+}
+
+fn run<E: MigrationExecutor>(executor: &mut E, sql: &str) -> MigrationResult<(), E::Error>
+where
+    E::Error: Display,
+{
+    executor.execute(sql).map_err(MigrationError::Executor)
+}
+
+fn check_foreign_keys<E: MigrationExecutor>(
+    executor: &mut E,
+    version: u64,
+) -> MigrationResult<(), E::Error>
+where
+    E::Error: Display,
+{
+    match executor
+        .query_integer(FOREIGN_KEY_CHECK)
+        .map_err(MigrationError::Executor)?
+    {
+        0 => Ok(()),
+        _ => Err(Error::ForeignKeyViolation(version).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alter_table::AlterTableQuery;
+    use crate::create_table::ColumnDef;
+    use crate::drop::DropTable;
+    use rusqlite::Connection;
+
+    impl MigrationExecutor for Connection {
+        type Error = rusqlite::Error;
+
+        fn execute(&mut self, sql: &str) -> Result<(), Self::Error> {
+            self.execute_batch(sql)
+        }
+
+        fn table_exists(&mut self, table: &str) -> Result<bool, Self::Error> {
+            self.query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+                [table],
+                |row| row.get::<_, i64>(0),
+            )
+            .map(|count| count > 0)
+        }
+
+        fn query_applied(&mut self, sql: &str) -> Result<Vec<(u64, String)>, Self::Error> {
+            let mut statement = self.prepare(sql)?;
+            let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect()
+        }
+
+        fn query_integer(&mut self, sql: &str) -> Result<i64, Self::Error> {
+            self.query_row(sql, [], |row| row.get(0))
+        }
+    }
+
+    fn migrations() -> Vec<Migration> {
+        let users = CreateTableQuery::new("users")
+            .column(
+                ColumnDef::new("id", ColumnType::Integer)
+                    .primary_key()
+                    .auto_increment(),
+            )
+            .column(ColumnDef::new("name", ColumnType::Text).not_null())
+            .dialect(Dialect::Sqlite);
+        vec![
+            Migration::new(2, "add_users_email")
+                .up(&AlterTableQuery::new("users")
+                    .add_column(ColumnDef::new("email", ColumnType::Text))
+                    .dialect(Dialect::Sqlite))
+                .unwrap()
+                .down(
+                    &AlterTableQuery::new("users")
+                        .drop_column("email")
+                        .dialect(Dialect::Sqlite),
+                )
+                .unwrap(),
+            Migration::new(1, "create_users")
+                .up(&users)
+                .unwrap()
+                .down(&DropTable::new("users").confirm())
+                .unwrap(),
+        ]
+    }
+
+    fn columns(connection: &Connection) -> Vec<String> {
+        let mut statement = connection
+            .prepare("SELECT name FROM pragma_table_info('users')")
+            .unwrap();
+        let rows = statement.query_map([], |row| row.get(0)).unwrap();
+        rows.collect::<rusqlite::Result<_>>().unwrap()
+    }
+
+    #[test]
+    fn test_up_and_down() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        let migrator = Migrator::new(migrations())
+            .unwrap()
+            .dialect(Dialect::Sqlite);
+        assert_eq!(migrator.pending(&mut connection), Ok(vec![1, 2]));
+
+        let statements = migrator.up(&mut connection).unwrap();
+        assert_eq!(statements.len(), 9);
+        assert_eq!(columns(&connection), vec!["id", "name", "email"]);
+        assert_eq!(migrator.pending(&mut connection), Ok(vec![]));
+        assert_eq!(migrator.up(&mut connection), Ok(vec![]));
+        connection
+            .execute_batch("INSERT INTO users (name, email) VALUES ('John', 'john@example.com')")
+            .unwrap();
+
+        migrator.down_to(&mut connection, 1).unwrap();
+        assert_eq!(columns(&connection), vec!["id", "name"]);
+        assert_eq!(migrator.pending(&mut connection), Ok(vec![2]));
+        migrator.down_to(&mut connection, 0).unwrap();
+        assert!(columns(&connection).is_empty());
+        assert_eq!(migrator.pending(&mut connection), Ok(vec![1, 2]));
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        let statements = Migrator::new(migrations())
+            .unwrap()
+            .dialect(Dialect::Sqlite)
+            .dry_run()
+            .up(&mut connection)
+            .unwrap();
+        assert_eq!(statements[0], migrations_table());
+        assert_eq!(
+            statements[1..5],
+            [
+                "BEGIN".to_string(),
+                "CREATE TABLE users (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL)"
+                    .to_string(),
+                "INSERT INTO schema_migrations (version, name, checksum) VALUES (1, 'create_users', '{}')"
+                    .replace("{}", &migrations()[1].checksum()),
+                "COMMIT".to_string(),
+            ]
+        );
+        assert!(!connection.table_exists(MIGRATIONS_TABLE).unwrap());
+        assert!(columns(&connection).is_empty());
+    }
+
+    fn migrations_table() -> String {
+        Migrator::new(Vec::new())
+            .unwrap()
+            .dialect(Dialect::Sqlite)
+            .create_table()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sqlite_rebuild_keeps_cascading_rows() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch("PRAGMA foreign_keys = ON")
+            .unwrap();
+        let users = CreateTableQuery::new("users")
+            .column(ColumnDef::new("id", ColumnType::Integer).primary_key())
+            .column(ColumnDef::new("name", ColumnType::Text))
+            .dialect(Dialect::Sqlite);
+        let migrations = vec![
+            Migration::new(1, "create_users").up(&users).unwrap(),
+            Migration::new(2, "create_orders").up_sql(
+                "CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users (id) ON DELETE CASCADE)",
+            ),
+            Migration::new(3, "require_user_names")
+                .up(&AlterTableQuery::new("users")
+                    .set_not_null("name")
+                    .rebuild_from(users.clone())
+                    .dialect(Dialect::Sqlite))
+                .unwrap(),
+        ];
+        Migrator::new(migrations[..2].to_vec())
+            .unwrap()
+            .dialect(Dialect::Sqlite)
+            .up(&mut connection)
+            .unwrap();
+        connection
+            .execute_batch(
+                "INSERT INTO users (id, name) VALUES (1, 'John'); INSERT INTO orders (id, user_id) VALUES (1, 1)",
+            )
+            .unwrap();
+
+        let statements = Migrator::new(migrations)
+            .unwrap()
+            .dialect(Dialect::Sqlite)
+            .up(&mut connection)
+            .unwrap();
+        let orders: i64 = connection
+            .query_row("SELECT COUNT(*) FROM orders", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(orders, 1);
+        let foreign_keys: i64 = connection
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(foreign_keys, 1);
+        assert_eq!(statements[0], FOREIGN_KEYS_OFF);
+        assert_eq!(statements[statements.len() - 1], FOREIGN_KEYS_ON);
+    }
+
+    fn rebuild_users() -> Migration {
+        let users = CreateTableQuery::new("users")
+            .column(ColumnDef::new("id", ColumnType::Integer).primary_key())
+            .column(ColumnDef::new("name", ColumnType::Text))
+            .dialect(Dialect::Sqlite);
+        Migration::new(1, "require_user_names")
+            .up(&AlterTableQuery::new("users")
+                .set_not_null("name")
+                .rebuild_from(users)
+                .dialect(Dialect::Sqlite))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sqlite_rebuild_keeps_foreign_keys_off() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                 CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT)",
+            )
+            .unwrap();
+        let statements = Migrator::new(vec![rebuild_users()])
+            .unwrap()
+            .dialect(Dialect::Sqlite)
+            .up(&mut connection)
+            .unwrap();
+        let foreign_keys: i64 = connection
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(foreign_keys, 0);
+        assert_eq!(statements[1], "BEGIN");
+        assert_eq!(statements[statements.len() - 1], "COMMIT");
+    }
+
+    #[test]
+    fn test_foreign_key_violation_rolls_back() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "PRAGMA foreign_keys = ON;
+                 CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+                 CREATE TABLE orders (id INTEGER PRIMARY KEY, user_id INTEGER REFERENCES users (id))",
+            )
+            .unwrap();
+        let migrator = Migrator::new(vec![
+            rebuild_users().up_sql("INSERT INTO orders (id, user_id) VALUES (1, 99)")
+        ])
+        .unwrap()
+        .dialect(Dialect::Sqlite);
+        assert_eq!(
+            migrator.up(&mut connection),
+            Err(MigrationError::Migration(Error::ForeignKeyViolation(1)))
+        );
+        let orders: i64 = connection
+            .query_row("SELECT COUNT(*) FROM orders", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(orders, 0);
+        assert_eq!(migrator.pending(&mut connection), Ok(vec![1]));
+        let foreign_keys: i64 = connection
+            .query_row("PRAGMA foreign_keys", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(foreign_keys, 1);
+    }
+
+    #[test]
+    fn test_checksum_separates_statements() {
+        assert_ne!(
+            Migration::new(1, "a").up_sql("a;\nb").checksum(),
+            Migration::new(1, "a").up_sql("a").up_sql("b").checksum()
+        );
+    }
+
+    #[test]
+    fn test_checksum_mismatch() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        Migrator::new(migrations())
+            .unwrap()
+            .dialect(Dialect::Sqlite)
+            .up(&mut connection)
+            .unwrap();
+        let mut edited = migrations();
+        edited[0] = edited[0]
+            .clone()
+            .up_sql("CREATE INDEX idx_users_email ON users (email)");
+        let migrator = Migrator::new(edited).unwrap();
+        assert_eq!(
+            migrator.up(&mut connection),
+            Err(MigrationError::Migration(Error::ChecksumMismatch(2)))
+        );
+        let migrator = Migrator::new(migrations()[1..].to_vec()).unwrap();
+        assert_eq!(
+            migrator.pending(&mut connection),
+            Err(MigrationError::Migration(Error::UnknownMigration(2)))
+        );
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let mut connection = Connection::open_in_memory().unwrap();
+        let mut broken = migrations();
+        broken.push(
+            Migration::new(3, "broken")
+                .up_sql("CREATE TABLE posts (id INTEGER)")
+                .up_sql("ALTER TABLE missing ADD COLUMN x TEXT"),
+        );
+        let migrator = Migrator::new(broken).unwrap().dialect(Dialect::Sqlite);
+        assert!(matches!(
+            migrator.up(&mut connection),
+            Err(MigrationError::Executor(_))
+        ));
+        assert!(!connection.table_exists("posts").unwrap());
+        assert_eq!(migrator.pending(&mut connection), Ok(vec![3]));
+    }
+
+    #[test]
+    fn test_from_dir() {
+        let dir =
+            std::env::temp_dir().join(format!("sql-builder-migrations-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("1_create_posts.up.sql"),
+            "CREATE TABLE posts (id INTEGER);\n",
+        )
+        .unwrap();
+        fs::write(dir.join("1_create_posts.down.sql"), "DROP TABLE posts;\n").unwrap();
+        fs::write(dir.join("README.md"), "not a migration").unwrap();
+        let migrations = Migration::from_dir(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(
+            migrations,
+            vec![Migration::new(1, "create_posts")
+                .up_sql("CREATE TABLE posts (id INTEGER);")
+                .down_sql("DROP TABLE posts;")]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_version() {
+        let mut migrations = migrations();
+        migrations.push(Migration::new(1, "again"));
+        assert_eq!(
+            Migrator::new(migrations).unwrap_err(),
+            Error::DuplicateMigration(1)
+        );
+    }
+}
@@ -0,0 +1,976 @@
+use crate::delete::DeleteQuery;
+use crate::dialect::Dialect;
+use crate::error::Error;
+use crate::insert::InsertQuery;
+use crate::lexer::{intern, Cursor, Token, TokenKind};
+use crate::select::SelectQuery;
+use crate::update::{Assignment, UpdateQuery};
+use crate::where_clause::{WhereClause, WhereCondition, FULL_TABLE_AUDIT_COMMENT};
+use crate::Result;
+use crate::SqlBuilder;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Statement {
+    Select(SelectQuery),
+    Insert(InsertQuery),
+    Update(UpdateQuery),
+    Delete(DeleteQuery),
+}
+
+/// Reads SQL text back into builder values, the inverse of `SqlBuilder::build`.
+///
+/// Conditions the builders can not represent are kept as raw conditions.
+/// Multi-table `UPDATE ... FROM` and `DELETE ... USING` take their join conditions
+/// from the first conjuncts of the where clause, the way the builders render them.
+/// Names are interned for the `&'static str` the builders use, each distinct name is
+/// allocated once.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqlParser {
+    dialect: Dialect,
+}
+
+impl SqlParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The dialect set on parsed `UpdateQuery` and `DeleteQuery` values.
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    pub fn parse(&self, sql: &str) -> Result<Statement> {
+        let cursor = Cursor::new(sql)?;
+        match cursor.peek() {
+            Some(token) if token.is_keyword("SELECT") => {
+                self.parse_select(sql).map(Statement::Select)
+            }
+            Some(token) if token.is_keyword("INSERT") => {
+                self.parse_insert(sql).map(Statement::Insert)
+            }
+            Some(token) if token.is_keyword("UPDATE") => {
+                self.parse_update(sql).map(Statement::Update)
+            }
+            Some(token) if token.is_keyword("DELETE") => {
+                self.parse_delete(sql).map(Statement::Delete)
+            }
+            _ => Err(cursor.error()),
+        }
+    }
+
+    pub fn parse_select(&self, sql: &str) -> Result<SelectQuery> {
+        let mut cursor = Cursor::new(sql)?;
+        cursor.expect_keywords(&["SELECT"])?;
+        let mut columns = Vec::new();
+        if !cursor.eat_symbol("*") {
+            loop {
+                columns.push(intern(expression(&mut cursor, |token| {
+                    token.is_symbol(",") || token.is_keyword("FROM")
+                })?));
+                if !cursor.eat_symbol(",") {
+                    break;
+                }
+            }
+        }
+        cursor.expect_keywords(&["FROM"])?;
+        let mut query = SelectQuery::new(name(&mut cursor)?);
+        query.add_columns(&columns);
+        if cursor.eat_keywords(&["WHERE"]) {
+            query.where_clause(or_clause(&mut cursor)?);
+        }
+        if cursor.eat_keywords(&["ORDER", "BY"]) {
+            // columns sharing a direction form one group, as `order_by_columns` renders them
+            let mut group = Vec::new();
+            loop {
+                group.push(order_by_column(&mut cursor)?);
+                let desc = cursor.eat_keywords(&["DESC"]);
+                let asc = !desc && cursor.eat_keywords(&["ASC"]);
+                if desc || asc || !cursor.peek_symbol(",") {
+                    query.order_by_columns(&group);
+                    if desc {
+                        query.desc()?;
+                    }
+                    group.clear();
+                }
+                if !cursor.eat_symbol(",") {
+                    break;
+                }
+            }
+        }
+        if cursor.eat_keywords(&["LIMIT"]) {
+            query.limit(number(&mut cursor)?);
+        }
+        if cursor.eat_keywords(&["OFFSET"]) {
+            query.offset(number(&mut cursor)?);
+        }
+        finish(&mut cursor)?;
+        Ok(query)
+    }
+
+    pub fn parse_insert(&self, sql: &str) -> Result<InsertQuery> {
+        let mut cursor = Cursor::new(sql)?;
+        cursor.expect_keywords(&["INSERT", "INTO"])?;
+        let mut query = InsertQuery::new(name(&mut cursor)?);
+        cursor.expect_symbol("(")?;
+        loop {
+            query.add_column(name(&mut cursor)?);
+            if cursor.eat_symbol(")") {
+                break;
+            }
+            cursor.expect_symbol(",")?;
+        }
+        cursor.expect_keywords(&["VALUES"])?;
+        loop {
+            cursor.expect_symbol("(")?;
+            let mut record = Vec::new();
+            loop {
+                record.push(expression(&mut cursor, |token| token.is_symbol(","))?.to_string());
+                if cursor.eat_symbol(")") {
+                    break;
+                }
+                cursor.expect_symbol(",")?;
+            }
+            query.add_record_raw(&record)?;
+            if !cursor.eat_symbol(",") {
+                break;
+            }
+        }
+        finish(&mut cursor)?;
+        Ok(query)
+    }
+
+    pub fn parse_update(&self, sql: &str) -> Result<UpdateQuery> {
+        let mut cursor = Cursor::new(sql)?;
+        cursor.expect_keywords(&["UPDATE"])?;
+        let mut query = UpdateQuery::new(name(&mut cursor)?);
+        query.dialect(self.dialect);
+        if is_full_table(sql) {
+            query.allow_full_table();
+        }
+        for (table, on) in joins(&mut cursor, &["SET"])? {
+            query.join(table, on);
+        }
+        cursor.expect_keywords(&["SET"])?;
+        loop {
+            let field_start = cursor.pos;
+            let field = name(&mut cursor)?;
+            let field_tokens = cursor.pos - field_start;
+            cursor.expect_symbol("=")?;
+            let start = cursor.pos;
+            expression(&mut cursor, |token| {
+                token.is_symbol(",") || is_keyword_of(token, &["FROM", "WHERE", "ORDER", "LIMIT"])
+            })?;
+            query.assign(field, assignment(&cursor, field, field_tokens, start));
+            if !cursor.eat_symbol(",") {
+                break;
+            }
+        }
+        let from = if cursor.eat_keywords(&["FROM"]) {
+            name_list(&mut cursor)?
+        } else {
+            Vec::new()
+        };
+        let where_clause = if cursor.eat_keywords(&["WHERE"]) {
+            Some(or_clause(&mut cursor)?)
+        } else {
+            None
+        };
+        let (joins, where_clause) = split_join_conditions(&cursor, from, where_clause)?;
+        for (table, on) in joins {
+            query.join(table, on);
+        }
+        query.where_clause = where_clause;
+        if cursor.eat_keywords(&["ORDER", "BY"]) {
+            loop {
+                query.order_by(order_by_column(&mut cursor)?);
+                if cursor.eat_keywords(&["DESC"]) {
+                    query.desc()?;
+                } else {
+                    cursor.eat_keywords(&["ASC"]);
+                }
+                if !cursor.eat_symbol(",") {
+                    break;
+                }
+            }
+        }
+        if cursor.eat_keywords(&["LIMIT"]) {
+            query.limit(number(&mut cursor)?);
+        }
+        finish(&mut cursor)?;
+        Ok(query)
+    }
+
+    pub fn parse_delete(&self, sql: &str) -> Result<DeleteQuery> {
+        let mut cursor = Cursor::new(sql)?;
+        cursor.expect_keywords(&["DELETE"])?;
+        // the target table of the MySQL multi-table form
+        if !cursor.peek_keyword("FROM") {
+            name(&mut cursor)?;
+        }
+        cursor.expect_keywords(&["FROM"])?;
+        let mut query = DeleteQuery::new(name(&mut cursor)?).dialect(self.dialect);
+        if is_full_table(sql) {
+            query = query.allow_full_table();
+        }
+        for (table, on) in joins(&mut cursor, &["USING", "WHERE", "ORDER", "LIMIT"])? {
+            query = query.join(table, on);
+        }
+        let using = if cursor.eat_keywords(&["USING"]) {
+            name_list(&mut cursor)?
+        } else {
+            Vec::new()
+        };
+        let where_clause = if cursor.eat_keywords(&["WHERE"]) {
+            Some(or_clause(&mut cursor)?)
+        } else {
+            None
+        };
+        let (joins, where_clause) = split_join_conditions(&cursor, using, where_clause)?;
+        for (table, on) in joins {
+            query = query.join(table, on);
+        }
+        query.where_clause = where_clause;
+        if cursor.eat_keywords(&["ORDER", "BY"]) {
+            loop {
+                query = query.order_by(order_by_column(&mut cursor)?);
+                if cursor.eat_keywords(&["DESC"]) {
+                    query = query.desc()?;
+                } else {
+                    cursor.eat_keywords(&["ASC"]);
+                }
+                if !cursor.eat_symbol(",") {
+                    break;
+                }
+            }
+        }
+        if cursor.eat_keywords(&["LIMIT"]) {
+            query = query.limit(number(&mut cursor)?);
+        }
+        finish(&mut cursor)?;
+        Ok(query)
+    }
+
+    pub fn parse_where_clause(&self, sql: &str) -> Result<WhereClause> {
+        let mut cursor = Cursor::new(sql)?;
+        let where_clause = or_clause(&mut cursor)?;
+        finish(&mut cursor)?;
+        Ok(where_clause)
+    }
+}
+
+fn finish(cursor: &mut Cursor) -> Result<()> {
+    cursor.eat_symbol(";");
+    if cursor.is_done() {
+        Ok(())
+    } else {
+        Err(cursor.error())
+    }
+}
+
+fn is_full_table(sql: &str) -> bool {
+    sql.trim_start().starts_with(FULL_TABLE_AUDIT_COMMENT)
+}
+
+fn is_keyword_of(token: &Token, keywords: &[&str]) -> bool {
+    keywords.iter().any(|keyword| token.is_keyword(keyword))
+}
+
+/// Ends a condition: the next `AND` / `OR`, a closing parenthesis or the next clause.
+fn is_condition_end(token: &Token) -> bool {
+    token.is_symbol(")")
+        || token.is_symbol(";")
+        || is_keyword_of(
+            token,
+            &["AND", "OR", "ORDER", "LIMIT", "OFFSET", "GROUP", "HAVING"],
+        )
+}
+
+/// The text of the tokens up to the first `stop` token or closing parenthesis
+/// outside of parentheses.
+fn expression<'a, F: Fn(&Token) -> bool>(cursor: &mut Cursor<'a>, stop: F) -> Result<&'a str> {
+    let start = cursor.pos;
+    while let Some(token) = cursor.peek() {
+        if stop(&token) || token.is_symbol(")") {
+            break;
+        }
+        if token.is_symbol("(") {
+            cursor.skip_group()?;
+        } else {
+            cursor.next();
+        }
+    }
+    if cursor.pos == start {
+        return Err(cursor.error());
+    }
+    Ok(cursor.text_since(start))
+}
+
+/// A possibly qualified name such as `users.id` or `users.*`.
+fn name(cursor: &mut Cursor) -> Result<&'static str> {
+    let start = cursor.pos;
+    cursor.expect_ident()?;
+    while cursor.eat_symbol(".") {
+        if !cursor.eat_symbol("*") {
+            cursor.expect_ident()?;
+        }
+    }
+    Ok(intern(cursor.text_since(start)))
+}
+
+fn name_list(cursor: &mut Cursor) -> Result<Vec<&'static str>> {
+    let mut names = vec![name(cursor)?];
+    while cursor.eat_symbol(",") {
+        names.push(name(cursor)?);
+    }
+    Ok(names)
+}
+
+fn number(cursor: &mut Cursor) -> Result<u64> {
+    match cursor.peek() {
+        Some(token) if token.kind == TokenKind::Number => {
+            let number = token.text.parse().map_err(|_| cursor.error())?;
+            cursor.next();
+            Ok(number)
+        }
+        _ => Err(cursor.error()),
+    }
+}
+
+fn order_by_column(cursor: &mut Cursor) -> Result<&'static str> {
+    expression(cursor, |token| {
+        token.is_symbol(",") || is_keyword_of(token, &["ASC", "DESC", "LIMIT", "OFFSET"])
+    })
+    .map(intern)
+}
+
+/// A joined table and its `ON` condition.
+type JoinCondition = (&'static str, String);
+
+/// The `JOIN table ON ...` clauses of the MySQL multi-table forms.
+fn joins(cursor: &mut Cursor, stops: &[&str]) -> Result<Vec<JoinCondition>> {
+    let mut joins = Vec::new();
+    while cursor.eat_keywords(&["JOIN"]) || cursor.eat_keywords(&["INNER", "JOIN"]) {
+        let table = name(cursor)?;
+        cursor.expect_keywords(&["ON"])?;
+        let on = expression(cursor, |token| {
+            is_keyword_of(token, &["JOIN", "INNER"]) || is_keyword_of(token, stops)
+        })?;
+        joins.push((table, on.to_string()));
+    }
+    Ok(joins)
+}
+
+/// Takes the join conditions of the `FROM` / `USING` forms, the first conjuncts
+/// of the where clause, back out of it.
+fn split_join_conditions(
+    cursor: &Cursor,
+    tables: Vec<&'static str>,
+    where_clause: Option<WhereClause>,
+) -> Result<(Vec<JoinCondition>, Option<WhereClause>)> {
+    if tables.is_empty() {
+        return Ok((Vec::new(), where_clause));
+    }
+    let mut conjuncts = match where_clause {
+        Some(WhereClause::And(conjuncts)) => conjuncts,
+        Some(where_clause) => vec![where_clause],
+        None => Vec::new(),
+    };
+    if conjuncts.len() < tables.len() {
+        return Err(Error::InvalidSql("missing join condition", cursor.offset()));
+    }
+    let rest = conjuncts.split_off(tables.len());
+    let joins = tables
+        .into_iter()
+        .zip(conjuncts)
+        .map(|(table, on)| Ok((table, on.build()?)))
+        .collect::<Result<Vec<_>>>()?;
+    Ok((
+        joins,
+        rest.into_iter().reduce(|left, right| left.and(right)),
+    ))
+}
+
+fn assignment(
+    cursor: &Cursor,
+    field: &'static str,
+    field_tokens: usize,
+    start: usize,
+) -> Assignment {
+    let tokens = &cursor.tokens[start..cursor.pos];
+    let text = cursor.text_between(start, cursor.pos);
+    let is_literal = |token: &Token| {
+        matches!(token.kind, TokenKind::Number | TokenKind::String)
+            || token.is_keyword("TRUE")
+            || token.is_keyword("FALSE")
+    };
+    match tokens {
+        [token] if token.is_keyword("DEFAULT") => Assignment::Default,
+        [token] if token.is_keyword("NULL") => Assignment::Null,
+        [token] if token.is_keyword("CURRENT_TIMESTAMP") => Assignment::Now,
+        [token, open, close]
+            if token.is_keyword("NOW") && open.is_symbol("(") && close.is_symbol(")") =>
+        {
+            Assignment::Now
+        }
+        [token] if is_literal(token) => Assignment::Value(text.to_string()),
+        [minus, number] if minus.is_symbol("-") && number.kind == TokenKind::Number => {
+            Assignment::Value(text.to_string())
+        }
+        [token, open, ..] if token.is_keyword("UUID_TO_BIN") && open.is_symbol("(") => {
+            Assignment::Value(text.to_string())
+        }
+        _ if tokens.len() > field_tokens + 1
+            && cursor.text_between(start, start + field_tokens) == field
+            && (tokens[field_tokens].is_symbol("+") || tokens[field_tokens].is_symbol("-")) =>
+        {
+            let by = cursor
+                .text_between(start + field_tokens + 1, cursor.pos)
+                .to_string();
+            match tokens[field_tokens].is_symbol("+") {
+                true => Assignment::Increment(by),
+                false => Assignment::Decrement(by),
+            }
+        }
+        _ if tokens
+            .iter()
+            .all(|token| token.ident().is_some() || token.is_symbol(".")) =>
+        {
+            Assignment::Column(intern(text))
+        }
+        _ => Assignment::Expr(text.to_string()),
+    }
+}
+
+/// `and_clause (OR and_clause)*`.
+fn or_clause(cursor: &mut Cursor) -> Result<WhereClause> {
+    let mut clause = and_clause(cursor)?;
+    while cursor.eat_keywords(&["OR"]) {
+        clause = clause.or(and_clause(cursor)?);
+    }
+    Ok(clause)
+}
+
+fn and_clause(cursor: &mut Cursor) -> Result<WhereClause> {
+    let mut clause = not_clause(cursor)?;
+    while cursor.eat_keywords(&["AND"]) {
+        clause = clause.and(not_clause(cursor)?);
+    }
+    Ok(clause)
+}
+
+fn not_clause(cursor: &mut Cursor) -> Result<WhereClause> {
+    if cursor.eat_keywords(&["NOT"]) {
+        return Ok(!not_clause(cursor)?);
+    }
+    primary(cursor)
+}
+
+/// A parenthesized group, a condition, or else a raw condition.
+fn primary(cursor: &mut Cursor) -> Result<WhereClause> {
+    let start = cursor.pos;
+    let at_end = |cursor: &Cursor| cursor.peek().is_none_or(|token| is_condition_end(&token));
+    if cursor.eat_symbol("(") {
+        if let Ok(clause) = or_clause(cursor) {
+            if cursor.eat_symbol(")") && at_end(cursor) {
+                return Ok(clause);
+            }
+        }
+    } else if let Ok(condition) = condition(cursor) {
+        if at_end(cursor) {
+            return Ok(WhereClause::Condition(condition));
+        }
+    }
+    cursor.pos = start;
+    raw_condition(cursor)
+}
+
+fn condition(cursor: &mut Cursor) -> Result<WhereCondition> {
+    let field = name(cursor)?;
+    let not = cursor.eat_keywords(&["NOT"]);
+    let operator = match cursor.next() {
+        Some(token) => token,
+        None => return Err(cursor.error()),
+    };
+    let value = |cursor: &mut Cursor| operand(cursor).map(str::to_string);
+    Ok(match operator.text.to_uppercase().as_str() {
+        "=" if !not => WhereCondition::Equals(field, value(cursor)?),
+        "!=" | "<>" if !not => WhereCondition::NotEquals(field, value(cursor)?),
+        ">" if !not => WhereCondition::GreaterThan(field, value(cursor)?),
+        ">=" if !not => WhereCondition::GreaterThanOrEquals(field, value(cursor)?),
+        "<" if !not => WhereCondition::LessThan(field, value(cursor)?),
+        "<=" if !not => WhereCondition::LessThanOrEquals(field, value(cursor)?),
+        "LIKE" if not => WhereCondition::NotLike(field, value(cursor)?),
+        "LIKE" => WhereCondition::Like(field, value(cursor)?),
+        "IN" => {
+            cursor.expect_symbol("(")?;
+            let mut values = Vec::new();
+            while !cursor.eat_symbol(")") {
+                if !values.is_empty() {
+                    cursor.expect_symbol(",")?;
+                }
+                values.push(value(cursor)?);
+            }
+            match not {
+                true => WhereCondition::NotIn(field, values),
+                false => WhereCondition::In(field, values),
+            }
+        }
+        "BETWEEN" => {
+            let min = value(cursor)?;
+            cursor.expect_keywords(&["AND"])?;
+            let max = value(cursor)?;
+            match not {
+                true => WhereCondition::NotBetween(field, min, max),
+                false => WhereCondition::Between(field, min, max),
+            }
+        }
+        "IS" if !not => {
+            let not = cursor.eat_keywords(&["NOT"]);
+            cursor.expect_keywords(&["NULL"])?;
+            match not {
+                true => WhereCondition::IsNotNull(field),
+                false => WhereCondition::IsNull(field),
+            }
+        }
+        _ => return Err(cursor.error()),
+    })
+}
+
+/// A single value: a literal, a placeholder, a possibly qualified name,
+/// a function call or a parenthesized expression.
+fn operand<'a>(cursor: &mut Cursor<'a>) -> Result<&'a str> {
+    let start = cursor.pos;
+    if !cursor.eat_symbol("-") {
+        cursor.eat_symbol("+");
+    }
+    match cursor.peek() {
+        Some(token)
+            if matches!(token.kind, TokenKind::Number | TokenKind::String)
+                || token.is_symbol("?") =>
+        {
+            cursor.next();
+        }
+        Some(token) if token.ident().is_some() => {
+            name(cursor)?;
+            if cursor.peek_symbol("(") {
+                cursor.skip_group()?;
+            }
+        }
+        Some(token) if token.is_symbol("(") => cursor.skip_group()?,
+        _ => return Err(cursor.error()),
+    }
+    // PostgreSQL casts such as `'2023-01-01'::date`
+    if cursor.eat_symbol("::") {
+        cursor.expect_ident()?;
+    }
+    Ok(cursor.text_since(start))
+}
+
+/// Everything up to the end of the condition, `BETWEEN ... AND ...` included.
+fn raw_condition(cursor: &mut Cursor) -> Result<WhereClause> {
+    let start = cursor.pos;
+    let mut between = false;
+    while let Some(token) = cursor.peek() {
+        if token.is_keyword("BETWEEN") {
+            between = true;
+        } else if token.is_keyword("AND") && between {
+            between = false;
+        } else if is_condition_end(&token) {
+            break;
+        }
+        if token.is_symbol("(") {
+            cursor.skip_group()?;
+        } else {
+            cursor.next();
+        }
+    }
+    if cursor.pos == start {
+        return Err(cursor.error());
+    }
+    Ok(WhereClause::raw_condition(
+        cursor.text_since(start).to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repr::ToSqlRepr;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_parse_where_clause() {
+        let parser = SqlParser::new();
+        assert_eq!(
+            parser.parse_where_clause("name = 'Jack' AND (age = 18 OR age IN (20, 22))"),
+            Ok(WhereClause::equals("name", "Jack")
+                .and(WhereClause::equals("age", 18).or_in("age", vec![20, 22])))
+        );
+        assert_eq!(
+            parser.parse_where_clause(
+                "a.id <> ? AND price NOT BETWEEN -1 AND 2.5 AND deleted_at IS NOT NULL"
+            ),
+            Ok(
+                WhereClause::Condition(WhereCondition::NotEquals("a.id", "?".to_string()))
+                    .and_not_between("price", -1.0, 2.5)
+                    .and_is_not_null("deleted_at")
+            )
+        );
+        assert_eq!(
+            parser.parse_where_clause(
+                "LOWER(name) LIKE 'j%' OR 1=1 OR NOT (age > 1) AND x BETWEEN 1 AND 2"
+            ),
+            Ok(
+                WhereClause::raw_condition("LOWER(name) LIKE 'j%'".to_string())
+                    .or_raw_condition("1=1".to_string())
+                    .or((!WhereClause::greater_than("age", 1)).and_between("x", 1, 2))
+            )
+        );
+        assert_eq!(
+            parser.parse_where_clause("id = 1 AND"),
+            Err(Error::InvalidSql("unexpected end of input", 10))
+        );
+    }
+
+    #[test]
+    fn test_parse_statements() {
+        let parser = SqlParser::new();
+        let mut select = SelectQuery::new("users");
+        select
+            .add_columns(&["id", "COUNT(*)"])
+            .where_clause(WhereClause::equals("id", 1))
+            .order_by("name")
+            .order_by_columns(&["age", "id"])
+            .desc()
+            .unwrap()
+            .limit(10)
+            .offset(20);
+        assert_eq!(
+            parser.parse(
+                "SELECT id, COUNT(*) FROM users WHERE id = 1 ORDER BY name ASC, age, id DESC LIMIT 10 OFFSET 20;"
+            ),
+            Ok(Statement::Select(select))
+        );
+
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_columns(["id", "name"].into_iter())
+            .add_record_raw(&["1".to_string(), "'Jack'".to_string()])
+            .unwrap()
+            .add_record_raw(&["2".to_string(), "LOWER('Rose')".to_string()])
+            .unwrap();
+        assert_eq!(
+            parser.parse("INSERT INTO users (id, name) VALUES (1, 'Jack'), (2, LOWER('Rose'))"),
+            Ok(Statement::Insert(insert))
+        );
+
+        let mut update = UpdateQuery::new("users");
+        update
+            .set_field("name", &"Jack")
+            .increment("visits", 1)
+            .set_now("seen_at")
+            .set_column("nickname", "name")
+            .set_default("role")
+            .set_null("email")
+            .set_expr("bio", "COALESCE(bio, '')".to_string())
+            .add_where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            parser.parse(
+                "UPDATE users SET name = 'Jack', visits = visits + 1, seen_at = NOW(), nickname = name, role = DEFAULT, email = NULL, bio = COALESCE(bio, '') WHERE id = 1"
+            ),
+            Ok(Statement::Update(update))
+        );
+
+        let delete = DeleteQuery::new("events")
+            .where_clause(WhereClause::less_than("created_at", "2023-01-01"))
+            .order_by("id")
+            .limit(1000);
+        assert_eq!(
+            parser
+                .parse("DELETE FROM events WHERE created_at < '2023-01-01' ORDER BY id LIMIT 1000"),
+            Ok(Statement::Delete(delete))
+        );
+        assert_eq!(
+            parser.parse("MERGE INTO users"),
+            Err(Error::InvalidSql("unexpected token", 0))
+        );
+    }
+
+    #[test]
+    fn test_parse_multi_table() {
+        for dialect in [Dialect::MySql, Dialect::Postgres] {
+            let parser = SqlParser::new().dialect(dialect);
+            let mut update = UpdateQuery::new("users");
+            update
+                .dialect(dialect)
+                .set_field("users.active", &false)
+                .join("bans", "bans.user_id = users.id".to_string())
+                .add_where_clause(
+                    WhereClause::greater_than("bans.until", "2023-01-01").or_is_null("bans.until"),
+                );
+            assert_eq!(parser.parse_update(&update.build().unwrap()), Ok(update));
+
+            let delete = DeleteQuery::new("orders")
+                .dialect(dialect)
+                .join("users", "users.id = orders.user_id".to_string())
+                .where_clause(
+                    WhereClause::equals("users.active", false).and_is_null("orders.paid_at"),
+                );
+            assert_eq!(parser.parse_delete(&delete.build().unwrap()), Ok(delete));
+        }
+
+        let mut update = UpdateQuery::new("users");
+        update.set_field("active", &false).allow_full_table();
+        assert_eq!(
+            SqlParser::new().parse_update(&update.build().unwrap()),
+            Ok(update)
+        );
+    }
+
+    fn field() -> impl Strategy<Value = &'static str> {
+        prop::sample::select(vec!["id", "name", "age", "users.id", "`order`"])
+    }
+
+    fn value() -> impl Strategy<Value = String> {
+        prop_oneof![
+            any::<i32>().prop_map(|value| value.to_sql_repr()),
+            "[a-z %_]{0,8}".prop_map(|value| value.as_str().to_sql_repr()),
+            any::<bool>().prop_map(|value| value.to_sql_repr()),
+            (0..1000u32).prop_map(|value| (value as f64 / 8.0).to_sql_repr()),
+            Just("NULL".to_string()),
+        ]
+    }
+
+    fn condition() -> impl Strategy<Value = WhereClause> {
+        prop_oneof![
+            (field(), value())
+                .prop_map(|(f, v)| WhereClause::Condition(WhereCondition::Equals(f, v))),
+            (field(), value())
+                .prop_map(|(f, v)| WhereClause::Condition(WhereCondition::NotEquals(f, v))),
+            (field(), value())
+                .prop_map(|(f, v)| WhereClause::Condition(WhereCondition::GreaterThan(f, v))),
+            (field(), value())
+                .prop_map(|(f, v)| WhereClause::Condition(WhereCondition::LessThanOrEquals(f, v))),
+            (field(), value())
+                .prop_map(|(f, v)| WhereClause::Condition(WhereCondition::Like(f, v))),
+            (field(), value())
+                .prop_map(|(f, v)| WhereClause::Condition(WhereCondition::NotLike(f, v))),
+            (field(), prop::collection::vec(value(), 1..4))
+                .prop_map(|(f, v)| WhereClause::Condition(WhereCondition::In(f, v))),
+            (field(), prop::collection::vec(value(), 1..4))
+                .prop_map(|(f, v)| WhereClause::Condition(WhereCondition::NotIn(f, v))),
+            (field(), value(), value())
+                .prop_map(|(f, a, b)| WhereClause::Condition(WhereCondition::Between(f, a, b))),
+            (field(), value(), value())
+                .prop_map(|(f, a, b)| WhereClause::Condition(WhereCondition::NotBetween(f, a, b))),
+            field().prop_map(WhereClause::is_null),
+            field().prop_map(WhereClause::is_not_null),
+            prop::sample::select(vec![
+                "1=1",
+                "LOWER(name) = 'x'",
+                "age + 1 > 2",
+                "EXISTS (SELECT 1)"
+            ])
+            .prop_map(|raw| WhereClause::raw_condition(raw.to_string())),
+        ]
+    }
+
+    fn where_clause() -> impl Strategy<Value = WhereClause> {
+        condition().prop_recursive(4, 32, 2, |inner| {
+            prop_oneof![
+                (inner.clone(), inner.clone()).prop_map(|(left, right)| left.and(right)),
+                (inner.clone(), inner.clone()).prop_map(|(left, right)| left.or(right)),
+                inner.prop_map(|clause| !clause),
+            ]
+        })
+    }
+
+    /// Arbitrary trees over the boolean variables `p0 = 1` .. `p3 = 1`, including
+    /// the nested, single and empty groups the builder methods never produce.
+    fn boolean_tree() -> impl Strategy<Value = WhereClause> {
+        (0..4usize)
+            .prop_map(|n| WhereClause::equals(["p0", "p1", "p2", "p3"][n], 1))
+            .prop_recursive(5, 48, 3, |inner| {
+                prop_oneof![
+                    prop::collection::vec(inner.clone(), 0..4).prop_map(WhereClause::And),
+                    prop::collection::vec(inner.clone(), 0..4).prop_map(WhereClause::Or),
+                    inner.prop_map(|clause| !clause),
+                ]
+            })
+    }
+
+    fn evaluate(clause: &WhereClause, assignment: &[bool]) -> bool {
+        match clause {
+            WhereClause::And(clauses) => clauses.iter().all(|c| evaluate(c, assignment)),
+            WhereClause::Or(clauses) => clauses.iter().any(|c| evaluate(c, assignment)),
+            WhereClause::Not(clause) => !evaluate(clause, assignment),
+            WhereClause::Condition(WhereCondition::Equals(field, _)) => {
+                assignment[field[1..].parse::<usize>().unwrap()]
+            }
+            WhereClause::Condition(WhereCondition::NotEquals(field, _)) => {
+                !assignment[field[1..].parse::<usize>().unwrap()]
+            }
+            WhereClause::Condition(WhereCondition::RawCondition(raw)) => match raw.as_str() {
+                "1 = 1" => true,
+                "1 = 0" => false,
+                raw => panic!("unexpected condition {raw}"),
+            },
+            clause => panic!("unexpected clause {clause:?}"),
+        }
+    }
+
+    fn assignment() -> impl Strategy<Value = Assignment> {
+        prop_oneof![
+            value()
+                .prop_filter("NULL parses as Assignment::Null", |value| value != "NULL")
+                .prop_map(Assignment::Value),
+            Just(Assignment::Column("name")),
+            Just(Assignment::Default),
+            Just(Assignment::Now),
+            Just(Assignment::Expr("COALESCE(name, 'x')".to_string())),
+            any::<i16>().prop_map(|by| Assignment::Increment(by.to_sql_repr())),
+            any::<i16>().prop_map(|by| Assignment::Decrement(by.to_sql_repr())),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_where_clause_precedence(
+            clause in boolean_tree(),
+            assignments in prop::collection::vec(prop::array::uniform4(any::<bool>()), 8),
+        ) {
+            let sql = clause.build().unwrap();
+            let parsed = SqlParser::new().parse_where_clause(&sql).unwrap();
+            for assignment in assignments {
+                prop_assert_eq!(
+                    evaluate(&parsed, &assignment),
+                    evaluate(&clause, &assignment),
+                    "{}",
+                    sql
+                );
+            }
+        }
+
+        #[test]
+        fn test_normalize(
+            clause in boolean_tree(),
+            assignments in prop::collection::vec(prop::array::uniform4(any::<bool>()), 8),
+        ) {
+            let normalized = clause.clone().normalize();
+            prop_assert!(!normalized.build().unwrap().contains("NOT"));
+            for assignment in assignments {
+                prop_assert_eq!(
+                    evaluate(&normalized, &assignment),
+                    evaluate(&clause, &assignment)
+                );
+            }
+        }
+
+        #[test]
+        fn test_simplify(
+            clause in boolean_tree(),
+            assignments in prop::collection::vec(prop::array::uniform4(any::<bool>()), 8),
+        ) {
+            let simplified = clause.clone().simplify();
+            prop_assert_eq!(simplified.clone().simplify(), simplified.clone());
+            for assignment in assignments {
+                prop_assert_eq!(
+                    evaluate(&simplified, &assignment),
+                    evaluate(&clause, &assignment)
+                );
+            }
+        }
+
+        #[test]
+        fn test_where_clause_round_trip(clause in where_clause()) {
+            let sql = clause.build().unwrap();
+            prop_assert_eq!(SqlParser::new().parse_where_clause(&sql), Ok(clause));
+        }
+
+        #[test]
+        fn test_select_round_trip(
+            columns in prop::collection::vec(field(), 0..3),
+            clause in prop::option::of(where_clause()),
+            order_by in prop::collection::vec((field(), any::<bool>()), 0..3),
+            limit in prop::option::of(0..100u64),
+            offset in prop::option::of(0..100u64),
+        ) {
+            let mut select = SelectQuery::new("users");
+            select.add_columns(&columns);
+            if let Some(clause) = clause {
+                select.where_clause(clause);
+            }
+            for (column, desc) in order_by {
+                select.order_by(column);
+                if desc {
+                    select.desc().unwrap();
+                }
+            }
+            if let Some(limit) = limit {
+                select.limit(limit);
+            }
+            if let Some(offset) = offset {
+                select.offset(offset);
+            }
+            let sql = select.build().unwrap();
+            prop_assert_eq!(SqlParser::new().parse_select(&sql), Ok(select));
+        }
+
+        #[test]
+        fn test_insert_round_trip(records in prop::collection::vec((value(), value()), 1..4)) {
+            let mut insert = InsertQuery::new("users");
+            insert.add_columns(["id", "name"].into_iter());
+            for (id, name) in records {
+                insert.add_record_raw(&[id, name]).unwrap();
+            }
+            let sql = insert.build().unwrap();
+            prop_assert_eq!(SqlParser::new().parse_insert(&sql), Ok(insert));
+        }
+
+        #[test]
+        fn test_update_round_trip(
+            fields in prop::collection::vec((field(), assignment()), 1..4),
+            clause in where_clause(),
+            order_by in prop::option::of((field(), any::<bool>())),
+            limit in prop::option::of(0..100u64),
+        ) {
+            let mut update = UpdateQuery::new("users");
+            for (field, assignment) in fields {
+                update.assign(field, assignment);
+            }
+            update.add_where_clause(clause).allow_full_table();
+            if let Some((column, desc)) = order_by {
+                update.order_by(column);
+                if desc {
+                    update.desc().unwrap();
+                }
+            }
+            if let Some(limit) = limit {
+                update.limit(limit);
+            }
+            let sql = update.build().unwrap();
+            prop_assert_eq!(SqlParser::new().parse_update(&sql), Ok(update));
+        }
+
+        #[test]
+        fn test_delete_round_trip(
+            clause in where_clause(),
+            order_by in prop::option::of((field(), any::<bool>())),
+            limit in prop::option::of(0..100u64),
+        ) {
+            let mut delete = DeleteQuery::new("users").where_clause(clause).allow_full_table();
+            if let Some((column, desc)) = order_by {
+                delete = delete.order_by(column);
+                if desc {
+                    delete = delete.desc().unwrap();
+                }
+            }
+            if let Some(limit) = limit {
+                delete = delete.limit(limit);
+            }
+            let sql = delete.build().unwrap();
+            prop_assert_eq!(SqlParser::new().parse_delete(&sql), Ok(delete));
+        }
+    }
+}
@@ -59,6 +59,7 @@ impl ToSqlRepr for DateTime<Local> {
 
 use serde_json::Value;
 impl ToSqlRepr for Value {
+    #[allow(clippy::to_string_in_format_args)]
     fn to_sql_repr(&self) -> String {
         format!("'{}'", self.to_string())
     }
@@ -66,6 +67,7 @@ impl ToSqlRepr for Value {
 
 use uuid::Uuid;
 impl ToSqlRepr for Uuid {
+    #[allow(clippy::to_string_in_format_args)]
     fn to_sql_repr(&self) -> String {
         format!("UUID_TO_BIN('{}')", self.to_string())
     }
@@ -107,6 +109,7 @@ mod test {
     }
 
     #[test]
+    #[allow(clippy::to_string_in_format_args)]
     fn test_uuid() {
         let uuid = Uuid::new_v4();
         assert_eq!(
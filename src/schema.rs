@@ -0,0 +1,1087 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use uuid::Uuid;
+
+use crate::create_table::{
+    ColumnDef, ColumnDefault, ColumnType, CreateTableQuery, ForeignKey, ForeignKeyAction,
+    TableConstraint,
+};
+use crate::error::Error;
+use crate::lexer::{intern, Cursor, TokenKind};
+use crate::schema_diff::SchemaDiff;
+use crate::where_clause::{WhereClause, WhereCondition};
+use crate::Result;
+
+/// Checks a builder against a [`Schema`] before it is sent to the database.
+pub trait Validate {
+    fn validate(&self, schema: &Schema) -> Result<()>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableDef {
+    pub name: &'static str,
+    pub columns: Vec<ColumnDef>,
+    pub constraints: Vec<(Option<&'static str>, TableConstraint)>,
+}
+
+impl TableDef {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            columns: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    pub fn column(mut self, column: ColumnDef) -> Self {
+        self.columns.push(column);
+        self
+    }
+
+    pub fn constraint(mut self, name: Option<&'static str>, constraint: TableConstraint) -> Self {
+        self.constraints.push((name, constraint));
+        self
+    }
+
+    pub fn get_column(&self, name: &str) -> Option<&ColumnDef> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    pub(crate) fn require_column(&self, name: &'static str) -> Result<&ColumnDef> {
+        self.get_column(name)
+            .ok_or(Error::UnknownColumn(self.name, name))
+    }
+
+    pub fn create_table(&self) -> CreateTableQuery {
+        let mut query = CreateTableQuery::new(self.name);
+        query.columns = self.columns.clone();
+        query.constraints = self.constraints.clone();
+        query
+    }
+}
+
+impl From<CreateTableQuery> for TableDef {
+    fn from(query: CreateTableQuery) -> Self {
+        Self {
+            name: query.table,
+            columns: query.columns,
+            constraints: query.constraints,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Schema {
+    pub tables: Vec<TableDef>,
+    /// Views and materialized views, by name only.
+    pub views: Vec<&'static str>,
+}
+
+impl Schema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn table(mut self, table: TableDef) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    pub fn view(mut self, name: &'static str) -> Self {
+        self.views.push(name);
+        self
+    }
+
+    /// Reads the `CREATE TABLE` and `CREATE VIEW` statements of a DDL script, other
+    /// statements are skipped.
+    /// Names are interned for the `&'static str` the builders use, each distinct
+    /// name is allocated once.
+    pub fn from_ddl(ddl: &str) -> Result<Self> {
+        let mut cursor = Cursor::new(ddl)?;
+        let mut schema = Schema::new();
+        while !cursor.is_done() {
+            if cursor.eat_symbol(";") {
+                continue;
+            }
+            if cursor.peek_keyword("CREATE")
+                && (cursor
+                    .peek_nth(1)
+                    .is_some_and(|token| token.is_keyword("TABLE"))
+                    || cursor
+                        .peek_nth(2)
+                        .is_some_and(|token| token.is_keyword("TABLE")))
+            {
+                schema.tables.push(parse_create_table(&mut cursor)?);
+            } else if let Some(view) = parse_create_view(&mut cursor)? {
+                schema.views.push(view);
+            } else {
+                while !cursor.is_done() && !cursor.peek_symbol(";") {
+                    cursor.next();
+                }
+            }
+        }
+        Ok(schema)
+    }
+
+    /// The migration from this schema to `desired`.
+    pub fn diff(&self, desired: &Schema) -> SchemaDiff {
+        SchemaDiff::new(self, desired)
+    }
+
+    pub fn get_table(&self, name: &str) -> Option<&TableDef> {
+        self.tables.iter().find(|table| table.name == name)
+    }
+
+    pub(crate) fn require_table(&self, name: &'static str) -> Result<&TableDef> {
+        self.get_table(name).ok_or(Error::UnknownTable(name))
+    }
+
+    pub(crate) fn require_view(&self, name: &'static str) -> Result<()> {
+        if self.views.contains(&name) {
+            Ok(())
+        } else {
+            Err(Error::UnknownView(name))
+        }
+    }
+
+    /// Checks a constraint of `table`, which may not be part of the schema yet:
+    /// its columns and the table and columns a foreign key references.
+    pub(crate) fn validate_constraint(
+        &self,
+        table: &TableDef,
+        constraint: &TableConstraint,
+    ) -> Result<()> {
+        match constraint {
+            TableConstraint::PrimaryKey(columns) | TableConstraint::Unique(columns) => {
+                for column in columns {
+                    table.require_column(column)?;
+                }
+            }
+            TableConstraint::ForeignKey(foreign_key) => {
+                for column in &foreign_key.columns {
+                    table.require_column(column)?;
+                }
+                let referenced = if foreign_key.table == table.name {
+                    table
+                } else {
+                    self.require_table(foreign_key.table)?
+                };
+                for column in &foreign_key.references {
+                    referenced.require_column(column)?;
+                }
+            }
+            TableConstraint::Check(_) => {}
+        }
+        Ok(())
+    }
+
+    /// Checks the definition of a table that may not be part of the schema yet.
+    pub(crate) fn validate_table_def(&self, table: &TableDef) -> Result<()> {
+        for (i, column) in table.columns.iter().enumerate() {
+            if table.columns[..i]
+                .iter()
+                .any(|other| other.name == column.name)
+            {
+                return Err(Error::ColumnExists(table.name, column.name));
+            }
+            if let Some(foreign_key) = &column.references {
+                self.validate_constraint(table, &TableConstraint::ForeignKey(foreign_key.clone()))?;
+            }
+        }
+        for (_, constraint) in &table.constraints {
+            self.validate_constraint(table, constraint)?;
+        }
+        Ok(())
+    }
+
+    /// Finds the column `field` refers to among `tables`, `field` may be qualified.
+    /// Expressions such as `COUNT(*)` or `name AS n` can not be checked and give `None`.
+    pub(crate) fn resolve_column(
+        &self,
+        tables: &[&'static str],
+        field: &'static str,
+    ) -> Result<Option<(&'static str, &ColumnDef)>> {
+        if field == "*"
+            || field.ends_with(".*")
+            || field.contains(|c: char| c.is_whitespace() || c == '(')
+        {
+            return Ok(None);
+        }
+        if let Some((table, column)) = field.split_once('.') {
+            let table = match tables.iter().find(|name| **name == table) {
+                Some(table) => self.require_table(table)?,
+                None => return Err(Error::UnknownTable(table)),
+            };
+            return Ok(Some((table.name, table.require_column(column)?)));
+        }
+        for table in tables {
+            if let Some(column) = self.require_table(table)?.get_column(field) {
+                return Ok(Some((table, column)));
+            }
+        }
+        Err(Error::UnknownColumn(tables[0], field))
+    }
+
+    pub(crate) fn validate_where_clause(
+        &self,
+        tables: &[&'static str],
+        where_clause: &WhereClause,
+    ) -> Result<()> {
+        match where_clause {
+            WhereClause::And(clauses) | WhereClause::Or(clauses) => clauses
+                .iter()
+                .try_for_each(|clause| self.validate_where_clause(tables, clause)),
+            WhereClause::Not(clause) => self.validate_where_clause(tables, clause),
+            WhereClause::Condition(condition) => {
+                let (field, values) = match condition {
+                    WhereCondition::Equals(field, value)
+                    | WhereCondition::NotEquals(field, value)
+                    | WhereCondition::GreaterThan(field, value)
+                    | WhereCondition::GreaterThanOrEquals(field, value)
+                    | WhereCondition::LessThan(field, value)
+                    | WhereCondition::LessThanOrEquals(field, value) => {
+                        (field, vec![value.as_str()])
+                    }
+                    WhereCondition::Like(field, _) | WhereCondition::NotLike(field, _) => {
+                        (field, vec![])
+                    }
+                    WhereCondition::In(field, values) | WhereCondition::NotIn(field, values) => {
+                        (field, values.iter().map(String::as_str).collect())
+                    }
+                    WhereCondition::Between(field, min, max)
+                    | WhereCondition::NotBetween(field, min, max) => {
+                        (field, vec![min.as_str(), max.as_str()])
+                    }
+                    WhereCondition::IsNull(field) | WhereCondition::IsNotNull(field) => {
+                        (field, vec![])
+                    }
+                    WhereCondition::RawCondition(_) => return Ok(()),
+                };
+                if let Some((table, column)) = self.resolve_column(tables, field)? {
+                    for value in values {
+                        check_literal(table, column, value)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Checks a value written to a column: kind, length and nullability.
+pub(crate) fn validate_value(table: &'static str, column: &ColumnDef, value: &str) -> Result<()> {
+    if value == "NULL" && column.not_null {
+        return Err(Error::NullInNotNullColumn(table, column.name));
+    }
+    check_literal(table, column, value)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Literal<'a> {
+    Null,
+    Boolean,
+    Integer(&'a str),
+    Decimal,
+    String(&'a str),
+    Uuid,
+    Expr,
+}
+
+impl<'a> Literal<'a> {
+    fn of(value: &'a str) -> Self {
+        let value = value.trim();
+        if value == "NULL" {
+            Literal::Null
+        } else if value == "TRUE" || value == "FALSE" {
+            Literal::Boolean
+        } else if value.parse::<i128>().is_ok() {
+            Literal::Integer(value)
+        } else if value.parse::<f64>().is_ok() {
+            Literal::Decimal
+        } else if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+            Literal::String(&value[1..value.len() - 1])
+        } else if value.starts_with("UUID_TO_BIN('") {
+            Literal::Uuid
+        } else {
+            Literal::Expr
+        }
+    }
+}
+
+/// Checks that a literal rendered by `ToSqlRepr` fits the column's type,
+/// `NULL` and expressions are accepted.
+pub(crate) fn check_literal(table: &'static str, column: &ColumnDef, value: &str) -> Result<()> {
+    let literal = Literal::of(value);
+    let matches = match (column.column_type, literal) {
+        (_, Literal::Null) | (_, Literal::Expr) => true,
+        (ColumnType::Integer | ColumnType::BigInteger, literal) => {
+            matches!(literal, Literal::Integer(_))
+        }
+        (ColumnType::Float | ColumnType::Decimal(_, _), literal) => {
+            matches!(literal, Literal::Integer(_) | Literal::Decimal)
+        }
+        (ColumnType::Text, literal) => matches!(literal, Literal::String(_)),
+        (ColumnType::Varchar(length), Literal::String(text)) => {
+            if text.chars().count() > length as usize {
+                return Err(Error::ValueTooLong(table, column.name, length));
+            }
+            true
+        }
+        (ColumnType::Varchar(_), _) => false,
+        (ColumnType::Timestamp, Literal::String(text)) => {
+            NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f").is_ok()
+                || NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok()
+                || DateTime::parse_from_rfc3339(text).is_ok()
+        }
+        (ColumnType::Timestamp, _) => false,
+        (ColumnType::Json, Literal::String(text)) => {
+            serde_json::from_str::<serde_json::Value>(text).is_ok()
+        }
+        (ColumnType::Json, _) => false,
+        (ColumnType::Uuid, Literal::String(text)) => Uuid::parse_str(text).is_ok(),
+        (ColumnType::Uuid, literal) => literal == Literal::Uuid,
+        (ColumnType::Boolean, Literal::Integer(number)) => number == "0" || number == "1",
+        (ColumnType::Boolean, literal) => literal == Literal::Boolean,
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(Error::ValueTypeMismatch(
+            table,
+            column.name,
+            column.column_type,
+        ))
+    }
+}
+
+/// The name of a `CREATE [OR REPLACE] [MATERIALIZED] VIEW` statement, the rest of
+/// the statement is left for the caller to skip. `None` for other statements.
+fn parse_create_view(cursor: &mut Cursor) -> Result<Option<&'static str>> {
+    let start = cursor.pos;
+    if cursor.eat_keywords(&["CREATE"]) {
+        cursor.eat_keywords(&["OR", "REPLACE"]);
+        cursor.eat_keywords(&["MATERIALIZED"]);
+        if cursor.eat_keywords(&["VIEW"]) {
+            cursor.eat_keywords(&["IF", "NOT", "EXISTS"]);
+            let mut name = expect_name(cursor)?;
+            while cursor.eat_symbol(".") {
+                name = expect_name(cursor)?;
+            }
+            return Ok(Some(name));
+        }
+    }
+    cursor.pos = start;
+    Ok(None)
+}
+
+fn expect_name(cursor: &mut Cursor) -> Result<&'static str> {
+    cursor.expect_ident().map(intern)
+}
+
+fn parse_create_table(cursor: &mut Cursor) -> Result<TableDef> {
+    cursor.expect_keywords(&["CREATE"])?;
+    if !cursor.eat_keywords(&["TEMPORARY"]) {
+        cursor.eat_keywords(&["TEMP"]);
+    }
+    cursor.expect_keywords(&["TABLE"])?;
+    cursor.eat_keywords(&["IF", "NOT", "EXISTS"]);
+    let mut name = expect_name(cursor)?;
+    while cursor.eat_symbol(".") {
+        name = expect_name(cursor)?;
+    }
+    let mut table = TableDef::new(name);
+    cursor.expect_symbol("(")?;
+    loop {
+        parse_definition(cursor, &mut table)?;
+        if cursor.eat_symbol(")") {
+            break;
+        }
+        cursor.expect_symbol(",")?;
+    }
+    // table options such as `ENGINE=InnoDB` or `WITHOUT ROWID`
+    while !cursor.is_done() && !cursor.peek_symbol(";") {
+        cursor.next();
+    }
+    Ok(table)
+}
+
+fn parse_definition(cursor: &mut Cursor, table: &mut TableDef) -> Result<()> {
+    let name = if cursor.eat_keywords(&["CONSTRAINT"]) {
+        Some(expect_name(cursor)?)
+    } else {
+        None
+    };
+    let constraint = if cursor.eat_keywords(&["PRIMARY", "KEY"]) {
+        TableConstraint::PrimaryKey(parse_column_list(cursor)?)
+    } else if cursor.eat_keywords(&["UNIQUE"]) {
+        if !cursor.eat_keywords(&["KEY"]) {
+            cursor.eat_keywords(&["INDEX"]);
+        }
+        if !cursor.peek_symbol("(") {
+            expect_name(cursor)?;
+        }
+        TableConstraint::Unique(parse_column_list(cursor)?)
+    } else if cursor.eat_keywords(&["CHECK"]) {
+        TableConstraint::Check(cursor.group_text()?.to_string())
+    } else if cursor.eat_keywords(&["FOREIGN", "KEY"]) {
+        if !cursor.peek_symbol("(") {
+            expect_name(cursor)?;
+        }
+        let columns = parse_column_list(cursor)?;
+        cursor.expect_keywords(&["REFERENCES"])?;
+        let mut foreign_key = parse_references(cursor)?;
+        foreign_key.columns = columns;
+        TableConstraint::ForeignKey(foreign_key)
+    } else if name.is_none()
+        && ["KEY", "INDEX", "FULLTEXT", "SPATIAL"]
+            .iter()
+            .any(|keyword| cursor.peek_keyword(keyword))
+    {
+        // plain MySQL indexes are not part of the model
+        skip_definition(cursor)?;
+        return Ok(());
+    } else if name.is_none() {
+        let column = expect_name(cursor)?;
+        table.columns.push(parse_column(cursor, column)?);
+        return Ok(());
+    } else {
+        return Err(cursor.error());
+    };
+    table.constraints.push((name, constraint));
+    Ok(())
+}
+
+fn skip_definition(cursor: &mut Cursor) -> Result<()> {
+    while !cursor.peek_symbol(",") && !cursor.peek_symbol(")") {
+        if cursor.peek_symbol("(") {
+            cursor.skip_group()?;
+        } else if cursor.next().is_none() {
+            return Err(cursor.error());
+        }
+    }
+    Ok(())
+}
+
+/// `(a, b)`, ignoring MySQL prefix lengths and sort directions.
+fn parse_column_list(cursor: &mut Cursor) -> Result<Vec<&'static str>> {
+    cursor.expect_symbol("(")?;
+    let mut columns = Vec::new();
+    loop {
+        columns.push(expect_name(cursor)?);
+        if cursor.peek_symbol("(") {
+            cursor.skip_group()?;
+        }
+        if !cursor.eat_keywords(&["ASC"]) {
+            cursor.eat_keywords(&["DESC"]);
+        }
+        if cursor.eat_symbol(")") {
+            return Ok(columns);
+        }
+        cursor.expect_symbol(",")?;
+    }
+}
+
+/// `table [(columns)] [ON DELETE action] [ON UPDATE action]`, after `REFERENCES`.
+fn parse_references(cursor: &mut Cursor) -> Result<ForeignKey> {
+    let table = expect_name(cursor)?;
+    let references = if cursor.peek_symbol("(") {
+        parse_column_list(cursor)?
+    } else {
+        Vec::new()
+    };
+    let mut foreign_key = ForeignKey::new(&[], table, &references);
+    loop {
+        if cursor.eat_keywords(&["ON", "DELETE"]) {
+            foreign_key.on_delete = Some(parse_foreign_key_action(cursor)?);
+        } else if cursor.eat_keywords(&["ON", "UPDATE"]) {
+            foreign_key.on_update = Some(parse_foreign_key_action(cursor)?);
+        } else {
+            return Ok(foreign_key);
+        }
+    }
+}
+
+fn parse_foreign_key_action(cursor: &mut Cursor) -> Result<ForeignKeyAction> {
+    if cursor.eat_keywords(&["CASCADE"]) {
+        Ok(ForeignKeyAction::Cascade)
+    } else if cursor.eat_keywords(&["SET", "NULL"]) {
+        Ok(ForeignKeyAction::SetNull)
+    } else if cursor.eat_keywords(&["SET", "DEFAULT"]) {
+        Ok(ForeignKeyAction::SetDefault)
+    } else if cursor.eat_keywords(&["RESTRICT"]) {
+        Ok(ForeignKeyAction::Restrict)
+    } else if cursor.eat_keywords(&["NO", "ACTION"]) {
+        Ok(ForeignKeyAction::NoAction)
+    } else {
+        Err(cursor.error())
+    }
+}
+
+fn parse_column(cursor: &mut Cursor, name: &'static str) -> Result<ColumnDef> {
+    let start = cursor.pos;
+    let type_name = expect_name(cursor)?.to_uppercase();
+    let type_name = if (type_name == "CHARACTER" && cursor.eat_keywords(&["VARYING"]))
+        || type_name == "NVARCHAR"
+    {
+        "VARCHAR".to_string()
+    } else {
+        if type_name == "DOUBLE" {
+            cursor.eat_keywords(&["PRECISION"]);
+        }
+        type_name
+    };
+    let mut arguments = Vec::new();
+    if cursor.eat_symbol("(") {
+        loop {
+            match cursor.next() {
+                Some(token) if token.kind == TokenKind::Number => {
+                    arguments.push(token.text.parse::<u32>().map_err(|_| cursor.error())?)
+                }
+                _ => return Err(cursor.error()),
+            }
+            if cursor.eat_symbol(")") {
+                break;
+            }
+            cursor.expect_symbol(",")?;
+        }
+    }
+    if !cursor.eat_keywords(&["WITH", "TIME", "ZONE"]) {
+        cursor.eat_keywords(&["WITHOUT", "TIME", "ZONE"]);
+    }
+    cursor.eat_keywords(&["UNSIGNED"]);
+    let column_type = match (type_name.as_str(), arguments.as_slice()) {
+        ("TINYINT", [1]) => ColumnType::Boolean,
+        ("INT" | "INTEGER" | "SMALLINT" | "MEDIUMINT" | "TINYINT" | "SERIAL", _) => {
+            ColumnType::Integer
+        }
+        ("BIGINT" | "BIGSERIAL", _) => ColumnType::BigInteger,
+        ("REAL" | "FLOAT" | "DOUBLE", _) => ColumnType::Float,
+        ("TEXT" | "CLOB" | "TINYTEXT" | "MEDIUMTEXT" | "LONGTEXT", _) => ColumnType::Text,
+        ("VARCHAR" | "CHAR" | "CHARACTER", [length]) => ColumnType::Varchar(*length),
+        ("VARCHAR" | "CHAR" | "CHARACTER", []) => ColumnType::Text,
+        ("TIMESTAMP" | "TIMESTAMPTZ" | "DATETIME" | "DATE", _) => ColumnType::Timestamp,
+        ("JSON" | "JSONB", _) => ColumnType::Json,
+        ("UUID", _) | ("BINARY", [16]) => ColumnType::Uuid,
+        ("BOOLEAN" | "BOOL", _) => ColumnType::Boolean,
+        ("DECIMAL" | "NUMERIC", [precision, scale]) => {
+            ColumnType::Decimal(decimal_digits(*precision)?, decimal_digits(*scale)?)
+        }
+        ("DECIMAL" | "NUMERIC", [precision]) => ColumnType::Decimal(decimal_digits(*precision)?, 0),
+        ("DECIMAL" | "NUMERIC", []) => ColumnType::Decimal(10, 0),
+        _ => {
+            return Err(Error::UnsupportedColumnType(intern(
+                cursor.text_since(start),
+            )))
+        }
+    };
+    let mut column = ColumnDef::new(name, column_type);
+    column.auto_increment = type_name.ends_with("SERIAL");
+
+    while !cursor.is_done() && !cursor.peek_symbol(",") && !cursor.peek_symbol(")") {
+        if cursor.eat_keywords(&["NOT", "NULL"]) {
+            column.not_null = true;
+        } else if cursor.eat_keywords(&["NULL"]) {
+            column.not_null = false;
+        } else if cursor.eat_keywords(&["DEFAULT"]) {
+            column.default = Some(parse_default(cursor)?);
+        } else if cursor.eat_keywords(&["PRIMARY", "KEY"]) {
+            column.primary_key = true;
+            if !cursor.eat_keywords(&["ASC"]) {
+                cursor.eat_keywords(&["DESC"]);
+            }
+        } else if cursor.eat_keywords(&["AUTOINCREMENT"])
+            || cursor.eat_keywords(&["AUTO_INCREMENT"])
+            || cursor.eat_keywords(&["GENERATED", "BY", "DEFAULT", "AS", "IDENTITY"])
+            || cursor.eat_keywords(&["GENERATED", "ALWAYS", "AS", "IDENTITY"])
+        {
+            column.auto_increment = true;
+        } else if cursor.eat_keywords(&["UNIQUE"]) {
+            column.unique = true;
+            cursor.eat_keywords(&["KEY"]);
+        } else if cursor.eat_keywords(&["CHECK"]) {
+            column.check = Some(cursor.group_text()?.to_string());
+        } else if cursor.eat_keywords(&["REFERENCES"]) {
+            let mut foreign_key = parse_references(cursor)?;
+            foreign_key.columns = vec![name];
+            column.references = Some(foreign_key);
+        } else if cursor.eat_keywords(&["ON", "UPDATE"])
+            || cursor.eat_keywords(&["COLLATE"])
+            || cursor.eat_keywords(&["COMMENT"])
+            || cursor.eat_keywords(&["CHARACTER", "SET"])
+            || cursor.eat_keywords(&["CONSTRAINT"])
+        {
+            cursor.next();
+            if cursor.peek_symbol("(") {
+                cursor.skip_group()?;
+            }
+        } else {
+            return Err(cursor.error());
+        }
+    }
+    Ok(column)
+}
+
+fn decimal_digits(digits: u32) -> Result<u8> {
+    u8::try_from(digits).map_err(|_| Error::DecimalOutOfRange(digits))
+}
+
+fn parse_default(cursor: &mut Cursor) -> Result<ColumnDefault> {
+    let start = cursor.pos;
+    let default = match cursor.peek() {
+        Some(token) if token.is_symbol("(") => {
+            let expr = cursor.group_text()?;
+            if is_now(expr) {
+                ColumnDefault::Now
+            } else {
+                ColumnDefault::Expr(expr.to_string())
+            }
+        }
+        Some(token) if token.is_symbol("-") => {
+            cursor.next();
+            match cursor.next() {
+                Some(number) if number.kind == TokenKind::Number => {
+                    ColumnDefault::Value(cursor.text_since(start).replace(' ', ""))
+                }
+                _ => return Err(cursor.error()),
+            }
+        }
+        Some(token) if token.kind == TokenKind::Number || token.kind == TokenKind::String => {
+            cursor.next();
+            ColumnDefault::Value(token.text.to_string())
+        }
+        Some(token) if token.kind == TokenKind::Word => {
+            cursor.next();
+            if cursor.peek_symbol("(") {
+                cursor.skip_group()?;
+            }
+            let text = cursor.text_since(start);
+            if is_now(text) {
+                ColumnDefault::Now
+            } else if ["TRUE", "FALSE", "NULL"]
+                .iter()
+                .any(|keyword| token.is_keyword(keyword))
+            {
+                ColumnDefault::Value(text.to_uppercase())
+            } else {
+                ColumnDefault::Expr(text.to_string())
+            }
+        }
+        _ => return Err(cursor.error()),
+    };
+    // PostgreSQL casts such as `'active'::text`
+    if cursor.eat_symbol("::") {
+        expect_name(cursor)?;
+    }
+    Ok(default)
+}
+
+fn is_now(expr: &str) -> bool {
+    let expr = expr.trim().to_uppercase().replace(' ', "");
+    [
+        "NOW()",
+        "CURRENT_TIMESTAMP",
+        "CURRENT_TIMESTAMP()",
+        "LOCALTIMESTAMP",
+    ]
+    .contains(&expr.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alter_table::AlterTableQuery;
+    use crate::create_table::{ColumnDef, ForeignKey};
+    use crate::delete::DeleteQuery;
+    use crate::dialect::Dialect;
+    use crate::drop::DropIndex;
+    use crate::insert::InsertQuery;
+    use crate::select::SelectQuery;
+    use crate::update::UpdateQuery;
+    use crate::view::RefreshMaterializedViewQuery;
+    use crate::SqlBuilder;
+
+    fn schema() -> Schema {
+        Schema::new()
+            .table(
+                TableDef::new("users")
+                    .column(
+                        ColumnDef::new("id", ColumnType::BigInteger)
+                            .primary_key()
+                            .auto_increment(),
+                    )
+                    .column(ColumnDef::new("name", ColumnType::Varchar(8)).not_null())
+                    .column(ColumnDef::new("age", ColumnType::Integer))
+                    .column(
+                        ColumnDef::new("active", ColumnType::Boolean)
+                            .not_null()
+                            .default(&true),
+                    )
+                    .column(ColumnDef::new("created_at", ColumnType::Timestamp)),
+            )
+            .table(
+                TableDef::new("orders")
+                    .column(ColumnDef::new("id", ColumnType::BigInteger).primary_key())
+                    .column(ColumnDef::new("user_id", ColumnType::BigInteger).not_null()),
+            )
+    }
+
+    #[test]
+    fn test_from_ddl() {
+        let ddl = "
+            CREATE TABLE IF NOT EXISTS `users` (
+                `id` BIGINT UNSIGNED NOT NULL AUTO_INCREMENT,
+                `name` VARCHAR(64) NOT NULL COMMENT 'display name',
+                `active` TINYINT(1) NOT NULL DEFAULT 1,
+                `balance` DECIMAL(10,2) DEFAULT '0.00',
+                `created_at` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+                PRIMARY KEY (`id`),
+                UNIQUE KEY `uk_name` (`name`),
+                KEY `idx_created_at` (`created_at`)
+            ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+            CREATE INDEX idx_users_name ON users (name);
+            CREATE TABLE orders (
+                id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+                user_id bigint REFERENCES users (id) ON DELETE CASCADE,
+                status character varying(16) DEFAULT 'new'::text,
+                total numeric(12, 2) CHECK (total >= 0),
+                payload jsonb,
+                CONSTRAINT total_positive CHECK (total > 0)
+            );
+        ";
+        let schema = Schema::from_ddl(ddl).unwrap();
+        let users = schema.get_table("users").unwrap();
+        assert_eq!(
+            users.columns,
+            vec![
+                ColumnDef::new("id", ColumnType::BigInteger)
+                    .not_null()
+                    .auto_increment(),
+                ColumnDef::new("name", ColumnType::Varchar(64)).not_null(),
+                ColumnDef::new("active", ColumnType::Boolean)
+                    .not_null()
+                    .default(&1),
+                ColumnDef::new("balance", ColumnType::Decimal(10, 2)).default(&"0.00"),
+                ColumnDef::new("created_at", ColumnType::Timestamp)
+                    .not_null()
+                    .default_now(),
+            ]
+        );
+        assert_eq!(
+            users.constraints,
+            vec![
+                (None, TableConstraint::PrimaryKey(vec!["id"])),
+                (None, TableConstraint::Unique(vec!["name"])),
+            ]
+        );
+        let orders = schema.get_table("orders").unwrap();
+        assert_eq!(
+            orders.columns,
+            vec![
+                ColumnDef::new("id", ColumnType::Uuid)
+                    .primary_key()
+                    .default_expr("gen_random_uuid()".to_string()),
+                ColumnDef::new("user_id", ColumnType::BigInteger).references(
+                    "users",
+                    "id",
+                    Some(ForeignKeyAction::Cascade)
+                ),
+                ColumnDef::new("status", ColumnType::Varchar(16)).default(&"new"),
+                ColumnDef::new("total", ColumnType::Decimal(12, 2)).check("total >= 0".to_string()),
+                ColumnDef::new("payload", ColumnType::Json),
+            ]
+        );
+        assert_eq!(
+            orders.constraints,
+            vec![(
+                Some("total_positive"),
+                TableConstraint::Check("total > 0".to_string())
+            )]
+        );
+    }
+
+    #[test]
+    fn test_from_ddl_round_trip() {
+        let table = TableDef::new("users")
+            .column(ColumnDef::new("id", ColumnType::BigInteger).primary_key())
+            .column(
+                ColumnDef::new("name", ColumnType::Varchar(64))
+                    .not_null()
+                    .default(&"anonymous"),
+            )
+            .column(ColumnDef::new("age", ColumnType::Integer).default(&-1))
+            .column(ColumnDef::new("score", ColumnType::Float))
+            .constraint(
+                Some("age_check"),
+                TableConstraint::Check("age >= -1".to_string()),
+            );
+        for dialect in [Dialect::MySql, Dialect::Postgres, Dialect::Sqlite] {
+            let ddl = table.create_table().dialect(dialect).build().unwrap();
+            let schema = Schema::from_ddl(&ddl).unwrap();
+            let parsed = schema.get_table("users").unwrap();
+            assert_eq!(parsed.constraints, table.constraints);
+            assert_eq!(parsed.columns[1], table.columns[1]);
+            assert_eq!(parsed.columns[2], table.columns[2]);
+        }
+    }
+
+    #[test]
+    fn test_from_ddl_errors() {
+        assert_eq!(
+            Schema::from_ddl("CREATE TABLE t (data BLOB)").unwrap_err(),
+            Error::UnsupportedColumnType("BLOB")
+        );
+        assert_eq!(
+            Schema::from_ddl("CREATE TABLE t (id INTEGER NOT)").unwrap_err(),
+            Error::InvalidSql("unexpected token", 27)
+        );
+        assert_eq!(
+            Schema::from_ddl("CREATE TABLE t (price DECIMAL(300, 2))").unwrap_err(),
+            Error::DecimalOutOfRange(300)
+        );
+    }
+
+    #[test]
+    fn test_from_ddl_views() {
+        let ddl = "
+            CREATE TABLE users (id INTEGER PRIMARY KEY);
+            CREATE OR REPLACE VIEW active_users AS SELECT * FROM users;
+            CREATE MATERIALIZED VIEW IF NOT EXISTS public.user_counts AS SELECT COUNT(*) FROM users;
+        ";
+        let schema = Schema::from_ddl(ddl).unwrap();
+        assert_eq!(schema.tables.len(), 1);
+        assert_eq!(schema.views, vec!["active_users", "user_counts"]);
+    }
+
+    #[test]
+    fn test_validate_select() {
+        let schema = schema();
+        let mut select = SelectQuery::new("users");
+        select
+            .add_columns(&["id", "name", "COUNT(*)"])
+            .where_clause(WhereClause::equals("age", 18).and_in("active", vec![true]));
+        assert_eq!(select.validate(&schema), Ok(()));
+        select.add_column("email");
+        assert_eq!(
+            select.validate(&schema),
+            Err(Error::UnknownColumn("users", "email"))
+        );
+        let mut select = SelectQuery::new("users");
+        select.where_clause(WhereClause::equals("age", "eighteen"));
+        assert_eq!(
+            select.validate(&schema),
+            Err(Error::ValueTypeMismatch(
+                "users",
+                "age",
+                ColumnType::Integer
+            ))
+        );
+        assert_eq!(
+            SelectQuery::new("accounts").validate(&schema),
+            Err(Error::UnknownTable("accounts"))
+        );
+    }
+
+    #[test]
+    fn test_validate_insert() {
+        let schema = schema();
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_columns(["name", "created_at"].into_iter())
+            .add_record_raw(&["'John'".to_string(), "'2023-03-01 10:00:00'".to_string()])
+            .unwrap();
+        assert_eq!(insert.validate(&schema), Ok(()));
+        insert
+            .add_record_raw(&["'Johnathan Doe'".to_string(), "NULL".to_string()])
+            .unwrap();
+        assert_eq!(
+            insert.validate(&schema),
+            Err(Error::ValueTooLong("users", "name", 8))
+        );
+
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_column("age")
+            .add_record_raw(&["18".to_string()])
+            .unwrap();
+        assert_eq!(
+            insert.validate(&schema),
+            Err(Error::MissingNotNullColumn("users", "name"))
+        );
+
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_column("name")
+            .add_record_raw(&["NULL".to_string()])
+            .unwrap();
+        assert_eq!(
+            insert.validate(&schema),
+            Err(Error::NullInNotNullColumn("users", "name"))
+        );
+
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_columns(["name", "created_at"].into_iter())
+            .add_record_raw(&["'John'".to_string(), "'yesterday'".to_string()])
+            .unwrap();
+        assert_eq!(
+            insert.validate(&schema),
+            Err(Error::ValueTypeMismatch(
+                "users",
+                "created_at",
+                ColumnType::Timestamp
+            ))
+        );
+    }
+
+    #[test]
+    fn test_validate_update() {
+        let schema = schema();
+        let mut update = UpdateQuery::new("users");
+        update
+            .set_field("active", &false)
+            .increment("age", 1)
+            .set_now("created_at")
+            .join("orders", "orders.user_id = users.id".to_string())
+            .add_where_clause(WhereClause::equals("orders.id", 1));
+        assert_eq!(update.validate(&schema), Ok(()));
+        update.set_null("active");
+        assert_eq!(
+            update.validate(&schema),
+            Err(Error::NullInNotNullColumn("users", "active"))
+        );
+        let mut update = UpdateQuery::new("users");
+        update
+            .increment("name", 1)
+            .add_where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            update.validate(&schema),
+            Err(Error::ValueTypeMismatch(
+                "users",
+                "name",
+                ColumnType::Varchar(8)
+            ))
+        );
+    }
+
+    #[test]
+    fn test_validate_delete() {
+        let schema = schema();
+        let delete = DeleteQuery::new("orders").where_clause(WhereClause::equals("users.id", 1));
+        assert_eq!(delete.validate(&schema), Err(Error::UnknownTable("users")));
+        let delete = delete.join("users", "users.id = orders.user_id".to_string());
+        assert_eq!(delete.validate(&schema), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_create_table() {
+        let schema = schema();
+        let create =
+            CreateTableQuery::new("users").column(ColumnDef::new("id", ColumnType::Integer));
+        assert_eq!(create.validate(&schema), Err(Error::TableExists("users")));
+        assert_eq!(create.if_not_exists().validate(&schema), Ok(()));
+        let create = CreateTableQuery::new("payments")
+            .column(ColumnDef::new("id", ColumnType::BigInteger))
+            .column(
+                ColumnDef::new("order_id", ColumnType::BigInteger).references("orders", "id", None),
+            )
+            .column(
+                ColumnDef::new("parent_id", ColumnType::BigInteger)
+                    .references("payments", "id", None),
+            );
+        assert_eq!(create.validate(&schema), Ok(()));
+        let unknown =
+            create
+                .clone()
+                .foreign_key(ForeignKey::new(&["order_id"], "invoices", &["id"]));
+        assert_eq!(
+            unknown.validate(&schema),
+            Err(Error::UnknownTable("invoices"))
+        );
+        let unknown = create.clone().constraint(
+            "payments_pk",
+            TableConstraint::PrimaryKey(vec!["payment_id"]),
+        );
+        assert_eq!(
+            unknown.validate(&schema),
+            Err(Error::UnknownColumn("payments", "payment_id"))
+        );
+        let duplicate = create.column(ColumnDef::new("id", ColumnType::Integer));
+        assert_eq!(
+            duplicate.validate(&schema),
+            Err(Error::ColumnExists("payments", "id"))
+        );
+    }
+
+    #[test]
+    fn test_validate_alter_table() {
+        let schema = schema();
+        let alter = AlterTableQuery::new("orders")
+            .rename_column("user_id", "customer_id")
+            .add_constraint(
+                "orders_customer",
+                TableConstraint::ForeignKey(ForeignKey::new(&["customer_id"], "users", &["id"])),
+            )
+            .drop_constraint("orders_customer");
+        assert_eq!(alter.validate(&schema), Ok(()));
+        assert_eq!(
+            alter.set_not_null("user_id").validate(&schema),
+            Err(Error::UnknownColumn("orders", "user_id"))
+        );
+        assert_eq!(
+            AlterTableQuery::new("invoices")
+                .drop_column("id")
+                .validate(&schema),
+            Err(Error::UnknownTable("invoices"))
+        );
+        assert_eq!(
+            AlterTableQuery::new("orders")
+                .add_column(ColumnDef::new("user_id", ColumnType::Integer))
+                .validate(&schema),
+            Err(Error::ColumnExists("orders", "user_id"))
+        );
+        assert_eq!(
+            AlterTableQuery::new("orders")
+                .add_column(
+                    ColumnDef::new("coupon_id", ColumnType::Integer)
+                        .references("coupons", "id", None)
+                )
+                .validate(&schema),
+            Err(Error::UnknownTable("coupons"))
+        );
+        assert_eq!(
+            AlterTableQuery::new("orders")
+                .rename_to("users")
+                .validate(&schema),
+            Err(Error::TableExists("users"))
+        );
+    }
+
+    #[test]
+    fn test_validate_drop_index_and_refresh() {
+        let schema = schema().view("user_counts");
+        assert_eq!(
+            DropIndex::new("idx_orders").on("orders").validate(&schema),
+            Ok(())
+        );
+        assert_eq!(
+            DropIndex::new("idx_invoices")
+                .on("invoices")
+                .validate(&schema),
+            Err(Error::UnknownTable("invoices"))
+        );
+        assert_eq!(
+            DropIndex::new("idx_invoices")
+                .on("invoices")
+                .if_exists()
+                .validate(&schema),
+            Ok(())
+        );
+        assert_eq!(
+            RefreshMaterializedViewQuery::new("user_counts").validate(&schema),
+            Ok(())
+        );
+        assert_eq!(
+            RefreshMaterializedViewQuery::new("order_counts").validate(&schema),
+            Err(Error::UnknownView("order_counts"))
+        );
+    }
+}
@@ -0,0 +1,561 @@
+use crate::alter_table::{AlterOperation, AlterTableQuery};
+use crate::create_table::{ColumnDef, ColumnType, TableConstraint};
+use crate::dialect::Dialect;
+use crate::drop::DropTable;
+use crate::error::Error;
+use crate::schema::{Schema, TableDef};
+use crate::Result;
+use crate::SqlBuilder;
+
+/// One statement of a migration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationStep {
+    pub table: &'static str,
+    pub sql: String,
+    /// Whether the statement drops or rewrites existing data.
+    pub destructive: bool,
+}
+
+/// Migration from a current to a desired schema: new tables are created first,
+/// then existing tables are altered, then removed tables are dropped.
+/// Renames can not be told apart from a drop and an add, and are migrated as such.
+#[derive(Debug, Clone)]
+pub struct SchemaDiff {
+    current: Schema,
+    desired: Schema,
+    allow_destructive: bool,
+    dialect: Dialect,
+}
+
+impl SchemaDiff {
+    pub fn new(current: &Schema, desired: &Schema) -> Self {
+        Self {
+            current: current.clone(),
+            desired: desired.clone(),
+            allow_destructive: false,
+            dialect: Dialect::default(),
+        }
+    }
+
+    /// Destructive steps (dropped tables and columns, narrowed column types)
+    /// refuse to build unless they are allowed explicitly.
+    pub fn allow_destructive(mut self) -> Self {
+        self.allow_destructive = true;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// All steps of the migration with their destructive flag, whether allowed or not.
+    pub fn steps(&self) -> Result<Vec<MigrationStep>> {
+        let mut steps = Vec::new();
+        for table in self.created_tables() {
+            steps.push(MigrationStep {
+                table: table.name,
+                sql: table.create_table().dialect(self.dialect).build()?,
+                destructive: false,
+            });
+        }
+        for desired in &self.desired.tables {
+            if let Some(current) = self.current.get_table(desired.name) {
+                steps.extend(self.alter_steps(current, desired)?);
+            }
+        }
+        for current in self.dropped_tables() {
+            steps.push(MigrationStep {
+                table: current.name,
+                sql: DropTable::new(current.name)
+                    .confirm()
+                    .dialect(self.dialect)
+                    .build()?,
+                destructive: true,
+            });
+        }
+        Ok(steps)
+    }
+
+    pub fn build_statements(&self) -> Result<Vec<String>> {
+        let steps = self.steps()?;
+        if !self.allow_destructive {
+            if let Some(step) = steps.iter().find(|step| step.destructive) {
+                return Err(Error::DestructiveMigration(step.table));
+            }
+        }
+        Ok(steps.into_iter().map(|step| step.sql).collect())
+    }
+
+    /// New tables, each after the tables its foreign keys reference.
+    fn created_tables(&self) -> Vec<&TableDef> {
+        dependency_order(
+            self.desired
+                .tables
+                .iter()
+                .filter(|table| self.current.get_table(table.name).is_none())
+                .collect(),
+        )
+    }
+
+    /// Removed tables, each before the tables its foreign keys reference.
+    fn dropped_tables(&self) -> Vec<&TableDef> {
+        let mut ordered = dependency_order(
+            self.current
+                .tables
+                .iter()
+                .filter(|table| self.desired.get_table(table.name).is_none())
+                .collect(),
+        );
+        ordered.reverse();
+        ordered
+    }
+
+    fn alter_steps(&self, current: &TableDef, desired: &TableDef) -> Result<Vec<MigrationStep>> {
+        let table = desired.name;
+        let mut operations = Vec::new();
+
+        let current_constraints = constraints(current);
+        let desired_constraints = constraints(desired);
+        for (name, constraint) in &current_constraints {
+            if desired_constraints.contains(&(*name, constraint.clone())) {
+                continue;
+            }
+            match name {
+                Some(name) => operations.push((AlterOperation::DropConstraint(name), false)),
+                None => return Err(Error::UnnamedConstraintChange(table)),
+            }
+        }
+
+        for column in &desired.columns {
+            let Some(before) = current.get_column(column.name) else {
+                operations.push((AlterOperation::AddColumn(column.clone()), false));
+                continue;
+            };
+            if (
+                before.primary_key,
+                before.unique,
+                &before.check,
+                before.auto_increment,
+            ) != (
+                column.primary_key,
+                column.unique,
+                &column.check,
+                column.auto_increment,
+            ) {
+                return Err(Error::UnsupportedColumnChange(table, column.name));
+            }
+            // types and defaults only differ if their native rendering does,
+            // e.g. SQLite has no distinct BIGINT
+            let retyped =
+                before.column_type.to_sql(self.dialect) != column.column_type.to_sql(self.dialect);
+            let redefaulted = before
+                .default
+                .as_ref()
+                .map(|default| default.to_sql(self.dialect))
+                != column
+                    .default
+                    .as_ref()
+                    .map(|default| default.to_sql(self.dialect));
+            let destructive = retyped && !is_widening(before.column_type, column.column_type);
+            if self.dialect == Dialect::MySql {
+                if retyped || before.not_null != column.not_null || redefaulted {
+                    let mut modified = column.clone();
+                    modified.references = None;
+                    operations.push((AlterOperation::ModifyColumn(modified), destructive));
+                }
+                continue;
+            }
+            if retyped {
+                operations.push((
+                    AlterOperation::ChangeColumnType(column.name, column.column_type),
+                    destructive,
+                ));
+            }
+            if before.not_null != column.not_null {
+                operations.push(match column.not_null {
+                    true => (AlterOperation::SetNotNull(column.name), false),
+                    false => (AlterOperation::DropNotNull(column.name), false),
+                });
+            }
+            if redefaulted {
+                operations.push(match &column.default {
+                    Some(default) => (
+                        AlterOperation::SetDefault(column.name, default.clone()),
+                        false,
+                    ),
+                    None => (AlterOperation::DropDefault(column.name), false),
+                });
+            }
+        }
+
+        for column in &current.columns {
+            if desired.get_column(column.name).is_none() {
+                operations.push((AlterOperation::DropColumn(column.name), true));
+            }
+        }
+
+        for (name, constraint) in &desired_constraints {
+            if current_constraints.contains(&(*name, constraint.clone())) {
+                continue;
+            }
+            match name {
+                Some(name) => operations.push((
+                    AlterOperation::AddConstraint(name, constraint.clone()),
+                    false,
+                )),
+                // added along with the new column holding the reference
+                None if is_added_reference(current, desired, constraint) => {}
+                None => return Err(Error::UnnamedConstraintChange(table)),
+            }
+        }
+
+        if operations.is_empty() {
+            return Ok(Vec::new());
+        }
+        let alter = |operations: &[(AlterOperation, bool)]| {
+            let mut query = AlterTableQuery::new(table).dialect(self.dialect);
+            query.operations = operations
+                .iter()
+                .map(|(operation, _)| operation.clone())
+                .collect();
+            query.rebuild_from(current.create_table())
+        };
+        let query = alter(&operations);
+        if query.requires_rebuild() {
+            // SQLite rewrites the whole table in a single script
+            let destructive = operations.iter().any(|(_, destructive)| *destructive);
+            return Ok(vec![MigrationStep {
+                table,
+                sql: query.build()?,
+                destructive,
+            }]);
+        }
+        let mut steps = Vec::new();
+        for operation in &operations {
+            for sql in alter(std::slice::from_ref(operation)).build_statements()? {
+                steps.push(MigrationStep {
+                    table,
+                    sql,
+                    destructive: operation.1,
+                });
+            }
+        }
+        Ok(steps)
+    }
+}
+
+impl SqlBuilder for SchemaDiff {
+    fn build(&self) -> Result<String> {
+        Ok(self.build_statements()?.join("; "))
+    }
+}
+
+/// Table constraints together with the column level foreign keys,
+/// `CreateTableQuery` renders both the same way.
+fn constraints(table: &TableDef) -> Vec<(Option<&'static str>, TableConstraint)> {
+    let mut constraints = table.constraints.clone();
+    for column in &table.columns {
+        if let Some(foreign_key) = &column.references {
+            constraints.push((None, TableConstraint::ForeignKey(foreign_key.clone())));
+        }
+    }
+    constraints
+}
+
+fn foreign_keys(table: &TableDef) -> Vec<&'static str> {
+    constraints(table)
+        .into_iter()
+        .filter_map(|(_, constraint)| match constraint {
+            TableConstraint::ForeignKey(foreign_key) => Some(foreign_key.table),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_added_reference(
+    current: &TableDef,
+    desired: &TableDef,
+    constraint: &TableConstraint,
+) -> bool {
+    desired.columns.iter().any(|column: &ColumnDef| {
+        current.get_column(column.name).is_none()
+            && column.references.as_ref().is_some_and(|foreign_key| {
+                *constraint == TableConstraint::ForeignKey(foreign_key.clone())
+            })
+    })
+}
+
+/// Whether every value of type `from` is kept as is by type `to`.
+fn is_widening(from: ColumnType, to: ColumnType) -> bool {
+    match (from, to) {
+        (ColumnType::Integer, ColumnType::BigInteger) => true,
+        (ColumnType::Varchar(from), ColumnType::Varchar(to)) => from <= to,
+        (ColumnType::Varchar(_), ColumnType::Text) => true,
+        (
+            ColumnType::Decimal(from_precision, from_scale),
+            ColumnType::Decimal(to_precision, to_scale),
+        ) => {
+            from_scale <= to_scale
+                && integer_digits(from_precision, from_scale)
+                    <= integer_digits(to_precision, to_scale)
+        }
+        (ColumnType::Integer | ColumnType::BigInteger, ColumnType::Decimal(precision, scale)) => {
+            integer_digits(precision, scale) >= 19
+        }
+        _ => false,
+    }
+}
+
+/// Digits left of the decimal point, negative when the scale exceeds the precision.
+fn integer_digits(precision: u8, scale: u8) -> i16 {
+    i16::from(precision) - i16::from(scale)
+}
+
+/// Orders `pending` so each table comes after the tables its foreign keys reference.
+fn dependency_order(mut pending: Vec<&TableDef>) -> Vec<&TableDef> {
+    let mut ordered = Vec::new();
+    while !pending.is_empty() {
+        let ready = pending
+            .iter()
+            .position(|table| {
+                foreign_keys(table).iter().all(|referenced| {
+                    *referenced == table.name
+                        || pending.iter().all(|other| other.name != *referenced)
+                })
+            })
+            // a reference cycle, fall back to the declared order
+            .unwrap_or(0);
+        ordered.push(pending.remove(ready));
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_table::{ColumnDefault, ForeignKey, ForeignKeyAction};
+
+    fn users() -> TableDef {
+        TableDef::new("users")
+            .column(ColumnDef::new("id", ColumnType::BigInteger).primary_key())
+            .column(ColumnDef::new("name", ColumnType::Varchar(32)).not_null())
+            .column(ColumnDef::new("nickname", ColumnType::Text))
+    }
+
+    #[test]
+    fn test_no_changes() {
+        let schema = Schema::new().table(users());
+        assert_eq!(schema.diff(&schema).steps(), Ok(vec![]));
+        assert_eq!(schema.diff(&schema).build(), Ok(String::new()));
+
+        // the same schema read back from its DDL
+        for dialect in [Dialect::MySql, Dialect::Postgres, Dialect::Sqlite] {
+            let ddl = users().create_table().dialect(dialect).build().unwrap();
+            let parsed = Schema::from_ddl(&ddl).unwrap();
+            assert_eq!(parsed.diff(&schema).dialect(dialect).steps(), Ok(vec![]));
+        }
+    }
+
+    #[test]
+    fn test_create_and_drop_tables() {
+        let current = Schema::new()
+            .table(TableDef::new("logs").column(ColumnDef::new("message", ColumnType::Text)));
+        let orders = TableDef::new("orders")
+            .column(ColumnDef::new("id", ColumnType::BigInteger).primary_key())
+            .column(
+                ColumnDef::new("user_id", ColumnType::BigInteger).references(
+                    "users",
+                    "id",
+                    Some(ForeignKeyAction::Cascade),
+                ),
+            );
+        let desired = Schema::new().table(orders).table(users());
+        let diff = current.diff(&desired).dialect(Dialect::Postgres);
+        let steps = diff.steps().unwrap();
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| (step.table, step.destructive))
+                .collect::<Vec<_>>(),
+            vec![("users", false), ("orders", false), ("logs", true)]
+        );
+        assert_eq!(steps[2].sql, "DROP TABLE logs");
+        assert_eq!(diff.build(), Err(Error::DestructiveMigration("logs")));
+        assert_eq!(
+            diff.allow_destructive().build_statements().unwrap().len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_drop_tables_in_dependency_order() {
+        let orders = TableDef::new("orders")
+            .column(ColumnDef::new("id", ColumnType::BigInteger).primary_key())
+            .column(
+                ColumnDef::new("user_id", ColumnType::BigInteger).references("users", "id", None),
+            );
+        let current = Schema::new().table(orders).table(users());
+        let steps = current
+            .diff(&Schema::new())
+            .dialect(Dialect::Postgres)
+            .steps()
+            .unwrap();
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| step.sql.as_str())
+                .collect::<Vec<_>>(),
+            vec!["DROP TABLE orders", "DROP TABLE users"]
+        );
+    }
+
+    #[test]
+    fn test_alter_columns() {
+        let current = Schema::new().table(users());
+        let desired = Schema::new().table(
+            TableDef::new("users")
+                .column(ColumnDef::new("id", ColumnType::BigInteger).primary_key())
+                .column(
+                    ColumnDef::new("name", ColumnType::Varchar(64))
+                        .not_null()
+                        .default(&""),
+                )
+                .column(ColumnDef::new("age", ColumnType::Integer)),
+        );
+        let diff = current.diff(&desired).dialect(Dialect::Postgres);
+        assert_eq!(
+            diff.steps().unwrap(),
+            vec![
+                MigrationStep {
+                    table: "users",
+                    sql: "ALTER TABLE users ALTER COLUMN name TYPE VARCHAR(64)".to_string(),
+                    destructive: false,
+                },
+                MigrationStep {
+                    table: "users",
+                    sql: "ALTER TABLE users ALTER COLUMN name SET DEFAULT ''".to_string(),
+                    destructive: false,
+                },
+                MigrationStep {
+                    table: "users",
+                    sql: "ALTER TABLE users ADD COLUMN age INTEGER".to_string(),
+                    destructive: false,
+                },
+                MigrationStep {
+                    table: "users",
+                    sql: "ALTER TABLE users DROP COLUMN nickname".to_string(),
+                    destructive: true,
+                },
+            ]
+        );
+        assert_eq!(diff.build(), Err(Error::DestructiveMigration("users")));
+
+        assert_eq!(
+            current
+                .diff(&desired)
+                .dialect(Dialect::MySql)
+                .allow_destructive()
+                .build_statements(),
+            Ok(vec![
+                "ALTER TABLE users MODIFY COLUMN name VARCHAR(64) NOT NULL DEFAULT ''".to_string(),
+                "ALTER TABLE users ADD COLUMN age INT".to_string(),
+                "ALTER TABLE users DROP COLUMN nickname".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_narrowing_is_destructive() {
+        let current = Schema::new().table(users());
+        let mut narrowed = users();
+        narrowed.columns[1].column_type = ColumnType::Varchar(16);
+        let desired = Schema::new().table(narrowed);
+        let steps = current
+            .diff(&desired)
+            .dialect(Dialect::Postgres)
+            .steps()
+            .unwrap();
+        assert!(steps[0].destructive);
+    }
+
+    #[test]
+    fn test_decimal_scale_above_precision() {
+        assert!(is_widening(
+            ColumnType::Decimal(2, 5),
+            ColumnType::Decimal(3, 5)
+        ));
+        assert!(!is_widening(
+            ColumnType::Decimal(2, 5),
+            ColumnType::Decimal(10, 2)
+        ));
+        assert!(!is_widening(
+            ColumnType::Decimal(10, 2),
+            ColumnType::Decimal(2, 5)
+        ));
+        assert!(!is_widening(
+            ColumnType::BigInteger,
+            ColumnType::Decimal(2, 5)
+        ));
+    }
+
+    #[test]
+    fn test_sqlite_rebuild() {
+        let current = Schema::new().table(users());
+        let mut desired = users();
+        desired.columns[2].default = Some(ColumnDefault::Value("'anonymous'".to_string()));
+        let desired = Schema::new().table(desired);
+        let steps = current
+            .diff(&desired)
+            .dialect(Dialect::Sqlite)
+            .steps()
+            .unwrap();
+        assert_eq!(steps.len(), 1);
+        assert!(!steps[0].destructive);
+        assert!(steps[0]
+            .sql
+            .starts_with("PRAGMA foreign_keys = OFF; CREATE TABLE users_rebuild"));
+    }
+
+    #[test]
+    fn test_constraints() {
+        let current = Schema::new().table(users().constraint(
+            Some("name_check"),
+            TableConstraint::Check("name <> ''".to_string()),
+        ));
+        let desired = Schema::new().table(users().constraint(
+            Some("name_check"),
+            TableConstraint::Check("length(name) > 1".to_string()),
+        ));
+        assert_eq!(
+            current
+                .diff(&desired)
+                .dialect(Dialect::Postgres)
+                .build_statements(),
+            Ok(vec![
+                "ALTER TABLE users DROP CONSTRAINT name_check".to_string(),
+                "ALTER TABLE users ADD CONSTRAINT name_check CHECK (length(name) > 1)".to_string(),
+            ])
+        );
+
+        let desired = Schema::new().table(users().constraint(
+            None,
+            TableConstraint::ForeignKey(ForeignKey::new(&["name"], "names", &["name"])),
+        ));
+        assert_eq!(
+            current.diff(&desired).build(),
+            Err(Error::UnnamedConstraintChange("users"))
+        );
+    }
+
+    #[test]
+    fn test_unsupported_column_change() {
+        let current = Schema::new().table(users());
+        let mut desired = users();
+        desired.columns[1].unique = true;
+        assert_eq!(
+            current.diff(&Schema::new().table(desired)).build(),
+            Err(Error::UnsupportedColumnChange("users", "name"))
+        );
+    }
+}
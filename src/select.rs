@@ -1,15 +1,34 @@
+use crate::dialect::Dialect;
 use crate::error::Error;
+use crate::evaluate::Row;
+use crate::keyset::{keyset_condition, Cursor};
+use crate::schema::{Schema, Validate};
 use crate::Result;
-use crate::{where_clause::WhereClause, SqlBuilder};
+use crate::{
+    join::Join,
+    where_clause::{where_sql, WhereClause},
+    SqlBuilder,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SelectQuery {
-    table: &'static str,
-    columns: Vec<&'static str>,
-    where_clause: Option<WhereClause>,
-    limit: Option<u64>,
-    offset: Option<u64>,
-    order_by: Vec<OrderBy>,
+    pub(crate) table: &'static str,
+    pub(crate) columns: Vec<&'static str>,
+    pub(crate) where_clause: Option<WhereClause>,
+    pub(crate) limit: Option<u64>,
+    pub(crate) offset: Option<u64>,
+    pub(crate) order_by: Vec<OrderBy>,
+    pub(crate) soft_delete: Option<&'static str>,
+    deleted: Deleted,
+    pub(crate) after: Option<Cursor>,
+}
+
+/// Which soft-deleted rows a [`SelectQuery`] returns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Deleted {
+    Exclude,
+    Include,
+    Only,
 }
 
 impl SelectQuery {
@@ -21,6 +40,9 @@ impl SelectQuery {
             limit: None,
             offset: None,
             order_by: Vec::new(),
+            soft_delete: None,
+            deleted: Deleted::Exclude,
+            after: None,
         }
     }
 
@@ -80,6 +102,47 @@ impl SelectQuery {
         self.columns.push("COUNT(*)");
         self
     }
+
+    /// Marks `table` as soft-deleted through `column`, a timestamp set instead of
+    /// deleting the row. Only rows where `column IS NULL` are returned by default.
+    pub fn soft_delete(&mut self, column: &'static str) -> &mut Self {
+        self.soft_delete = Some(column);
+        self
+    }
+
+    /// Returns soft-deleted rows too.
+    pub fn with_deleted(&mut self) -> &mut Self {
+        self.deleted = Deleted::Include;
+        self
+    }
+
+    /// Returns soft-deleted rows only, `column IS NOT NULL`.
+    pub fn only_deleted(&mut self) -> &mut Self {
+        self.deleted = Deleted::Only;
+        self
+    }
+
+    /// Keyset pagination, selects the rows after `cursor` in the `ORDER BY` order
+    /// instead of skipping rows with `OFFSET`.
+    pub fn after(&mut self, cursor: Cursor) -> &mut Self {
+        self.after = Some(cursor);
+        self
+    }
+
+    /// The cursor of `row`, usually the last row of a page, to pass to `after`
+    /// for the next one.
+    pub fn cursor<R: Row>(&self, row: &R) -> Result<Cursor> {
+        Cursor::from_row(&self.order_by, row)
+    }
+
+    fn deleted_condition(&self) -> Option<WhereClause> {
+        let column = self.soft_delete?;
+        match self.deleted {
+            Deleted::Exclude => Some(WhereClause::is_null(column)),
+            Deleted::Include => None,
+            Deleted::Only => Some(WhereClause::is_not_null(column)),
+        }
+    }
 }
 
 impl SqlBuilder for SelectQuery {
@@ -87,31 +150,26 @@ impl SqlBuilder for SelectQuery {
         let mut sql = String::new();
         sql.push_str("SELECT ");
         if self.columns.is_empty() {
-            sql.push_str("*");
+            sql.push('*');
         } else {
             sql.push_str(&self.columns.join(", "));
         }
         sql.push_str(" FROM ");
         sql.push_str(self.table);
-        if let Some(where_clause) = &self.where_clause {
-            sql.push_str(" WHERE ");
-            sql.push_str(&where_clause.build()?);
-        }
-        if !self.order_by.is_empty() {
-            sql.push_str(" ORDER BY ");
-            sql.push_str(
-                &self
-                    .order_by
-                    .iter()
-                    .map(|o| o.build())
-                    .collect::<Result<Vec<String>>>()?
-                    .join(", "),
-            );
-        }
-        if let Some(limit) = self.limit {
-            sql.push_str(" LIMIT ");
-            sql.push_str(&limit.to_string());
-        }
+        let where_clause = self
+            .where_clause
+            .iter()
+            .cloned()
+            .chain(self.deleted_condition())
+            .chain(
+                self.after
+                    .as_ref()
+                    .map(|cursor| keyset_condition(&self.order_by, cursor))
+                    .transpose()?,
+            )
+            .reduce(|left, right| left.and(right));
+        sql.push_str(&where_sql(where_clause.as_ref())?);
+        sql.push_str(&order_by_limit(&self.order_by, self.limit)?);
         if let Some(offset) = self.offset {
             sql.push_str(" OFFSET ");
             sql.push_str(&offset.to_string());
@@ -120,16 +178,65 @@ impl SqlBuilder for SelectQuery {
     }
 }
 
-#[derive(Debug, Clone)]
-enum Order {
+/// ` ORDER BY ... LIMIT n`, each part only when present.
+pub(crate) fn order_by_limit(order_by: &[OrderBy], limit: Option<u64>) -> Result<String> {
+    let mut sql = String::new();
+    if !order_by.is_empty() {
+        sql.push_str(" ORDER BY ");
+        sql.push_str(
+            &order_by
+                .iter()
+                .map(|o| o.build())
+                .collect::<Result<Vec<String>>>()?
+                .join(", "),
+        );
+    }
+    if let Some(limit) = limit {
+        sql.push_str(" LIMIT ");
+        sql.push_str(&limit.to_string());
+    }
+    Ok(sql)
+}
+
+/// The column identifying rows when `UPDATE`/`DELETE ... LIMIT` is emulated,
+/// `key` when set and the dialect's implicit row identifier otherwise.
+pub(crate) fn limit_key(key: Option<&'static str>, dialect: Dialect) -> Result<&'static str> {
+    key.or(dialect.row_key()).ok_or(Error::UnsupportedByDialect(
+        "emulating LIMIT without a limit_key",
+        dialect,
+    ))
+}
+
+/// `SELECT table.key FROM table [JOIN ...] WHERE ... [ORDER BY ...] [LIMIT n]`,
+/// used to emulate `UPDATE`/`DELETE ... LIMIT` with `key IN (subquery)`.
+pub(crate) fn key_subquery(
+    table: &'static str,
+    key: &'static str,
+    joins: &[Join],
+    where_clause: Option<&WhereClause>,
+    order_by: &[OrderBy],
+    limit: Option<u64>,
+) -> Result<String> {
+    let mut sql = format!("SELECT {}.{} FROM {}", table, key, table);
+    for join in joins {
+        sql.push(' ');
+        sql.push_str(&join.build()?);
+    }
+    sql.push_str(&where_sql(where_clause)?);
+    sql.push_str(&order_by_limit(order_by, limit)?);
+    Ok(sql)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Order {
     Asc,
     Desc,
 }
 
-#[derive(Debug, Clone)]
-struct OrderBy {
-    columns: Vec<&'static str>,
-    order: Order,
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderBy {
+    pub columns: Vec<&'static str>,
+    pub order: Order,
 }
 
 impl OrderBy {
@@ -170,10 +277,35 @@ impl SqlBuilder for OrderBy {
     }
 }
 
+impl Validate for SelectQuery {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        let tables = [self.table];
+        schema.require_table(self.table)?;
+        for column in &self.columns {
+            schema.resolve_column(&tables, column)?;
+        }
+        if let Some(where_clause) = &self.where_clause {
+            schema.validate_where_clause(&tables, where_clause)?;
+        }
+        for order_by in &self.order_by {
+            for column in &order_by.columns {
+                schema.resolve_column(&tables, column)?;
+            }
+        }
+        if let Some(column) = self.soft_delete {
+            schema.resolve_column(&tables, column)?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::where_clause::WhereClause;
+    use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+    use base64::Engine;
+    use serde_json::json;
 
     #[test]
     fn test_basic_select() {
@@ -264,4 +396,144 @@ mod test {
             "SELECT id, name, email FROM users WHERE id = 1 ORDER BY id DESC OFFSET 5"
         );
     }
+
+    #[test]
+    fn test_select_soft_delete() {
+        let mut select = SelectQuery::new("users");
+        select
+            .where_clause(WhereClause::equals("id", 1).or_equals("id", 2))
+            .soft_delete("deleted_at");
+        assert_eq!(
+            select.build(),
+            Ok("SELECT * FROM users WHERE (id = 1 OR id = 2) AND deleted_at IS NULL".to_string())
+        );
+        assert_eq!(
+            select.clone().only_deleted().build(),
+            Ok(
+                "SELECT * FROM users WHERE (id = 1 OR id = 2) AND deleted_at IS NOT NULL"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            select.with_deleted().build(),
+            Ok("SELECT * FROM users WHERE id = 1 OR id = 2".to_string())
+        );
+        let mut select = SelectQuery::new("users");
+        select.soft_delete("deleted_at").count();
+        assert_eq!(
+            select.build(),
+            Ok("SELECT COUNT(*) FROM users WHERE deleted_at IS NULL".to_string())
+        );
+    }
+
+    #[test]
+    fn test_select_after() {
+        let mut select = SelectQuery::new("posts");
+        select
+            .where_clause(WhereClause::equals("author_id", 3))
+            .order_by_columns(&["created_at", "id"])
+            .limit(20);
+        let values = vec![json!("2024-01-02 03:04:05"), json!(42)];
+        let cursor = Cursor::new(&select.order_by, values.clone()).unwrap();
+        assert_eq!(
+            select.clone().after(cursor).build(),
+            Ok("SELECT * FROM posts WHERE author_id = 3 AND (created_at, id) > ('2024-01-02 03:04:05', 42) ORDER BY created_at, id ASC LIMIT 20".to_string())
+        );
+        select.desc().unwrap();
+        let cursor = Cursor::new(&select.order_by, values.clone()).unwrap();
+        assert_eq!(
+            select.clone().after(cursor).build(),
+            Ok("SELECT * FROM posts WHERE author_id = 3 AND (created_at > '2024-01-02 03:04:05' OR created_at = '2024-01-02 03:04:05' AND id < 42) ORDER BY created_at, id DESC LIMIT 20".to_string())
+        );
+        let mut select = SelectQuery::new("posts");
+        select
+            .order_by("created_at")
+            .desc()
+            .unwrap()
+            .order_by("id")
+            .desc()
+            .unwrap();
+        let cursor = Cursor::new(&select.order_by, values.clone()).unwrap();
+        select.after(cursor);
+        assert_eq!(
+            select.build(),
+            Ok("SELECT * FROM posts WHERE (created_at, id) < ('2024-01-02 03:04:05', 42) ORDER BY created_at DESC, id DESC".to_string())
+        );
+        let mut select = SelectQuery::new("posts");
+        select.order_by("created_at").desc().unwrap().order_by("id");
+        let cursor = Cursor::new(&select.order_by, values).unwrap();
+        select.after(cursor);
+        assert_eq!(
+            select.build(),
+            Ok("SELECT * FROM posts WHERE created_at < '2024-01-02 03:04:05' OR created_at = '2024-01-02 03:04:05' AND id > 42 ORDER BY created_at DESC, id ASC".to_string())
+        );
+    }
+
+    #[test]
+    fn test_select_after_errors() {
+        let cursor = Cursor::new(&[OrderBy::new("id")], vec![json!(42)]).unwrap();
+        let mut select = SelectQuery::new("posts");
+        select.after(cursor.clone());
+        assert_eq!(select.build(), Err(Error::NoOrderByClause));
+        select.order_by_columns(&["created_at", "id"]);
+        assert_eq!(select.build(), Err(Error::CursorMismatch(1, 2)));
+        let mut select = SelectQuery::new("posts");
+        select.order_by("title").after(cursor.clone());
+        assert_eq!(select.build(), Err(Error::CursorOrderMismatch));
+        select.desc().unwrap().order_by("id").after(cursor.clone());
+        assert_eq!(select.build(), Err(Error::CursorMismatch(1, 2)));
+        let mut select = SelectQuery::new("posts");
+        select.order_by("id").desc().unwrap().after(cursor.clone());
+        assert_eq!(select.build(), Err(Error::CursorOrderMismatch));
+        assert_eq!(
+            Cursor::new(&[OrderBy::new("id")], vec![json!(1), json!(2)]),
+            Err(Error::CursorMismatch(2, 1))
+        );
+        let mut select = SelectQuery::new("posts");
+        select.order_by_columns(&["created_at", "id"]).after(cursor);
+        assert_eq!(
+            select.cursor(&json!({"id": 7})),
+            Err(Error::MissingRowColumn("created_at"))
+        );
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let mut select = SelectQuery::new("posts");
+        select.order_by("created_at").order_by("id");
+        let row = json!({"id": 42, "created_at": "2024-01-02", "title": "Hello"});
+        let cursor = select.cursor(&row).unwrap();
+        assert_eq!(cursor.values(), &[json!("2024-01-02"), json!(42)]);
+        assert_eq!(Cursor::decode(&cursor.encode()), Ok(cursor.clone()));
+        assert_eq!(
+            select.after(cursor).build(),
+            Ok("SELECT * FROM posts WHERE (created_at, id) > ('2024-01-02', 42) ORDER BY created_at ASC, id ASC".to_string())
+        );
+        assert_eq!(Cursor::decode("not a cursor"), Err(Error::InvalidCursor));
+        let title = [OrderBy::new("title")];
+        assert_eq!(
+            Cursor::new(&title, vec![json!(null)]),
+            Err(Error::InvalidCursor)
+        );
+        for text in ["x\\' OR 1=1 -- ", "x\u{0}y", "x\ny"] {
+            assert_eq!(
+                Cursor::new(&title, vec![json!(text)]),
+                Err(Error::InvalidCursor)
+            );
+            let forged = URL_SAFE_NO_PAD.encode(json!([["title ASC"], [text]]).to_string());
+            assert_eq!(Cursor::decode(&forged), Err(Error::InvalidCursor));
+        }
+        let cursor = Cursor::new(&title, vec![json!("x' OR '1'='1")]).unwrap();
+        let mut select = SelectQuery::new("posts");
+        select
+            .order_by("title")
+            .after(Cursor::decode(&cursor.encode()).unwrap());
+        assert_eq!(
+            select.build(),
+            Ok(
+                "SELECT * FROM posts WHERE title > 'x'' OR ''1''=''1' ORDER BY title ASC"
+                    .to_string()
+            )
+        );
+    }
 }
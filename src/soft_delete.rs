@@ -0,0 +1,174 @@
+use crate::delete::DeleteQuery;
+use crate::parser::Statement;
+use crate::select::SelectQuery;
+use crate::tenant::table_ref;
+use crate::Result;
+use crate::SqlBuilder;
+
+/// Soft-deletes the rows of the configured tables through a timestamp column.
+///
+/// Marked deletes of these tables become `UPDATE ... SET column = NOW()`, physically
+/// deleting rows needs `DeleteQuery::force_delete`. Marked selects leave out deleted
+/// rows unless they ask for them with `with_deleted` or `only_deleted`. Queries that
+/// already name a soft-delete column keep it.
+#[derive(Debug, Clone)]
+pub struct SoftDelete {
+    column: &'static str,
+    tables: Vec<&'static str>,
+}
+
+impl SoftDelete {
+    pub fn new(column: &'static str) -> Self {
+        Self {
+            column,
+            tables: Vec::new(),
+        }
+    }
+
+    pub fn table(mut self, table: &'static str) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    pub fn tables(mut self, tables: &[&'static str]) -> Self {
+        self.tables.extend(tables);
+        self
+    }
+
+    pub fn is_soft_deleted(&self, table: &str) -> bool {
+        let (name, _) = table_ref(table);
+        self.tables
+            .iter()
+            .any(|soft_deleted| soft_deleted.eq_ignore_ascii_case(name))
+    }
+
+    pub fn mark<Q: Mark>(&self, query: Q) -> Q {
+        query.mark(self)
+    }
+
+    /// Wraps the query so it is marked when built.
+    pub fn marked<Q: Mark>(&self, query: Q) -> Marked<'_, Q> {
+        Marked {
+            soft_delete: self,
+            query,
+        }
+    }
+}
+
+/// Statements a `SoftDelete` can be applied to.
+pub trait Mark: Sized {
+    fn mark(self, soft_delete: &SoftDelete) -> Self;
+}
+
+impl Mark for SelectQuery {
+    fn mark(mut self, soft_delete: &SoftDelete) -> Self {
+        if self.soft_delete.is_none() && soft_delete.is_soft_deleted(self.table) {
+            self.soft_delete(soft_delete.column);
+        }
+        self
+    }
+}
+
+impl Mark for DeleteQuery {
+    fn mark(self, soft_delete: &SoftDelete) -> Self {
+        if self.soft_delete.is_none() && soft_delete.is_soft_deleted(self.table) {
+            return self.soft_delete(soft_delete.column);
+        }
+        self
+    }
+}
+
+impl Mark for Statement {
+    fn mark(self, soft_delete: &SoftDelete) -> Self {
+        match self {
+            Statement::Select(select) => Statement::Select(select.mark(soft_delete)),
+            Statement::Delete(delete) => Statement::Delete(delete.mark(soft_delete)),
+            statement => statement,
+        }
+    }
+}
+
+/// A query marked when built, see `SoftDelete::marked`.
+#[derive(Debug, Clone)]
+pub struct Marked<'a, Q> {
+    soft_delete: &'a SoftDelete,
+    query: Q,
+}
+
+impl<Q: Mark + SqlBuilder + Clone> SqlBuilder for Marked<'_, Q> {
+    fn build(&self) -> Result<String> {
+        self.query.clone().mark(self.soft_delete).build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::parser::SqlParser;
+    use crate::where_clause::WhereClause;
+
+    fn soft_delete() -> SoftDelete {
+        SoftDelete::new("deleted_at").tables(&["users", "orders"])
+    }
+
+    #[test]
+    fn test_mark_delete() {
+        let delete = DeleteQuery::new("users").where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            soft_delete().marked(delete.clone()).build(),
+            Ok(
+                "UPDATE users SET deleted_at = NOW() WHERE id = 1 AND deleted_at IS NULL"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            soft_delete().mark(delete.clone().force_delete()).build(),
+            Ok("DELETE FROM users WHERE id = 1".to_string())
+        );
+        assert_eq!(
+            soft_delete().mark(delete.soft_delete("removed_at")).build(),
+            Ok(
+                "UPDATE users SET removed_at = NOW() WHERE id = 1 AND removed_at IS NULL"
+                    .to_string()
+            )
+        );
+        let delete = DeleteQuery::new("countries").where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            soft_delete().mark(delete).build(),
+            Ok("DELETE FROM countries WHERE id = 1".to_string())
+        );
+        assert_eq!(
+            soft_delete().mark(DeleteQuery::new("orders")).build(),
+            Err(Error::NoDeleteConditions)
+        );
+    }
+
+    #[test]
+    fn test_mark_select() {
+        let mut select = SelectQuery::new("users");
+        select.where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            soft_delete().mark(select.clone()).build(),
+            Ok("SELECT * FROM users WHERE id = 1 AND deleted_at IS NULL".to_string())
+        );
+        select.with_deleted();
+        assert_eq!(
+            soft_delete().mark(select).build(),
+            Ok("SELECT * FROM users WHERE id = 1".to_string())
+        );
+        let statement = SqlParser::new()
+            .parse("DELETE FROM orders WHERE id = 2")
+            .unwrap();
+        let Statement::Delete(delete) = soft_delete().mark(statement) else {
+            panic!("a delete stays a delete");
+        };
+        assert_eq!(
+            delete.build(),
+            Ok(
+                "UPDATE orders SET deleted_at = NOW() WHERE id = 2 AND deleted_at IS NULL"
+                    .to_string()
+            )
+        );
+    }
+}
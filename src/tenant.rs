@@ -0,0 +1,693 @@
+use crate::delete::DeleteQuery;
+use crate::error::Error;
+use crate::insert::InsertQuery;
+use crate::join::Join;
+use crate::lexer::{self, Token, TokenKind};
+use crate::parser::Statement;
+use crate::repr::ToSqlRepr;
+use crate::select::SelectQuery;
+use crate::update::{Assignment, UpdateQuery};
+use crate::visit::Visitor;
+use crate::where_clause::{WhereClause, WhereCondition};
+use crate::Result;
+use crate::SqlBuilder;
+
+/// Restricts statements on tenant scoped tables to the rows of a single tenant.
+///
+/// Scoping adds `column = value` to the where clause for the statement's table and
+/// each joined scoped table, qualified by the alias or table name when there are joins, and
+/// adds the column to inserts. Sub queries in raw sql can not be rewritten, one that
+/// reads a scoped table without comparing its tenant column to the tenant, outside of
+/// any `OR` or `NOT`, fails with `UnscopedTable`.
+/// Writing another tenant's value into the column fails with `CrossTenantWrite`.
+#[derive(Debug, Clone)]
+pub struct TenantScope {
+    column: &'static str,
+    value: String,
+    tables: Vec<&'static str>,
+}
+
+impl TenantScope {
+    pub fn new<T: ToSqlRepr>(column: &'static str, value: T) -> Self {
+        Self {
+            column,
+            value: value.to_sql_repr(),
+            tables: Vec::new(),
+        }
+    }
+
+    pub fn table(mut self, table: &'static str) -> Self {
+        self.tables.push(table);
+        self
+    }
+
+    pub fn tables(mut self, tables: &[&'static str]) -> Self {
+        self.tables.extend(tables);
+        self
+    }
+
+    pub fn is_scoped(&self, table: &str) -> bool {
+        self.scoped_table(table).is_some()
+    }
+
+    pub fn scope<Q: Scope>(&self, query: Q) -> Result<Q> {
+        query.scope(self)
+    }
+
+    /// Wraps the query so it is scoped when built, and fails to build when it can not be.
+    pub fn scoped<Q: Scope>(&self, query: Q) -> Scoped<'_, Q> {
+        Scoped {
+            tenant: self,
+            query,
+        }
+    }
+
+    fn scoped_table(&self, table: &str) -> Option<&'static str> {
+        let (name, _) = table_ref(table);
+        self.tables
+            .iter()
+            .find(|scoped| scoped.eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// The tenant predicates for the statement's table and its joined tables, qualified
+    /// by the alias or name the query uses for each table when there are joins.
+    fn predicates(&self, table: &'static str, joins: &[Join]) -> Option<WhereClause> {
+        if joins.is_empty() {
+            return self.is_scoped(table).then(|| {
+                WhereClause::Condition(WhereCondition::Equals(self.column, self.value.clone()))
+            });
+        }
+        std::iter::once(table)
+            .chain(joins.iter().map(|join| join.table))
+            .filter(|table| self.is_scoped(table))
+            .map(|table| {
+                let (_, qualifier) = table_ref(table);
+                WhereClause::raw_condition(format!(
+                    "{}.{} = {}",
+                    qualifier, self.column, self.value
+                ))
+            })
+            .reduce(WhereClause::and)
+    }
+
+    fn check_assignments(
+        &self,
+        table: &'static str,
+        fields: &[(&'static str, Assignment)],
+    ) -> Result<()> {
+        if !self.is_scoped(table) {
+            return Ok(());
+        }
+        for (column, assignment) in fields {
+            let column = column.rsplit('.').next().unwrap_or(column);
+            let same_tenant =
+                matches!(assignment, Assignment::Value(value) if *value == self.value);
+            if column == self.column && !same_tenant {
+                return Err(Error::CrossTenantWrite(table));
+            }
+        }
+        Ok(())
+    }
+
+    /// The first scoped table raw sql reads without comparing the tenant column to
+    /// the tenant, qualified by the table's alias or name. The unqualified column only
+    /// counts when the sql reads a single table, and the comparison only when it is a
+    /// conjunct of its clause, not under an `OR` or `NOT`. Sql that can not be
+    /// tokenized fails closed on any scoped table it reads.
+    fn unscoped_table(&self, sql: &str) -> Option<&'static str> {
+        let Ok(tokens) = lexer::tokenize(sql) else {
+            return self.read_table(sql);
+        };
+        let reads = reads(&tokens);
+        reads.iter().find_map(|(table, qualifier)| {
+            let scoped = self.scoped_table(table)?;
+            let filtered = tokens.iter().enumerate().any(|(i, token)| {
+                if token.ident() != Some(self.column) {
+                    return false;
+                }
+                let qualified = i >= 2 && tokens[i - 1].is_symbol(".");
+                let refers = match qualified {
+                    true => tokens[i - 2]
+                        .ident()
+                        .is_some_and(|name| name.eq_ignore_ascii_case(qualifier)),
+                    false => reads.len() == 1,
+                };
+                let start = if qualified { i - 2 } else { i };
+                let is_tenant =
+                    |at: usize| tokens.get(at).is_some_and(|token| token.text == self.value);
+                let compared =
+                    tokens.get(i + 1).is_some_and(|token| token.is_symbol("=")) && is_tenant(i + 2);
+                let reversed =
+                    start >= 2 && tokens[start - 1].is_symbol("=") && is_tenant(start - 2);
+                refers
+                    && ((compared && is_conjunct(&tokens, start, i + 2))
+                        || (reversed && is_conjunct(&tokens, start - 2, i)))
+            });
+            (!filtered).then_some(scoped)
+        })
+    }
+
+    /// The first scoped table following a `FROM` or `JOIN` in `sql`.
+    fn read_table(&self, sql: &str) -> Option<&'static str> {
+        let words = sql
+            .split(|c: char| !(c.is_alphanumeric() || "_.`\"".contains(c)))
+            .filter(|word| !word.is_empty())
+            .collect::<Vec<&str>>();
+        words.windows(2).find_map(|pair| {
+            let reads =
+                pair[0].eq_ignore_ascii_case("FROM") || pair[0].eq_ignore_ascii_case("JOIN");
+            reads.then(|| self.scoped_table(pair[1])).flatten()
+        })
+    }
+}
+
+/// The table name and the name its columns are qualified with: `public.orders o`
+/// gives `orders` and `o`, `public.orders` gives `orders` and `public.orders`.
+pub(crate) fn table_ref(table: &str) -> (&str, &str) {
+    let mut words = table.split_whitespace();
+    let reference = words.next().unwrap_or(table);
+    let name = reference.rsplit('.').next().unwrap_or(reference);
+    let qualifier = words.last().unwrap_or(reference);
+    (
+        name.trim_matches(['`', '"']),
+        qualifier.trim_matches(['`', '"']),
+    )
+}
+
+const CLAUSE_KEYWORDS: [&str; 22] = [
+    "WHERE",
+    "JOIN",
+    "INNER",
+    "LEFT",
+    "RIGHT",
+    "FULL",
+    "CROSS",
+    "NATURAL",
+    "ON",
+    "USING",
+    "GROUP",
+    "ORDER",
+    "HAVING",
+    "LIMIT",
+    "OFFSET",
+    "UNION",
+    "EXCEPT",
+    "INTERSECT",
+    "WINDOW",
+    "FOR",
+    "SET",
+    "RETURNING",
+];
+
+/// Whether `tokens[start..=end]` is a conjunct of its clause: no `OR` sits next to it,
+/// or next to any parenthesized group around it, before the clause ends, and neither
+/// is negated by `NOT`.
+fn is_conjunct(tokens: &[Token], mut start: usize, mut end: usize) -> bool {
+    loop {
+        if start > 0 && tokens[start - 1].is_keyword("NOT") {
+            return false;
+        }
+        let left = group_bound(tokens[..start].iter().enumerate().rev(), "(", ")");
+        let right = group_bound(tokens.iter().enumerate().skip(end + 1), ")", "(");
+        match (left, right) {
+            (Some(Some(open)), Some(Some(close))) => (start, end) = (open, close),
+            (Some(_), Some(_)) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Walks `tokens` to the `open` parenthesis of the enclosing group, giving its index,
+/// or to the end of the clause, giving `None`. Fails on an `OR` at the walked level.
+fn group_bound<'a, 't: 'a>(
+    tokens: impl Iterator<Item = (usize, &'a Token<'t>)>,
+    open: &str,
+    close: &str,
+) -> Option<Option<usize>> {
+    let mut depth = 0;
+    for (i, token) in tokens {
+        if token.is_symbol(close) {
+            depth += 1;
+        } else if token.is_symbol(open) {
+            if depth == 0 {
+                return Some(Some(i));
+            }
+            depth -= 1;
+        } else if depth == 0 && token.is_keyword("OR") {
+            return None;
+        } else if depth == 0
+            && (token.is_symbol(";")
+                || CLAUSE_KEYWORDS
+                    .iter()
+                    .any(|keyword| token.is_keyword(keyword)))
+        {
+            return Some(None);
+        }
+    }
+    Some(None)
+}
+
+/// The tables read after each `FROM` and `JOIN`, including comma separated ones,
+/// as the table name and the alias or name its columns are qualified with. Derived
+/// tables are skipped, the tables they read are found at their own `FROM`.
+fn reads<'a>(tokens: &[Token<'a>]) -> Vec<(&'a str, &'a str)> {
+    let mut reads = Vec::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let from = token.is_keyword("FROM");
+        if !from && !token.is_keyword("JOIN") {
+            continue;
+        }
+        let mut j = i + 1;
+        loop {
+            while tokens
+                .get(j)
+                .is_some_and(|token| token.is_keyword("ONLY") || token.is_keyword("LATERAL"))
+            {
+                j += 1;
+            }
+            let read = match tokens.get(j) {
+                Some(token) if token.is_symbol("(") => {
+                    let mut depth = 0;
+                    while let Some(token) = tokens.get(j) {
+                        j += 1;
+                        if token.is_symbol("(") {
+                            depth += 1;
+                        } else if token.is_symbol(")") {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                    }
+                    None
+                }
+                Some(token) => match token.ident() {
+                    Some(mut name) => {
+                        j += 1;
+                        while tokens.get(j).is_some_and(|token| token.is_symbol(".")) {
+                            match tokens.get(j + 1).and_then(Token::ident) {
+                                Some(part) => name = part,
+                                None => break,
+                            }
+                            j += 2;
+                        }
+                        Some(name)
+                    }
+                    None => break,
+                },
+                None => break,
+            };
+            if tokens.get(j).is_some_and(|token| token.is_keyword("AS")) {
+                j += 1;
+            }
+            let alias = tokens.get(j).filter(|token| {
+                token.kind == TokenKind::QuotedIdent
+                    || (token.kind == TokenKind::Word
+                        && !CLAUSE_KEYWORDS
+                            .iter()
+                            .any(|keyword| token.is_keyword(keyword)))
+            });
+            if alias.is_some() {
+                j += 1;
+            }
+            if let Some(name) = read {
+                reads.push((name, alias.map_or(name, |alias| alias.text)));
+            }
+            if !(from && tokens.get(j).is_some_and(|token| token.is_symbol(","))) {
+                break;
+            }
+            j += 1;
+        }
+    }
+    reads
+}
+
+/// Statements a `TenantScope` can be applied to.
+pub trait Scope: Sized {
+    fn scope(self, tenant: &TenantScope) -> Result<Self>;
+}
+
+impl Scope for SelectQuery {
+    fn scope(mut self, tenant: &TenantScope) -> Result<Self> {
+        if let Some(predicate) = tenant.predicates(self.table, &[]) {
+            self.where_clause = Some(match self.where_clause {
+                Some(where_clause) => where_clause.and(predicate),
+                None => predicate,
+            });
+        }
+        check_raw_sql(tenant, |checker| checker.visit_select(&self))?;
+        Ok(self)
+    }
+}
+
+impl Scope for InsertQuery {
+    fn scope(mut self, tenant: &TenantScope) -> Result<Self> {
+        // without columns the insert fails to build anyway
+        if tenant.is_scoped(self.table) && !self.columns.is_empty() {
+            match self
+                .columns
+                .iter()
+                .position(|column| *column == tenant.column)
+            {
+                Some(i) => {
+                    if self.values.iter().any(|record| record[i] != tenant.value) {
+                        return Err(Error::CrossTenantWrite(self.table));
+                    }
+                }
+                None => {
+                    self.columns.push(tenant.column);
+                    for record in &mut self.values {
+                        record.push(tenant.value.clone());
+                    }
+                }
+            }
+        }
+        check_raw_sql(tenant, |checker| checker.visit_insert(&self))?;
+        Ok(self)
+    }
+}
+
+impl Scope for UpdateQuery {
+    fn scope(mut self, tenant: &TenantScope) -> Result<Self> {
+        tenant.check_assignments(self.table, &self.assignments())?;
+        // scoping must not turn an update of every row into a valid one
+        let full_table = self
+            .where_clause
+            .as_ref()
+            .is_none_or(WhereClause::is_tautology);
+        if let Some(predicate) = tenant.predicates(self.table, &self.joins) {
+            if full_table && !self.allow_full_table {
+                return Err(Error::NoUpdateConditions);
+            }
+            self.where_clause = Some(match self.where_clause {
+                Some(where_clause) => where_clause.and(predicate),
+                None => predicate,
+            });
+        }
+        check_raw_sql(tenant, |checker| checker.visit_update(&self))?;
+        Ok(self)
+    }
+}
+
+impl Scope for DeleteQuery {
+    fn scope(mut self, tenant: &TenantScope) -> Result<Self> {
+        let full_table = self
+            .where_clause
+            .as_ref()
+            .is_none_or(WhereClause::is_tautology);
+        if let Some(predicate) = tenant.predicates(self.table, &self.joins) {
+            if full_table && !self.allow_full_table {
+                return Err(Error::NoDeleteConditions);
+            }
+            self.where_clause = Some(match self.where_clause {
+                Some(where_clause) => where_clause.and(predicate),
+                None => predicate,
+            });
+        }
+        check_raw_sql(tenant, |checker| checker.visit_delete(&self))?;
+        Ok(self)
+    }
+}
+
+impl Scope for Statement {
+    fn scope(self, tenant: &TenantScope) -> Result<Self> {
+        Ok(match self {
+            Statement::Select(select) => Statement::Select(select.scope(tenant)?),
+            Statement::Insert(insert) => Statement::Insert(insert.scope(tenant)?),
+            Statement::Update(update) => Statement::Update(update.scope(tenant)?),
+            Statement::Delete(delete) => Statement::Delete(delete.scope(tenant)?),
+        })
+    }
+}
+
+/// A query scoped to a tenant when built, see `TenantScope::scoped`.
+#[derive(Debug, Clone)]
+pub struct Scoped<'a, Q> {
+    tenant: &'a TenantScope,
+    query: Q,
+}
+
+impl<Q: Scope + SqlBuilder + Clone> SqlBuilder for Scoped<'_, Q> {
+    fn build(&self) -> Result<String> {
+        self.query.clone().scope(self.tenant)?.build()
+    }
+}
+
+/// Looks for scoped tables read in raw sql: expressions, values, raw conditions, join
+/// conditions, and tables that are derived tables or comma separated lists.
+struct RawSqlChecker<'a> {
+    tenant: &'a TenantScope,
+    unscoped: Option<&'static str>,
+}
+
+impl Visitor for RawSqlChecker<'_> {
+    fn visit_table(&mut self, table: &'static str) {
+        // plain tables are scoped by the predicates
+        if table.contains(['(', ',']) {
+            self.visit_value(&format!("FROM {}", table));
+        }
+    }
+
+    fn visit_column(&mut self, column: &'static str) {
+        self.visit_value(column);
+    }
+
+    fn visit_value(&mut self, value: &str) {
+        if self.unscoped.is_none() {
+            self.unscoped = self.tenant.unscoped_table(value);
+        }
+    }
+}
+
+fn check_raw_sql<F: FnOnce(&mut RawSqlChecker)>(tenant: &TenantScope, visit: F) -> Result<()> {
+    let mut checker = RawSqlChecker {
+        tenant,
+        unscoped: None,
+    };
+    visit(&mut checker);
+    match checker.unscoped {
+        Some(table) => Err(Error::UnscopedTable(table)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dialect::Dialect;
+
+    fn tenant() -> TenantScope {
+        TenantScope::new("tenant_id", 7).tables(&["users", "orders"])
+    }
+
+    #[test]
+    fn test_scope_select() {
+        let mut select = SelectQuery::new("users");
+        select.where_clause(WhereClause::equals("id", 1).or_equals("id", 2));
+        assert_eq!(
+            tenant().scope(select).unwrap().build().unwrap(),
+            "SELECT * FROM users WHERE (id = 1 OR id = 2) AND tenant_id = 7"
+        );
+        let select = SelectQuery::new("users");
+        assert_eq!(
+            tenant().scope(select).unwrap().build().unwrap(),
+            "SELECT * FROM users WHERE tenant_id = 7"
+        );
+        let select = SelectQuery::new("countries");
+        assert_eq!(
+            tenant().scope(select).unwrap().build().unwrap(),
+            "SELECT * FROM countries"
+        );
+    }
+
+    #[test]
+    fn test_scope_sub_query() {
+        let tenant = tenant();
+        let mut select = SelectQuery::new("countries");
+        select.where_clause(WhereClause::raw_condition(
+            "id IN (SELECT country_id FROM users)".to_string(),
+        ));
+        assert_eq!(
+            tenant.scoped(select.clone()).build(),
+            Err(Error::UnscopedTable("users"))
+        );
+        select.where_clause(WhereClause::raw_condition(
+            "id IN (SELECT country_id FROM users WHERE users.tenant_id = 7)".to_string(),
+        ));
+        assert!(tenant.scoped(select.clone()).build().is_ok());
+        select.where_clause(WhereClause::raw_condition(
+            "name != 'from users'".to_string(),
+        ));
+        assert!(tenant.scoped(select.clone()).build().is_ok());
+        let mut select = SelectQuery::new("countries");
+        select.add_column("(SELECT COUNT(*) FROM `orders` o WHERE o.country_id = countries.id)");
+        assert_eq!(tenant.scope(select), Err(Error::UnscopedTable("orders")));
+    }
+
+    #[test]
+    fn test_scope_sub_query_reads() {
+        let tenant = tenant();
+        let scope = |condition: &str| {
+            let mut select = SelectQuery::new("countries");
+            select.where_clause(WhereClause::raw_condition(condition.to_string()));
+            tenant.scope(select).map(|_| ())
+        };
+        assert_eq!(
+            scope("id IN (SELECT country_id FROM users u JOIN orders o ON o.user_id = u.id WHERE u.tenant_id = 7)"),
+            Err(Error::UnscopedTable("orders"))
+        );
+        assert_eq!(
+            scope("id IN (SELECT country_id FROM users u JOIN orders o ON o.user_id = u.id WHERE u.tenant_id = 7 AND 7 = o.tenant_id)"),
+            Ok(())
+        );
+        assert_eq!(
+            scope("id IN (SELECT c.id FROM countries c, orders WHERE c.tenant_id = 7)"),
+            Err(Error::UnscopedTable("orders"))
+        );
+        assert_eq!(
+            scope("id IN (SELECT country_id FROM public.orders)"),
+            Err(Error::UnscopedTable("orders"))
+        );
+        assert_eq!(
+            scope("id IN (SELECT country_id FROM public.orders WHERE tenant_id = 7)"),
+            Ok(())
+        );
+        assert_eq!(
+            scope("id IN (SELECT tenant_id FROM users)"),
+            Err(Error::UnscopedTable("users"))
+        );
+        assert_eq!(
+            scope("id IN (SELECT country_id FROM users WHERE tenant_id = 8)"),
+            Err(Error::UnscopedTable("users"))
+        );
+        assert_eq!(
+            scope(
+                "id IN (SELECT x.country_id FROM (SELECT * FROM orders) x WHERE x.tenant_id = 7)"
+            ),
+            Err(Error::UnscopedTable("orders"))
+        );
+        assert_eq!(
+            scope("tags @> (SELECT tags FROM users)"),
+            Err(Error::UnscopedTable("users"))
+        );
+    }
+
+    #[test]
+    fn test_scope_sub_query_disjunctions() {
+        let tenant = tenant();
+        let scope = |condition: &str| {
+            let mut select = SelectQuery::new("countries");
+            select.where_clause(WhereClause::raw_condition(condition.to_string()));
+            tenant.scope(select).map(|_| ())
+        };
+        assert_eq!(
+            scope("id IN (SELECT country_id FROM users WHERE tenant_id = 7 OR 1 = 1)"),
+            Err(Error::UnscopedTable("users"))
+        );
+        assert_eq!(
+            scope(
+                "id IN (SELECT country_id FROM users WHERE (active OR admin) AND (tenant_id = 7))"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            scope("id IN (SELECT country_id FROM users WHERE (tenant_id = 7 AND active) OR admin)"),
+            Err(Error::UnscopedTable("users"))
+        );
+        assert_eq!(
+            scope("id IN (SELECT country_id FROM users WHERE NOT (tenant_id = 7))"),
+            Err(Error::UnscopedTable("users"))
+        );
+        assert_eq!(
+            scope("id IN (SELECT country_id FROM users WHERE tenant_id = 7) OR id = 1"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_scope_table_references() {
+        let tenant = tenant();
+        let mut update = UpdateQuery::new("public.users");
+        update
+            .set_field("name", &"Jack")
+            .join("orders o", "o.user_id = public.users.id".to_string())
+            .add_where_clause(WhereClause::equals("o.id", 1));
+        assert_eq!(
+            tenant.scope(update).unwrap().build().unwrap(),
+            "UPDATE public.users JOIN orders o ON o.user_id = public.users.id SET name = 'Jack' WHERE o.id = 1 AND public.users.tenant_id = 7 AND o.tenant_id = 7"
+        );
+        let delete = DeleteQuery::new("countries")
+            .join(
+                "(SELECT country_id FROM orders) recent",
+                "recent.country_id = countries.id".to_string(),
+            )
+            .where_clause(WhereClause::equals("countries.id", 1));
+        assert_eq!(tenant.scope(delete), Err(Error::UnscopedTable("orders")));
+        let select = SelectQuery::new("countries, users");
+        assert_eq!(tenant.scope(select), Err(Error::UnscopedTable("users")));
+    }
+
+    #[test]
+    fn test_scope_insert() {
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_column("name")
+            .add_record_raw(&["'Jack'".to_string()])
+            .unwrap();
+        let scoped = tenant().scope(insert).unwrap();
+        assert_eq!(
+            scoped.build().unwrap(),
+            "INSERT INTO users (name, tenant_id) VALUES ('Jack', 7)"
+        );
+        assert_eq!(tenant().scope(scoped.clone()), Ok(scoped));
+        let mut insert = InsertQuery::new("users");
+        insert
+            .add_column("tenant_id")
+            .add_record_raw(&["8".to_string()])
+            .unwrap();
+        assert_eq!(
+            tenant().scope(insert),
+            Err(Error::CrossTenantWrite("users"))
+        );
+    }
+
+    #[test]
+    fn test_scope_update() {
+        let mut update = UpdateQuery::new("users");
+        update
+            .set_field("name", &"Jack")
+            .join("orders", "orders.user_id = users.id".to_string())
+            .add_where_clause(WhereClause::equals("orders.id", 1));
+        assert_eq!(
+            tenant().scope(update.clone()).unwrap().build().unwrap(),
+            "UPDATE users JOIN orders ON orders.user_id = users.id SET name = 'Jack' WHERE orders.id = 1 AND users.tenant_id = 7 AND orders.tenant_id = 7"
+        );
+        update.set_field("tenant_id", &8);
+        assert_eq!(
+            tenant().scope(update),
+            Err(Error::CrossTenantWrite("users"))
+        );
+        let mut update = UpdateQuery::new("users");
+        update.set_field("name", &"Jack");
+        assert_eq!(tenant().scope(update), Err(Error::NoUpdateConditions));
+    }
+
+    #[test]
+    fn test_scope_delete() {
+        let delete = DeleteQuery::new("orders")
+            .where_clause(WhereClause::equals("id", 1))
+            .dialect(Dialect::Postgres);
+        assert_eq!(
+            tenant().scoped(delete).build().unwrap(),
+            "DELETE FROM orders WHERE id = 1 AND tenant_id = 7"
+        );
+        let delete = DeleteQuery::new("orders").allow_full_table();
+        assert_eq!(
+            tenant().scoped(delete).build().unwrap(),
+            "/* allow_full_table */ DELETE FROM orders WHERE tenant_id = 7"
+        );
+        let delete = DeleteQuery::new("orders");
+        assert_eq!(tenant().scope(delete), Err(Error::NoDeleteConditions));
+    }
+}
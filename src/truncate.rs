@@ -0,0 +1,193 @@
+use crate::dialect::Dialect;
+use crate::error::Error;
+use crate::schema::{Schema, Validate};
+use crate::Result;
+use crate::SqlBuilder;
+
+#[derive(Debug, Clone)]
+pub struct TruncateQuery {
+    pub table: &'static str,
+    restart_identity: bool,
+    auto_increment: bool,
+    cascade: bool,
+    confirmed: bool,
+    dialect: Dialect,
+}
+
+impl TruncateQuery {
+    pub fn new(table: &'static str) -> Self {
+        Self {
+            table,
+            restart_identity: false,
+            auto_increment: false,
+            cascade: false,
+            confirmed: false,
+            dialect: Dialect::default(),
+        }
+    }
+
+    /// Resets the table's sequences. Implied by `TRUNCATE` on MySQL, on SQLite
+    /// emptying a table already restarts its rowids unless it is `AUTOINCREMENT`.
+    pub fn restart_identity(mut self) -> Self {
+        self.restart_identity = true;
+        self
+    }
+
+    /// The table has an `AUTOINCREMENT` key on SQLite, restarting its identity
+    /// then also clears its row in `sqlite_sequence`, which exists as soon as
+    /// such a table does.
+    pub fn auto_increment(mut self) -> Self {
+        self.auto_increment = true;
+        self
+    }
+
+    pub fn cascade(mut self) -> Self {
+        self.cascade = true;
+        self
+    }
+
+    /// Truncating removes every row of the table, it must be asked for explicitly.
+    pub fn confirm(mut self) -> Self {
+        self.confirmed = true;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+}
+
+impl TruncateQuery {
+    pub fn build_statements(&self) -> Result<Vec<String>> {
+        if !self.confirmed {
+            return Err(Error::TruncateNotConfirmed);
+        }
+        if self.cascade && self.dialect != Dialect::Postgres {
+            return Err(Error::UnsupportedByDialect(
+                "TRUNCATE ... CASCADE",
+                self.dialect,
+            ));
+        }
+        match self.dialect {
+            Dialect::MySql => Ok(vec![format!("TRUNCATE TABLE {}", self.table)]),
+            Dialect::Postgres => {
+                let mut sql = format!("TRUNCATE TABLE {}", self.table);
+                if self.restart_identity {
+                    sql.push_str(" RESTART IDENTITY");
+                }
+                if self.cascade {
+                    sql.push_str(" CASCADE");
+                }
+                Ok(vec![sql])
+            }
+            Dialect::Sqlite => {
+                let mut statements = vec![format!("DELETE FROM {}", self.table)];
+                if self.restart_identity && self.auto_increment {
+                    statements.push(format!(
+                        "DELETE FROM sqlite_sequence WHERE name = '{}'",
+                        self.table
+                    ));
+                }
+                Ok(statements)
+            }
+        }
+    }
+}
+
+impl SqlBuilder for TruncateQuery {
+    fn build(&self) -> Result<String> {
+        Ok(self.build_statements()?.join("; "))
+    }
+}
+
+impl Validate for TruncateQuery {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        schema.require_table(self.table).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_not_confirmed() {
+        let truncate = TruncateQuery::new("events").build();
+        assert_eq!(truncate, Err(Error::TruncateNotConfirmed));
+    }
+
+    #[test]
+    fn test_truncate() {
+        let truncate = TruncateQuery::new("events").confirm();
+        assert_eq!(truncate.build(), Ok("TRUNCATE TABLE events".to_string()));
+        assert_eq!(
+            truncate
+                .clone()
+                .restart_identity()
+                .cascade()
+                .dialect(Dialect::Postgres)
+                .build(),
+            Ok("TRUNCATE TABLE events RESTART IDENTITY CASCADE".to_string())
+        );
+        assert_eq!(
+            truncate
+                .clone()
+                .restart_identity()
+                .dialect(Dialect::Sqlite)
+                .build_statements(),
+            Ok(vec!["DELETE FROM events".to_string()])
+        );
+        assert_eq!(
+            truncate
+                .clone()
+                .restart_identity()
+                .auto_increment()
+                .dialect(Dialect::Sqlite)
+                .build_statements(),
+            Ok(vec![
+                "DELETE FROM events".to_string(),
+                "DELETE FROM sqlite_sequence WHERE name = 'events'".to_string()
+            ])
+        );
+        assert_eq!(
+            truncate.cascade().build(),
+            Err(Error::UnsupportedByDialect(
+                "TRUNCATE ... CASCADE",
+                Dialect::MySql
+            ))
+        );
+    }
+
+    #[test]
+    fn test_truncate_restarts_sqlite_identity() {
+        let connection = rusqlite::Connection::open_in_memory().unwrap();
+        connection
+            .execute_batch(
+                "CREATE TABLE events (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT);
+                 CREATE TABLE logs (id INTEGER PRIMARY KEY, name TEXT)",
+            )
+            .unwrap();
+        for (table, truncate) in [
+            ("events", TruncateQuery::new("events").auto_increment()),
+            ("logs", TruncateQuery::new("logs")),
+        ] {
+            let insert = format!("INSERT INTO {} (name) VALUES ('a'), ('b')", table);
+            connection.execute(&insert, []).unwrap();
+            let truncate = truncate
+                .restart_identity()
+                .confirm()
+                .dialect(Dialect::Sqlite);
+            for statement in truncate.build_statements().unwrap() {
+                connection.execute(&statement, []).unwrap();
+            }
+            connection.execute(&insert, []).unwrap();
+            let max_id: i64 = connection
+                .query_row(&format!("SELECT MAX(id) FROM {}", table), [], |row| {
+                    row.get(0)
+                })
+                .unwrap();
+            assert_eq!(max_id, 2, "{}", table);
+        }
+    }
+}
@@ -1,11 +1,76 @@
+use crate::error::Error;
+use crate::schema::{check_literal, validate_value, Schema, Validate};
 use crate::Result;
-use crate::{repr::ToSqlRepr, where_clause::WhereClause, SqlBuilder};
+use crate::{
+    dialect::Dialect,
+    join::{join_clauses, join_conditions, join_tables, Join},
+    repr::ToSqlRepr,
+    select::{key_subquery, limit_key, order_by_limit, OrderBy},
+    where_clause::{where_sql, WhereClause, WhereCondition, FULL_TABLE_AUDIT_COMMENT},
+    SqlBuilder,
+};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct UpdateQuery {
     pub table: &'static str,
+    /// Plain `SET field = value` pairs, the value being rendered sql.
     pub fields: Vec<(&'static str, String)>,
+    pub(crate) assignments: Vec<(&'static str, Assignment)>,
     pub where_clause: Option<WhereClause>,
+    pub(crate) joins: Vec<Join>,
+    pub(crate) order_by: Vec<OrderBy>,
+    pub(crate) limit: Option<u64>,
+    pub(crate) limit_key: Option<&'static str>,
+    pub(crate) allow_full_table: bool,
+    pub(crate) dialect: Dialect,
+    pub(crate) lock: Option<OptimisticLock>,
+}
+
+/// Right hand side of a `SET field = ...` assignment.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Assignment {
+    Value(String),
+    Expr(String),
+    Column(&'static str),
+    Default,
+    Null,
+    Now,
+    Increment(String),
+    Decrement(String),
+}
+
+impl Assignment {
+    pub fn build(&self, field: &'static str, dialect: Dialect) -> Result<String> {
+        Ok(match self {
+            Assignment::Value(value) | Assignment::Expr(value) => {
+                format!("{} = {}", field, value)
+            }
+            Assignment::Column(column) => format!("{} = {}", field, column),
+            Assignment::Default => {
+                if dialect == Dialect::Sqlite {
+                    return Err(Error::UnsupportedByDialect("SET ... = DEFAULT", dialect));
+                }
+                format!("{} = DEFAULT", field)
+            }
+            Assignment::Null => format!("{} = NULL", field),
+            Assignment::Now => format!("{} = {}", field, dialect.now()),
+            Assignment::Increment(by) => format!("{} = {} + {}", field, field, by),
+            Assignment::Decrement(by) => format!("{} = {} - {}", field, field, by),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum OptimisticLock {
+    Counter {
+        column: &'static str,
+        expected: i64,
+    },
+    Timestamp {
+        column: &'static str,
+        expected: String,
+        next: String,
+    },
 }
 
 impl UpdateQuery {
@@ -13,7 +78,15 @@ impl UpdateQuery {
         Self {
             table,
             fields: Vec::new(),
+            assignments: Vec::new(),
             where_clause: None,
+            joins: Vec::new(),
+            order_by: Vec::new(),
+            limit: None,
+            limit_key: None,
+            allow_full_table: false,
+            dialect: Dialect::default(),
+            lock: None,
         }
     }
 
@@ -22,34 +95,344 @@ impl UpdateQuery {
         self
     }
 
+    /// Sets `field` to a raw sql expression, e.g. `COALESCE('John', name)`.
+    pub fn set_expr(&mut self, field: &'static str, expr: String) -> &mut Self {
+        self.assignments.push((field, Assignment::Expr(expr)));
+        self
+    }
+
+    pub fn set_column(&mut self, field: &'static str, column: &'static str) -> &mut Self {
+        self.assignments.push((field, Assignment::Column(column)));
+        self
+    }
+
+    pub fn set_default(&mut self, field: &'static str) -> &mut Self {
+        self.assignments.push((field, Assignment::Default));
+        self
+    }
+
+    pub fn set_null(&mut self, field: &'static str) -> &mut Self {
+        self.assignments.push((field, Assignment::Null));
+        self
+    }
+
+    pub fn set_now(&mut self, field: &'static str) -> &mut Self {
+        self.assignments.push((field, Assignment::Now));
+        self
+    }
+
+    pub fn increment<T: ToSqlRepr>(&mut self, field: &'static str, by: T) -> &mut Self {
+        self.assignments
+            .push((field, Assignment::Increment(by.to_sql_repr())));
+        self
+    }
+
+    pub fn decrement<T: ToSqlRepr>(&mut self, field: &'static str, by: T) -> &mut Self {
+        self.assignments
+            .push((field, Assignment::Decrement(by.to_sql_repr())));
+        self
+    }
+
+    pub fn dialect(&mut self, dialect: Dialect) -> &mut Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Every `SET` assignment in the order they are built, the plain values in
+    /// `fields` first, then the ones added by the other `set_*` methods.
+    pub fn assignments(&self) -> Vec<(&'static str, Assignment)> {
+        self.fields
+            .iter()
+            .map(|(field, value)| (*field, Assignment::Value(value.clone())))
+            .chain(self.assignments.iter().cloned())
+            .collect()
+    }
+
+    /// Adds an assignment, plain values go to `fields`.
+    pub(crate) fn assign(&mut self, field: &'static str, assignment: Assignment) -> &mut Self {
+        match assignment {
+            Assignment::Value(value) => self.fields.push((field, value)),
+            assignment => self.assignments.push((field, assignment)),
+        }
+        self
+    }
+
     pub fn add_where_clause(&mut self, where_clause: WhereClause) -> &mut Self {
         self.where_clause = Some(where_clause);
         self
     }
+
+    /// Updates `table` joined with other tables. Rendered as `UPDATE a JOIN b ON ... SET`
+    /// on MySQL and as `UPDATE a SET ... FROM b WHERE ...` on PostgreSQL and SQLite,
+    /// qualify the updated fields only when targeting MySQL.
+    pub fn join(&mut self, table: &'static str, on: String) -> &mut Self {
+        self.joins.push(Join::new(table, on));
+        self
+    }
+
+    pub fn order_by(&mut self, column: &'static str) -> &mut Self {
+        self.order_by.push(OrderBy::new(column));
+        self
+    }
+
+    pub fn desc(&mut self) -> Result<&mut Self> {
+        if self.order_by.is_empty() {
+            return Err(Error::NoOrderByClause);
+        }
+        self.order_by.last_mut().unwrap().desc();
+        Ok(self)
+    }
+
+    pub fn asc(&mut self) -> Result<&mut Self> {
+        if self.order_by.is_empty() {
+            return Err(Error::NoOrderByClause);
+        }
+        self.order_by.last_mut().unwrap().asc();
+        Ok(self)
+    }
+
+    /// Updates at most `limit` rows. Dialects without `UPDATE ... LIMIT` get
+    /// `UPDATE t SET ... WHERE key IN (SELECT t.key FROM t WHERE ... LIMIT n)` instead.
+    pub fn limit(&mut self, limit: u64) -> &mut Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The column identifying rows in the emulated `LIMIT` subquery,
+    /// defaults to `ctid` on PostgreSQL and `rowid` on SQLite.
+    pub fn limit_key(&mut self, column: &'static str) -> &mut Self {
+        self.limit_key = Some(column);
+        self
+    }
+
+    /// Explicitly allows updating every row, without a where clause or with one that
+    /// matches everything such as `1=1`. The built statement is prefixed with
+    /// [`FULL_TABLE_AUDIT_COMMENT`].
+    pub fn allow_full_table(&mut self) -> &mut Self {
+        self.allow_full_table = true;
+        self
+    }
+
+    /// Optimistic locking on an integer version column: the column is bumped by one
+    /// and the update only matches rows still at `expected`.
+    /// An affected-row count of zero means someone else updated the record first.
+    pub fn versioned(&mut self, column: &'static str, expected: i64) -> &mut Self {
+        self.lock = Some(OptimisticLock::Counter { column, expected });
+        self
+    }
+
+    /// Optimistic locking on a timestamp column: the column is set to `next`
+    /// and the update only matches rows still at `expected`.
+    pub fn versioned_by_timestamp<T: ToSqlRepr>(
+        &mut self,
+        column: &'static str,
+        expected: &T,
+        next: &T,
+    ) -> &mut Self {
+        self.lock = Some(OptimisticLock::Timestamp {
+            column,
+            expected: expected.to_sql_repr(),
+            next: next.to_sql_repr(),
+        });
+        self
+    }
 }
 
 impl SqlBuilder for UpdateQuery {
     fn build(&self) -> Result<String> {
-        if self.fields.is_empty() {
-            return Err(crate::error::Error::NoUpdateFields);
+        if self.fields.is_empty() && self.assignments.is_empty() {
+            return Err(Error::NoUpdateFields);
+        }
+        if !self.allow_full_table
+            && self
+                .where_clause
+                .as_ref()
+                .is_none_or(WhereClause::is_tautology)
+        {
+            return Err(Error::NoUpdateConditions);
         }
 
-        let fields = self
-            .fields
+        let mut fields = self
+            .assignments()
             .iter()
-            .map(|(field, value)| format!("{} = {}", field, value))
-            .collect::<Vec<String>>()
-            .join(", ");
-        if let Some(where_clause) = &self.where_clause {
-            Ok(format!(
-                "UPDATE {} SET {} WHERE {}",
-                self.table,
-                fields,
-                where_clause.build()?
-            ))
+            .map(|(field, assignment)| assignment.build(field, self.dialect))
+            .collect::<Result<Vec<String>>>()?;
+        let lock_condition = match &self.lock {
+            Some(OptimisticLock::Counter { column, expected }) => {
+                fields.push(Assignment::Increment(1.to_sql_repr()).build(column, self.dialect)?);
+                Some(WhereClause::equals(column, *expected))
+            }
+            Some(OptimisticLock::Timestamp {
+                column,
+                expected,
+                next,
+            }) => {
+                fields.push(Assignment::Value(next.clone()).build(column, self.dialect)?);
+                Some(WhereClause::Condition(WhereCondition::Equals(
+                    column,
+                    expected.clone(),
+                )))
+            }
+            None => None,
+        };
+        let where_clause = self
+            .where_clause
+            .iter()
+            .cloned()
+            .chain(lock_condition)
+            .reduce(|left, right| left.and(right));
+        let fields = fields.join(", ");
+
+        let mut sql = if self.allow_full_table {
+            format!("{} ", FULL_TABLE_AUDIT_COMMENT)
         } else {
-            Err(crate::error::Error::NoUpdateConditions)
+            String::new()
+        };
+        if self.dialect != Dialect::MySql && self.limit.is_none() && !self.order_by.is_empty() {
+            return Err(Error::UnsupportedByDialect(
+                "ORDER BY without LIMIT on an UPDATE",
+                self.dialect,
+            ));
+        }
+        let native_limit = self.dialect.supports_update_delete_limit();
+        if self.joins.is_empty() {
+            if self.limit.is_some() && !native_limit {
+                let key = limit_key(self.limit_key, self.dialect)?;
+                sql.push_str(&format!(
+                    "UPDATE {} SET {} WHERE {} IN ({})",
+                    self.table,
+                    fields,
+                    key,
+                    key_subquery(
+                        self.table,
+                        key,
+                        &[],
+                        where_clause.as_ref(),
+                        &self.order_by,
+                        self.limit
+                    )?
+                ));
+            } else {
+                sql.push_str(&format!(
+                    "UPDATE {} SET {}{}",
+                    self.table,
+                    fields,
+                    where_sql(where_clause.as_ref())?
+                ));
+                sql.push_str(&order_by_limit(&self.order_by, self.limit)?);
+            }
+            return Ok(sql);
+        }
+        match self.dialect {
+            Dialect::MySql => {
+                if !self.order_by.is_empty() || self.limit.is_some() {
+                    return Err(Error::UnsupportedByDialect(
+                        "ORDER BY / LIMIT on a multi-table UPDATE",
+                        self.dialect,
+                    ));
+                }
+                sql.push_str(&format!(
+                    "UPDATE {} {} SET {}{}",
+                    self.table,
+                    join_clauses(&self.joins)?,
+                    fields,
+                    where_sql(where_clause.as_ref())?
+                ));
+            }
+            // the joined tables stay in FROM for the assignments, a limit picks
+            // the updated rows by key
+            Dialect::Postgres | Dialect::Sqlite => {
+                let limit_condition = match self.limit {
+                    Some(_) => {
+                        let key = limit_key(self.limit_key, self.dialect)?;
+                        Some(WhereClause::raw_condition(format!(
+                            "{}.{} IN ({})",
+                            self.table,
+                            key,
+                            key_subquery(
+                                self.table,
+                                key,
+                                &self.joins,
+                                where_clause.as_ref(),
+                                &self.order_by,
+                                self.limit
+                            )?
+                        )))
+                    }
+                    None => None,
+                };
+                let where_clause = join_conditions(&self.joins, where_clause)
+                    .into_iter()
+                    .chain(limit_condition)
+                    .reduce(WhereClause::and);
+                sql.push_str(&format!(
+                    "UPDATE {} SET {} FROM {}{}",
+                    self.table,
+                    fields,
+                    join_tables(&self.joins),
+                    where_sql(where_clause.as_ref())?
+                ));
+            }
         }
+        Ok(sql)
+    }
+}
+
+pub trait AsUpdateRecord {
+    fn as_update_record(&self) -> Vec<(&'static str, String)>;
+}
+
+impl Validate for UpdateQuery {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        let tables = std::iter::once(self.table)
+            .chain(self.joins.iter().map(|join| join.table))
+            .collect::<Vec<_>>();
+        for table in &tables {
+            schema.require_table(table)?;
+        }
+        for (field, assignment) in &self.assignments() {
+            let Some((table, column)) = schema.resolve_column(&tables, field)? else {
+                continue;
+            };
+            match assignment {
+                Assignment::Value(value) => validate_value(table, column, value)?,
+                Assignment::Null => validate_value(table, column, "NULL")?,
+                Assignment::Column(other) => {
+                    schema.resolve_column(&tables, other)?;
+                }
+                Assignment::Increment(by) | Assignment::Decrement(by) => {
+                    if !column.column_type.is_numeric() {
+                        return Err(Error::ValueTypeMismatch(
+                            table,
+                            column.name,
+                            column.column_type,
+                        ));
+                    }
+                    check_literal(table, column, by)?;
+                }
+                Assignment::Expr(_) | Assignment::Default | Assignment::Now => {}
+            }
+        }
+        match &self.lock {
+            Some(OptimisticLock::Counter { column, .. }) => {
+                schema.resolve_column(&tables, column)?;
+            }
+            Some(OptimisticLock::Timestamp {
+                column,
+                expected,
+                next,
+            }) => {
+                if let Some((table, column)) = schema.resolve_column(&tables, column)? {
+                    check_literal(table, column, expected)?;
+                    check_literal(table, column, next)?;
+                }
+            }
+            None => {}
+        }
+        if let Some(where_clause) = &self.where_clause {
+            schema.validate_where_clause(&tables, where_clause)?;
+        }
+        Ok(())
     }
 }
 
@@ -74,19 +457,195 @@ mod tests {
     #[test]
     fn test_update_query_no_fields() {
         let update_query = UpdateQuery::new("users");
-        assert_eq!(
-            update_query.build().unwrap_err(),
-            crate::error::Error::NoUpdateFields
-        );
+        assert_eq!(update_query.build().unwrap_err(), Error::NoUpdateFields);
     }
 
     #[test]
     fn test_update_query_no_conditions() {
         let mut update_query = UpdateQuery::new("users");
         update_query.set_field("name", &"John");
+        assert_eq!(update_query.build().unwrap_err(), Error::NoUpdateConditions);
+    }
+
+    #[test]
+    fn test_update_query_versioned() {
+        let mut update_query = UpdateQuery::new("users");
+        update_query
+            .set_field("name", &"John")
+            .add_where_clause(WhereClause::equals("id", 1))
+            .versioned("version", 3);
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE users SET name = 'John', version = version + 1 WHERE id = 1 AND version = 3"
+        );
+    }
+
+    #[test]
+    fn test_update_query_versioned_by_timestamp() {
+        let mut update_query = UpdateQuery::new("users");
+        update_query
+            .set_field("name", &"John")
+            .add_where_clause(WhereClause::equals("id", 1).or_equals("id", 2))
+            .versioned_by_timestamp("updated_at", &"2023-03-01 10:00:00", &"2023-03-02 08:30:00");
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE users SET name = 'John', updated_at = '2023-03-02 08:30:00' WHERE (id = 1 OR id = 2) AND updated_at = '2023-03-01 10:00:00'"
+        );
+    }
+
+    #[test]
+    fn test_update_query_public_fields() {
+        let mut update_query = UpdateQuery::new("users");
+        update_query.fields.push(("name", "'John'".to_string()));
+        update_query
+            .increment("logins", 1)
+            .set_field("age", &30)
+            .add_where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            update_query.fields,
+            vec![("name", "'John'".to_string()), ("age", "30".to_string())]
+        );
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE users SET name = 'John', age = 30, logins = logins + 1 WHERE id = 1"
+        );
+    }
+
+    #[test]
+    fn test_update_query_assignments() {
+        let mut update_query = UpdateQuery::new("users");
+        update_query
+            .increment("counter", 1)
+            .decrement("credits", 5)
+            .set_now("updated_at")
+            .set_expr("name", "COALESCE('John', name)".to_string())
+            .set_column("nickname", "name")
+            .set_default("status")
+            .set_null("deleted_at")
+            .add_where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            update_query.assignments()[0],
+            ("counter", Assignment::Increment("1".to_string()))
+        );
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE users SET counter = counter + 1, credits = credits - 5, updated_at = NOW(), name = COALESCE('John', name), nickname = name, status = DEFAULT, deleted_at = NULL WHERE id = 1"
+        );
+    }
+
+    #[test]
+    fn test_update_query_assignments_sqlite() {
+        let mut update_query = UpdateQuery::new("users");
+        update_query
+            .set_now("updated_at")
+            .dialect(Dialect::Sqlite)
+            .add_where_clause(WhereClause::equals("id", 1));
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE users SET updated_at = CURRENT_TIMESTAMP WHERE id = 1"
+        );
+        update_query.set_default("status");
         assert_eq!(
             update_query.build().unwrap_err(),
-            crate::error::Error::NoUpdateConditions
+            Error::UnsupportedByDialect("SET ... = DEFAULT", Dialect::Sqlite)
+        );
+    }
+
+    #[test]
+    fn test_update_query_join() {
+        let mut update_query = UpdateQuery::new("orders");
+        update_query
+            .join("users", "users.id = orders.user_id".to_string())
+            .set_column("orders.email", "users.email")
+            .add_where_clause(WhereClause::equals("users.active", true));
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE orders JOIN users ON users.id = orders.user_id SET orders.email = users.email WHERE users.active = TRUE"
+        );
+    }
+
+    #[test]
+    fn test_update_query_from() {
+        let mut update_query = UpdateQuery::new("orders");
+        update_query
+            .join("users", "users.id = orders.user_id".to_string())
+            .set_column("email", "users.email")
+            .add_where_clause(WhereClause::equals("users.active", true).or_is_null("orders.email"))
+            .dialect(Dialect::Postgres);
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE orders SET email = users.email FROM users WHERE users.id = orders.user_id AND (users.active = TRUE OR orders.email IS NULL)"
+        );
+    }
+
+    #[test]
+    fn test_update_query_order_by_limit() {
+        let mut update_query = UpdateQuery::new("events");
+        update_query
+            .set_field("archived", &true)
+            .add_where_clause(WhereClause::less_than("created_at", "2023-01-01"))
+            .order_by("id")
+            .desc()
+            .unwrap()
+            .limit(500);
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE events SET archived = TRUE WHERE created_at < '2023-01-01' ORDER BY id DESC LIMIT 500"
+        );
+        update_query.dialect(Dialect::Postgres).limit_key("id");
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE events SET archived = TRUE WHERE id IN (SELECT events.id FROM events WHERE created_at < '2023-01-01' ORDER BY id DESC LIMIT 500)"
+        );
+        update_query.join("users", "users.id = events.user_id".to_string());
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE events SET archived = TRUE FROM users WHERE users.id = events.user_id AND created_at < '2023-01-01' AND events.id IN (SELECT events.id FROM events JOIN users ON users.id = events.user_id WHERE created_at < '2023-01-01' ORDER BY id DESC LIMIT 500)"
+        );
+        assert_eq!(
+            update_query.dialect(Dialect::MySql).build().unwrap_err(),
+            Error::UnsupportedByDialect("ORDER BY / LIMIT on a multi-table UPDATE", Dialect::MySql)
+        );
+    }
+
+    #[test]
+    fn test_update_query_order_by_without_limit() {
+        let mut update_query = UpdateQuery::new("events");
+        update_query
+            .set_field("archived", &true)
+            .add_where_clause(WhereClause::less_than("created_at", "2023-01-01"))
+            .order_by("id");
+        assert_eq!(
+            update_query.build().unwrap(),
+            "UPDATE events SET archived = TRUE WHERE created_at < '2023-01-01' ORDER BY id ASC"
+        );
+        assert_eq!(
+            update_query.dialect(Dialect::Postgres).build().unwrap_err(),
+            Error::UnsupportedByDialect("ORDER BY without LIMIT on an UPDATE", Dialect::Postgres)
+        );
+    }
+
+    #[test]
+    fn test_update_query_tautology() {
+        let mut update_query = UpdateQuery::new("users");
+        update_query
+            .set_field("active", &false)
+            .add_where_clause(WhereClause::raw_condition("id = id".to_string()));
+        assert_eq!(update_query.build().unwrap_err(), Error::NoUpdateConditions);
+        update_query.allow_full_table();
+        assert_eq!(
+            update_query.build().unwrap(),
+            "/* allow_full_table */ UPDATE users SET active = FALSE WHERE id = id"
+        );
+    }
+
+    #[test]
+    fn test_update_query_allow_full_table() {
+        let mut update_query = UpdateQuery::new("users");
+        update_query.set_field("active", &false).allow_full_table();
+        assert_eq!(
+            update_query.build().unwrap(),
+            "/* allow_full_table */ UPDATE users SET active = FALSE"
         );
     }
 }
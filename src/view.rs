@@ -0,0 +1,251 @@
+use crate::dialect::Dialect;
+use crate::error::Error;
+use crate::schema::{Schema, Validate};
+use crate::select::SelectQuery;
+use crate::Result;
+use crate::SqlBuilder;
+
+#[derive(Debug, Clone)]
+pub struct CreateViewQuery {
+    pub view: &'static str,
+    pub select: SelectQuery,
+    columns: Vec<&'static str>,
+    or_replace: bool,
+    materialized: bool,
+    if_not_exists: bool,
+    dialect: Dialect,
+}
+
+impl CreateViewQuery {
+    pub fn new(view: &'static str, select: SelectQuery) -> Self {
+        Self {
+            view,
+            select,
+            columns: Vec::new(),
+            or_replace: false,
+            materialized: false,
+            if_not_exists: false,
+            dialect: Dialect::default(),
+        }
+    }
+
+    /// Names the view's columns instead of inheriting them from the select.
+    pub fn columns(mut self, columns: &[&'static str]) -> Self {
+        self.columns = columns.to_vec();
+        self
+    }
+
+    pub fn or_replace(mut self) -> Self {
+        self.or_replace = true;
+        self
+    }
+
+    /// Stores the result of the select, PostgreSQL only.
+    pub fn materialized(mut self) -> Self {
+        self.materialized = true;
+        self
+    }
+
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+}
+
+impl SqlBuilder for CreateViewQuery {
+    fn build(&self) -> Result<String> {
+        let unsupported = |feature| Err(Error::UnsupportedByDialect(feature, self.dialect));
+        if self.materialized && self.dialect != Dialect::Postgres {
+            return unsupported("CREATE MATERIALIZED VIEW");
+        }
+        if self.materialized && self.or_replace {
+            return unsupported("CREATE OR REPLACE MATERIALIZED VIEW");
+        }
+        if self.or_replace && self.dialect == Dialect::Sqlite {
+            return unsupported("CREATE OR REPLACE VIEW");
+        }
+        if self.or_replace && self.if_not_exists {
+            return unsupported("CREATE OR REPLACE VIEW IF NOT EXISTS");
+        }
+        if self.if_not_exists && !self.materialized && self.dialect != Dialect::Sqlite {
+            return unsupported("CREATE VIEW IF NOT EXISTS");
+        }
+
+        let mut sql = String::from("CREATE ");
+        if self.or_replace {
+            sql.push_str("OR REPLACE ");
+        }
+        if self.materialized {
+            sql.push_str("MATERIALIZED ");
+        }
+        sql.push_str("VIEW ");
+        if self.if_not_exists {
+            sql.push_str("IF NOT EXISTS ");
+        }
+        sql.push_str(self.view);
+        if !self.columns.is_empty() {
+            sql.push_str(&format!(" ({})", self.columns.join(", ")));
+        }
+        sql.push_str(" AS ");
+        sql.push_str(&self.select.build()?);
+        Ok(sql)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RefreshMaterializedViewQuery {
+    pub view: &'static str,
+    concurrently: bool,
+    dialect: Dialect,
+}
+
+impl RefreshMaterializedViewQuery {
+    pub fn new(view: &'static str) -> Self {
+        Self {
+            view,
+            concurrently: false,
+            dialect: Dialect::default(),
+        }
+    }
+
+    /// Refreshes without locking out reads, the view needs a unique index.
+    pub fn concurrently(mut self) -> Self {
+        self.concurrently = true;
+        self
+    }
+
+    pub fn dialect(mut self, dialect: Dialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+}
+
+impl SqlBuilder for RefreshMaterializedViewQuery {
+    fn build(&self) -> Result<String> {
+        if self.dialect != Dialect::Postgres {
+            return Err(Error::UnsupportedByDialect(
+                "REFRESH MATERIALIZED VIEW",
+                self.dialect,
+            ));
+        }
+        if self.concurrently {
+            Ok(format!(
+                "REFRESH MATERIALIZED VIEW CONCURRENTLY {}",
+                self.view
+            ))
+        } else {
+            Ok(format!("REFRESH MATERIALIZED VIEW {}", self.view))
+        }
+    }
+}
+
+impl Validate for CreateViewQuery {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        self.select.validate(schema)
+    }
+}
+
+impl Validate for RefreshMaterializedViewQuery {
+    fn validate(&self, schema: &Schema) -> Result<()> {
+        schema.require_view(self.view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::where_clause::WhereClause;
+
+    fn active_users() -> SelectQuery {
+        let mut select = SelectQuery::new("users");
+        select
+            .add_columns(&["id", "name"])
+            .where_clause(WhereClause::equals("active", true));
+        select
+    }
+
+    #[test]
+    fn test_create_view() {
+        let view = CreateViewQuery::new("active_users", active_users()).or_replace();
+        assert_eq!(
+            view.build().unwrap(),
+            "CREATE OR REPLACE VIEW active_users AS SELECT id, name FROM users WHERE active = TRUE"
+        );
+        let view = CreateViewQuery::new("active_users", active_users())
+            .columns(&["user_id", "user_name"])
+            .if_not_exists()
+            .dialect(Dialect::Sqlite);
+        assert_eq!(
+            view.build().unwrap(),
+            "CREATE VIEW IF NOT EXISTS active_users (user_id, user_name) AS SELECT id, name FROM users WHERE active = TRUE"
+        );
+    }
+
+    #[test]
+    fn test_create_materialized_view() {
+        let view = CreateViewQuery::new("active_users", active_users())
+            .materialized()
+            .if_not_exists()
+            .dialect(Dialect::Postgres);
+        assert_eq!(
+            view.build().unwrap(),
+            "CREATE MATERIALIZED VIEW IF NOT EXISTS active_users AS SELECT id, name FROM users WHERE active = TRUE"
+        );
+    }
+
+    #[test]
+    fn test_create_view_unsupported() {
+        let view = CreateViewQuery::new("active_users", active_users());
+        assert_eq!(
+            view.clone().materialized().build().unwrap_err(),
+            Error::UnsupportedByDialect("CREATE MATERIALIZED VIEW", Dialect::MySql)
+        );
+        assert_eq!(
+            view.clone()
+                .materialized()
+                .or_replace()
+                .dialect(Dialect::Postgres)
+                .build()
+                .unwrap_err(),
+            Error::UnsupportedByDialect("CREATE OR REPLACE MATERIALIZED VIEW", Dialect::Postgres)
+        );
+        assert_eq!(
+            view.clone()
+                .or_replace()
+                .dialect(Dialect::Sqlite)
+                .build()
+                .unwrap_err(),
+            Error::UnsupportedByDialect("CREATE OR REPLACE VIEW", Dialect::Sqlite)
+        );
+        assert_eq!(
+            view.if_not_exists().build().unwrap_err(),
+            Error::UnsupportedByDialect("CREATE VIEW IF NOT EXISTS", Dialect::MySql)
+        );
+    }
+
+    #[test]
+    fn test_refresh_materialized_view() {
+        let refresh = RefreshMaterializedViewQuery::new("active_users");
+        assert_eq!(
+            refresh.build().unwrap_err(),
+            Error::UnsupportedByDialect("REFRESH MATERIALIZED VIEW", Dialect::MySql)
+        );
+        assert_eq!(
+            refresh.clone().dialect(Dialect::Postgres).build().unwrap(),
+            "REFRESH MATERIALIZED VIEW active_users"
+        );
+        assert_eq!(
+            refresh
+                .concurrently()
+                .dialect(Dialect::Postgres)
+                .build()
+                .unwrap(),
+            "REFRESH MATERIALIZED VIEW CONCURRENTLY active_users"
+        );
+    }
+}
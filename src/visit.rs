@@ -0,0 +1,478 @@
+//! Read-only traversal of the builders, see `fold` for rewriting them.
+//!
+//! Every `visit_*` method defaults to the matching `walk_*` function, which visits
+//! the children. Override a method to inspect a node and call its `walk_*` function
+//! to keep descending.
+
+use crate::alter_table::{AlterOperation, AlterTableQuery};
+use crate::create_index::{CreateIndexQuery, IndexPart};
+use crate::create_table::{ColumnDef, CreateTableQuery, ForeignKey, TableConstraint};
+use crate::delete::DeleteQuery;
+use crate::drop::{DropIndex, DropTable};
+use crate::insert::InsertQuery;
+use crate::join::Join;
+use crate::parser::Statement;
+use crate::select::{OrderBy, SelectQuery};
+use crate::truncate::TruncateQuery;
+use crate::update::{Assignment, OptimisticLock, UpdateQuery};
+use crate::view::{CreateViewQuery, RefreshMaterializedViewQuery};
+use crate::where_clause::{WhereClause, WhereCondition};
+
+pub trait Visitor {
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_select(&mut self, select: &SelectQuery) {
+        walk_select(self, select)
+    }
+
+    fn visit_insert(&mut self, insert: &InsertQuery) {
+        walk_insert(self, insert)
+    }
+
+    fn visit_update(&mut self, update: &UpdateQuery) {
+        walk_update(self, update)
+    }
+
+    fn visit_delete(&mut self, delete: &DeleteQuery) {
+        walk_delete(self, delete)
+    }
+
+    fn visit_create_table(&mut self, create_table: &CreateTableQuery) {
+        walk_create_table(self, create_table)
+    }
+
+    fn visit_alter_table(&mut self, alter_table: &AlterTableQuery) {
+        walk_alter_table(self, alter_table)
+    }
+
+    fn visit_create_index(&mut self, create_index: &CreateIndexQuery) {
+        walk_create_index(self, create_index)
+    }
+
+    fn visit_create_view(&mut self, create_view: &CreateViewQuery) {
+        walk_create_view(self, create_view)
+    }
+
+    fn visit_refresh_materialized_view(&mut self, refresh: &RefreshMaterializedViewQuery) {
+        self.visit_table(refresh.view)
+    }
+
+    fn visit_truncate(&mut self, truncate: &TruncateQuery) {
+        self.visit_table(truncate.table)
+    }
+
+    fn visit_drop_table(&mut self, drop_table: &DropTable) {
+        self.visit_table(drop_table.table)
+    }
+
+    fn visit_drop_index(&mut self, drop_index: &DropIndex) {
+        if let Some(table) = drop_index.table {
+            self.visit_table(table);
+        }
+    }
+
+    fn visit_join(&mut self, join: &Join) {
+        walk_join(self, join)
+    }
+
+    fn visit_assignment(&mut self, column: &'static str, assignment: &Assignment) {
+        walk_assignment(self, column, assignment)
+    }
+
+    fn visit_where_clause(&mut self, where_clause: &WhereClause) {
+        walk_where_clause(self, where_clause)
+    }
+
+    fn visit_where_condition(&mut self, condition: &WhereCondition) {
+        walk_where_condition(self, condition)
+    }
+
+    fn visit_order_by(&mut self, order_by: &OrderBy) {
+        for column in &order_by.columns {
+            self.visit_column(column);
+        }
+    }
+
+    /// Tables, views and joined tables.
+    fn visit_table(&mut self, _table: &'static str) {}
+
+    /// Selected, inserted, assigned, filtered and ordered columns.
+    fn visit_column(&mut self, _column: &'static str) {}
+
+    /// Literals and expressions compared with or assigned to columns,
+    /// raw conditions, join conditions, check constraints and triggers.
+    fn visit_value(&mut self, _value: &str) {}
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Select(select) => visitor.visit_select(select),
+        Statement::Insert(insert) => visitor.visit_insert(insert),
+        Statement::Update(update) => visitor.visit_update(update),
+        Statement::Delete(delete) => visitor.visit_delete(delete),
+    }
+}
+
+pub fn walk_select<V: Visitor + ?Sized>(visitor: &mut V, select: &SelectQuery) {
+    visitor.visit_table(select.table);
+    for column in &select.columns {
+        visitor.visit_column(column);
+    }
+    if let Some(where_clause) = &select.where_clause {
+        visitor.visit_where_clause(where_clause);
+    }
+    for order_by in &select.order_by {
+        visitor.visit_order_by(order_by);
+    }
+    if let Some(column) = select.soft_delete {
+        visitor.visit_column(column);
+    }
+}
+
+pub fn walk_insert<V: Visitor + ?Sized>(visitor: &mut V, insert: &InsertQuery) {
+    visitor.visit_table(insert.table);
+    for column in &insert.columns {
+        visitor.visit_column(column);
+    }
+    for value in insert.values.iter().flatten() {
+        visitor.visit_value(value);
+    }
+}
+
+pub fn walk_update<V: Visitor + ?Sized>(visitor: &mut V, update: &UpdateQuery) {
+    visitor.visit_table(update.table);
+    for join in &update.joins {
+        visitor.visit_join(join);
+    }
+    for (column, assignment) in &update.assignments() {
+        visitor.visit_assignment(column, assignment);
+    }
+    if let Some(where_clause) = &update.where_clause {
+        visitor.visit_where_clause(where_clause);
+    }
+    for order_by in &update.order_by {
+        visitor.visit_order_by(order_by);
+    }
+    if let Some(key) = update.limit_key {
+        visitor.visit_column(key);
+    }
+    match &update.lock {
+        Some(OptimisticLock::Counter { column, .. }) => visitor.visit_column(column),
+        Some(OptimisticLock::Timestamp {
+            column,
+            expected,
+            next,
+        }) => {
+            visitor.visit_column(column);
+            visitor.visit_value(expected);
+            visitor.visit_value(next);
+        }
+        None => {}
+    }
+}
+
+pub fn walk_delete<V: Visitor + ?Sized>(visitor: &mut V, delete: &DeleteQuery) {
+    visitor.visit_table(delete.table);
+    for join in &delete.joins {
+        visitor.visit_join(join);
+    }
+    if let Some(where_clause) = &delete.where_clause {
+        visitor.visit_where_clause(where_clause);
+    }
+    for order_by in &delete.order_by {
+        visitor.visit_order_by(order_by);
+    }
+    if let Some(key) = delete.limit_key {
+        visitor.visit_column(key);
+    }
+    if let Some(column) = delete.soft_delete {
+        visitor.visit_column(column);
+    }
+    for (column, assignment) in &delete.soft_delete_fields {
+        visitor.visit_assignment(column, assignment);
+    }
+}
+
+pub fn walk_create_table<V: Visitor + ?Sized>(visitor: &mut V, create_table: &CreateTableQuery) {
+    visitor.visit_table(create_table.table);
+    for column in &create_table.columns {
+        walk_column_def(visitor, column);
+    }
+    for (_, constraint) in &create_table.constraints {
+        walk_table_constraint(visitor, constraint);
+    }
+}
+
+pub fn walk_alter_table<V: Visitor + ?Sized>(visitor: &mut V, alter_table: &AlterTableQuery) {
+    visitor.visit_table(alter_table.table);
+    for operation in &alter_table.operations {
+        match operation {
+            AlterOperation::AddColumn(column) | AlterOperation::ModifyColumn(column) => {
+                walk_column_def(visitor, column)
+            }
+            AlterOperation::RenameColumn(from, to) => {
+                visitor.visit_column(from);
+                visitor.visit_column(to);
+            }
+            AlterOperation::DropColumn(column)
+            | AlterOperation::ChangeColumnType(column, _)
+            | AlterOperation::SetDefault(column, _)
+            | AlterOperation::DropDefault(column)
+            | AlterOperation::SetNotNull(column)
+            | AlterOperation::DropNotNull(column) => visitor.visit_column(column),
+            AlterOperation::AddConstraint(_, constraint) => {
+                walk_table_constraint(visitor, constraint)
+            }
+            AlterOperation::DropConstraint(_) => {}
+            AlterOperation::RenameTable(table) => visitor.visit_table(table),
+        }
+    }
+    if let Some(current) = &alter_table.current {
+        visitor.visit_create_table(current);
+    }
+    for index in &alter_table.indexes {
+        visitor.visit_create_index(index);
+    }
+    for trigger in &alter_table.triggers {
+        visitor.visit_value(trigger);
+    }
+}
+
+pub fn walk_column_def<V: Visitor + ?Sized>(visitor: &mut V, column: &ColumnDef) {
+    visitor.visit_column(column.name);
+    if let Some(check) = &column.check {
+        visitor.visit_value(check);
+    }
+    if let Some(foreign_key) = &column.references {
+        walk_foreign_key(visitor, foreign_key);
+    }
+}
+
+pub fn walk_table_constraint<V: Visitor + ?Sized>(visitor: &mut V, constraint: &TableConstraint) {
+    match constraint {
+        TableConstraint::PrimaryKey(columns) | TableConstraint::Unique(columns) => {
+            for column in columns {
+                visitor.visit_column(column);
+            }
+        }
+        TableConstraint::Check(check) => visitor.visit_value(check),
+        TableConstraint::ForeignKey(foreign_key) => walk_foreign_key(visitor, foreign_key),
+    }
+}
+
+/// The referencing columns, then the referenced table and its columns.
+pub fn walk_foreign_key<V: Visitor + ?Sized>(visitor: &mut V, foreign_key: &ForeignKey) {
+    for column in &foreign_key.columns {
+        visitor.visit_column(column);
+    }
+    visitor.visit_table(foreign_key.table);
+    for column in &foreign_key.references {
+        visitor.visit_column(column);
+    }
+}
+
+pub fn walk_create_index<V: Visitor + ?Sized>(visitor: &mut V, create_index: &CreateIndexQuery) {
+    visitor.visit_table(create_index.table);
+    for (part, _) in &create_index.parts {
+        if let IndexPart::Column(column) = part {
+            visitor.visit_column(column);
+        }
+    }
+    if let Some(where_clause) = &create_index.where_clause {
+        visitor.visit_where_clause(where_clause);
+    }
+}
+
+pub fn walk_create_view<V: Visitor + ?Sized>(visitor: &mut V, create_view: &CreateViewQuery) {
+    visitor.visit_table(create_view.view);
+    visitor.visit_select(&create_view.select);
+}
+
+pub fn walk_join<V: Visitor + ?Sized>(visitor: &mut V, join: &Join) {
+    visitor.visit_table(join.table);
+    visitor.visit_value(&join.on);
+}
+
+pub fn walk_assignment<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    column: &'static str,
+    assignment: &Assignment,
+) {
+    visitor.visit_column(column);
+    match assignment {
+        Assignment::Value(value)
+        | Assignment::Expr(value)
+        | Assignment::Increment(value)
+        | Assignment::Decrement(value) => visitor.visit_value(value),
+        Assignment::Column(other) => visitor.visit_column(other),
+        Assignment::Default | Assignment::Null | Assignment::Now => {}
+    }
+}
+
+pub fn walk_where_clause<V: Visitor + ?Sized>(visitor: &mut V, where_clause: &WhereClause) {
+    match where_clause {
+        WhereClause::And(clauses) | WhereClause::Or(clauses) => {
+            for clause in clauses {
+                visitor.visit_where_clause(clause);
+            }
+        }
+        WhereClause::Not(clause) => visitor.visit_where_clause(clause),
+        WhereClause::Condition(condition) => visitor.visit_where_condition(condition),
+    }
+}
+
+pub fn walk_where_condition<V: Visitor + ?Sized>(visitor: &mut V, condition: &WhereCondition) {
+    match condition {
+        WhereCondition::Equals(field, value)
+        | WhereCondition::NotEquals(field, value)
+        | WhereCondition::GreaterThan(field, value)
+        | WhereCondition::GreaterThanOrEquals(field, value)
+        | WhereCondition::LessThan(field, value)
+        | WhereCondition::LessThanOrEquals(field, value)
+        | WhereCondition::Like(field, value)
+        | WhereCondition::NotLike(field, value) => {
+            visitor.visit_column(field);
+            visitor.visit_value(value);
+        }
+        WhereCondition::In(field, values) | WhereCondition::NotIn(field, values) => {
+            visitor.visit_column(field);
+            for value in values {
+                visitor.visit_value(value);
+            }
+        }
+        WhereCondition::Between(field, min, max) | WhereCondition::NotBetween(field, min, max) => {
+            visitor.visit_column(field);
+            visitor.visit_value(min);
+            visitor.visit_value(max);
+        }
+        WhereCondition::IsNull(field) | WhereCondition::IsNotNull(field) => {
+            visitor.visit_column(field)
+        }
+        WhereCondition::RawCondition(condition) => visitor.visit_value(condition),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::create_table::ColumnType;
+
+    #[derive(Default)]
+    struct Names {
+        tables: Vec<&'static str>,
+        columns: Vec<&'static str>,
+        values: Vec<String>,
+    }
+
+    impl Visitor for Names {
+        fn visit_table(&mut self, table: &'static str) {
+            self.tables.push(table);
+        }
+
+        fn visit_column(&mut self, column: &'static str) {
+            self.columns.push(column);
+        }
+
+        fn visit_value(&mut self, value: &str) {
+            self.values.push(value.to_string());
+        }
+    }
+
+    #[test]
+    fn test_visit_select() {
+        let mut select = SelectQuery::new("users");
+        select
+            .add_columns(&["id", "name"])
+            .where_clause(
+                WhereClause::equals("age", 18)
+                    .and(!WhereClause::in_("role", &vec!["admin", "owner"])),
+            )
+            .order_by("created_at");
+        let mut names = Names::default();
+        names.visit_statement(&Statement::Select(select));
+        assert_eq!(names.tables, vec!["users"]);
+        assert_eq!(
+            names.columns,
+            vec!["id", "name", "age", "role", "created_at"]
+        );
+        assert_eq!(names.values, vec!["18", "'admin'", "'owner'"]);
+    }
+
+    #[test]
+    fn test_visit_update() {
+        let mut update = UpdateQuery::new("users");
+        update
+            .set_field("name", &"Jack")
+            .increment("logins", 1)
+            .join("teams", "teams.id = users.team_id".to_string())
+            .add_where_clause(WhereClause::equals("teams.name", "core"))
+            .versioned("version", 3);
+        let mut names = Names::default();
+        names.visit_update(&update);
+        assert_eq!(names.tables, vec!["users", "teams"]);
+        assert_eq!(
+            names.columns,
+            vec!["name", "logins", "teams.name", "version"]
+        );
+        assert_eq!(
+            names.values,
+            vec!["teams.id = users.team_id", "'Jack'", "1", "'core'"]
+        );
+    }
+
+    /// Counts conditions without descending into negated clauses.
+    struct Positive(usize);
+
+    impl Visitor for Positive {
+        fn visit_where_clause(&mut self, where_clause: &WhereClause) {
+            if !matches!(where_clause, WhereClause::Not(_)) {
+                walk_where_clause(self, where_clause);
+            }
+        }
+
+        fn visit_where_condition(&mut self, _condition: &WhereCondition) {
+            self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn test_visit_override() {
+        let delete = DeleteQuery::new("users").where_clause(
+            WhereClause::equals("id", 1)
+                .or(!WhereClause::is_null("deleted_at"))
+                .or_equals("id", 2),
+        );
+        let mut positive = Positive(0);
+        positive.visit_delete(&delete);
+        assert_eq!(positive.0, 2);
+    }
+
+    #[test]
+    fn test_visit_create_and_alter_table() {
+        let create_table = CreateTableQuery::new("users")
+            .column(ColumnDef::new("id", ColumnType::BigInteger).primary_key())
+            .column(
+                ColumnDef::new("team_id", ColumnType::BigInteger).references("teams", "id", None),
+            )
+            .constraint("age_check", TableConstraint::Check("age >= 0".to_string()));
+        let mut names = Names::default();
+        names.visit_create_table(&create_table);
+        assert_eq!(names.tables, vec!["users", "teams"]);
+        assert_eq!(names.columns, vec!["id", "team_id", "team_id", "id"]);
+        assert_eq!(names.values, vec!["age >= 0"]);
+
+        let alter_table = AlterTableQuery::new("users")
+            .rename_column("team_id", "group_id")
+            .add_constraint(
+                "users_group",
+                TableConstraint::ForeignKey(ForeignKey::new(&["group_id"], "groups", &["id"])),
+            )
+            .rename_to("members");
+        let mut names = Names::default();
+        names.visit_alter_table(&alter_table);
+        assert_eq!(names.tables, vec!["users", "groups", "members"]);
+        assert_eq!(names.columns, vec!["team_id", "group_id", "group_id", "id"]);
+    }
+}
@@ -1,6 +1,9 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Not;
+
 use crate::{repr::ToSqlRepr, Result, SqlBuilder};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum WhereCondition {
     Equals(&'static str, String),
     NotEquals(&'static str, String),
@@ -19,10 +22,14 @@ pub enum WhereCondition {
     RawCondition(String),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum WhereClause {
-    And(Box<WhereClause>, Box<WhereClause>),
-    Or(Box<WhereClause>, Box<WhereClause>),
+    /// All of the clauses, an empty `And` matches every row.
+    And(Vec<WhereClause>),
+    /// Any of the clauses, an empty `Or` matches no row.
+    Or(Vec<WhereClause>),
+    /// The negation of the clause.
+    Not(Box<WhereClause>),
     Condition(WhereCondition),
 }
 
@@ -62,10 +69,11 @@ impl WhereClause {
         WhereClause::Condition(WhereCondition::NotLike(field, value.to_sql_repr()))
     }
 
+    #[allow(clippy::ptr_arg)]
     pub fn in_<T: ToSqlRepr>(field: &'static str, values: &Vec<T>) -> WhereClause {
         WhereClause::Condition(WhereCondition::In(
             field,
-            values.into_iter().map(|v| v.to_sql_repr()).collect(),
+            values.iter().map(|v| v.to_sql_repr()).collect(),
         ))
     }
 
@@ -108,42 +116,42 @@ impl WhereClause {
         Self::like(field, format!("%{}%", value))
     }
 
+    /// `self AND other`, flattened into a single `And` so long chains stay shallow.
     pub fn and(self, other: WhereClause) -> WhereClause {
-        WhereClause::And(Box::new(self), Box::new(other))
+        let mut clauses = match self {
+            WhereClause::And(clauses) => clauses,
+            clause => vec![clause],
+        };
+        match other {
+            WhereClause::And(others) => clauses.extend(others),
+            other => clauses.push(other),
+        }
+        WhereClause::And(clauses)
     }
 
+    /// `self OR other`, flattened into a single `Or` so long chains stay shallow.
     pub fn or(self, other: WhereClause) -> WhereClause {
-        WhereClause::Or(Box::new(self), Box::new(other))
+        let mut clauses = match self {
+            WhereClause::Or(clauses) => clauses,
+            clause => vec![clause],
+        };
+        match other {
+            WhereClause::Or(others) => clauses.extend(others),
+            other => clauses.push(other),
+        }
+        WhereClause::Or(clauses)
     }
 
     pub fn and_equals<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::Equals(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.and(Self::equals(field, value))
     }
 
     pub fn and_not_equals<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::NotEquals(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.and(Self::not_equals(field, value))
     }
 
     pub fn and_greater_than<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::GreaterThan(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.and(Self::greater_than(field, value))
     }
 
     pub fn and_greater_than_or_equals<T: ToSqlRepr>(
@@ -151,23 +159,11 @@ impl WhereClause {
         field: &'static str,
         value: T,
     ) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::GreaterThanOrEquals(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.and(Self::greater_than_or_equals(field, value))
     }
 
     pub fn and_less_than<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::LessThan(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.and(Self::less_than(field, value))
     }
 
     pub fn and_less_than_or_equals<T: ToSqlRepr>(
@@ -175,132 +171,59 @@ impl WhereClause {
         field: &'static str,
         value: T,
     ) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::LessThanOrEquals(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.and(Self::less_than_or_equals(field, value))
     }
 
     pub fn and_like<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::Like(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.and(Self::like(field, value))
     }
 
     pub fn and_not_like<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::NotLike(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.and(Self::not_like(field, value))
     }
 
     pub fn and_in<T: ToSqlRepr>(self, field: &'static str, values: Vec<T>) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::In(
-                field,
-                values.into_iter().map(|v| v.to_sql_repr()).collect(),
-            ))),
-        )
+        self.and(Self::in_(field, &values))
     }
 
     pub fn and_not_in<T: ToSqlRepr>(self, field: &'static str, values: Vec<T>) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::NotIn(
-                field,
-                values.into_iter().map(|v| v.to_sql_repr()).collect(),
-            ))),
-        )
+        self.and(Self::not_in(field, values))
     }
 
     pub fn and_between<T: ToSqlRepr>(self, field: &'static str, min: T, max: T) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::Between(
-                field,
-                min.to_sql_repr(),
-                max.to_sql_repr(),
-            ))),
-        )
+        self.and(Self::between(field, min, max))
     }
 
     pub fn and_not_between<T: ToSqlRepr>(self, field: &'static str, min: T, max: T) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::NotBetween(
-                field,
-                min.to_sql_repr(),
-                max.to_sql_repr(),
-            ))),
-        )
+        self.and(Self::not_between(field, min, max))
     }
 
     pub fn and_is_null(self, field: &'static str) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::IsNull(field))),
-        )
+        self.and(Self::is_null(field))
     }
 
     pub fn and_is_not_null(self, field: &'static str) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::IsNotNull(field))),
-        )
+        self.and(Self::is_not_null(field))
     }
 
     pub fn and_raw_condition(self, condition: String) -> WhereClause {
-        WhereClause::And(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::RawCondition(
-                condition,
-            ))),
-        )
+        self.and(Self::raw_condition(condition))
     }
 
     pub fn and_contains(self, field: &'static str, value: String) -> WhereClause {
-        WhereClause::And(Box::new(self), Box::new(Self::contains(field, value)))
+        self.and(Self::contains(field, value))
     }
 
     pub fn or_equals<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::Equals(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.or(Self::equals(field, value))
     }
 
     pub fn or_not_equals<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::NotEquals(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.or(Self::not_equals(field, value))
     }
 
     pub fn or_greater_than<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::GreaterThan(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.or(Self::greater_than(field, value))
     }
 
     pub fn or_greater_than_or_equals<T: ToSqlRepr>(
@@ -308,23 +231,11 @@ impl WhereClause {
         field: &'static str,
         value: T,
     ) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::GreaterThanOrEquals(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.or(Self::greater_than_or_equals(field, value))
     }
 
     pub fn or_less_than<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::LessThan(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.or(Self::less_than(field, value))
     }
 
     pub fn or_less_than_or_equals<T: ToSqlRepr>(
@@ -332,127 +243,437 @@ impl WhereClause {
         field: &'static str,
         value: T,
     ) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::LessThanOrEquals(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.or(Self::less_than_or_equals(field, value))
     }
 
     pub fn or_like<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::Like(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.or(Self::like(field, value))
     }
 
     pub fn or_not_like<T: ToSqlRepr>(self, field: &'static str, value: T) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::NotLike(
-                field,
-                value.to_sql_repr(),
-            ))),
-        )
+        self.or(Self::not_like(field, value))
     }
 
     pub fn or_in<T: ToSqlRepr>(self, field: &'static str, values: Vec<T>) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::In(
-                field,
-                values.into_iter().map(|v| v.to_sql_repr()).collect(),
-            ))),
-        )
+        self.or(Self::in_(field, &values))
     }
 
     pub fn or_not_in<T: ToSqlRepr>(self, field: &'static str, values: Vec<T>) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::NotIn(
-                field,
-                values.into_iter().map(|v| v.to_sql_repr()).collect(),
-            ))),
-        )
+        self.or(Self::not_in(field, values))
     }
 
     pub fn or_between<T: ToSqlRepr>(self, field: &'static str, min: T, max: T) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::Between(
-                field,
-                min.to_sql_repr(),
-                max.to_sql_repr(),
-            ))),
-        )
+        self.or(Self::between(field, min, max))
     }
 
     pub fn or_not_between<T: ToSqlRepr>(self, field: &'static str, min: T, max: T) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::NotBetween(
-                field,
-                min.to_sql_repr(),
-                max.to_sql_repr(),
-            ))),
-        )
+        self.or(Self::not_between(field, min, max))
     }
 
     pub fn or_is_null(self, field: &'static str) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::IsNull(field))),
-        )
+        self.or(Self::is_null(field))
     }
 
     pub fn or_is_not_null(self, field: &'static str) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::IsNotNull(field))),
-        )
+        self.or(Self::is_not_null(field))
     }
 
     pub fn or_raw_condition(self, condition: String) -> WhereClause {
-        WhereClause::Or(
-            Box::new(self),
-            Box::new(WhereClause::Condition(WhereCondition::RawCondition(
-                condition,
-            ))),
-        )
+        self.or(Self::raw_condition(condition))
     }
 
     pub fn or_contains(self, field: &'static str, value: String) -> WhereClause {
-        WhereClause::Or(Box::new(self), Box::new(Self::contains(field, value)))
+        self.or(Self::contains(field, value))
     }
-}
 
-impl SqlBuilder for WhereClause {
-    fn build(&self) -> Result<String> {
+    /// Pushes negations down to the conditions, e.g. `NOT (a = 1 OR b IS NULL)`
+    /// becomes `a != 1 AND b IS NOT NULL`, so the generated sql stays index friendly.
+    /// Only negated raw conditions keep their `NOT`.
+    pub fn normalize(self) -> WhereClause {
+        match self {
+            WhereClause::And(clauses) => clauses
+                .into_iter()
+                .map(WhereClause::normalize)
+                .fold(WhereClause::And(Vec::new()), WhereClause::and),
+            WhereClause::Or(clauses) => clauses
+                .into_iter()
+                .map(WhereClause::normalize)
+                .fold(WhereClause::Or(Vec::new()), WhereClause::or),
+            WhereClause::Not(clause) => clause.negate(),
+            WhereClause::Condition(condition) => WhereClause::Condition(condition),
+        }
+    }
+
+    /// The normalized negation of the clause, by De Morgan's laws.
+    fn negate(self) -> WhereClause {
+        match self {
+            WhereClause::And(clauses) => clauses
+                .into_iter()
+                .map(WhereClause::negate)
+                .fold(WhereClause::Or(Vec::new()), WhereClause::or),
+            WhereClause::Or(clauses) => clauses
+                .into_iter()
+                .map(WhereClause::negate)
+                .fold(WhereClause::And(Vec::new()), WhereClause::and),
+            WhereClause::Not(clause) => clause.normalize(),
+            WhereClause::Condition(condition) => match condition.negate() {
+                Some(negated) => WhereClause::Condition(negated),
+                None => !WhereClause::Condition(condition),
+            },
+        }
+    }
+
+    /// Folds constants and removes redundancy: nested groups are flattened, duplicate
+    /// conditions dropped, `a = 1 OR a = 2` merged into `a IN (1, 2)`, an empty `IN`
+    /// folded to false and an empty `NOT IN` to true. True and false come out as an
+    /// empty `And` and an empty `Or`.
+    pub fn simplify(self) -> WhereClause {
         match self {
-            WhereClause::Condition(condition) => condition.build(),
-            WhereClause::And(left, right) => {
-                let left = match left.as_ref() {
-                    WhereClause::Or(_, _) => {
-                        format!("({})", left.build()?)
+            WhereClause::And(clauses) => simplify_group(clauses, true),
+            WhereClause::Or(clauses) => simplify_group(clauses, false),
+            WhereClause::Not(clause) => match clause.simplify() {
+                WhereClause::And(clauses) if clauses.is_empty() => WhereClause::Or(clauses),
+                WhereClause::Or(clauses) if clauses.is_empty() => WhereClause::And(clauses),
+                clause => !clause,
+            },
+            WhereClause::Condition(condition) => match condition {
+                WhereCondition::In(_, values) if values.is_empty() => WhereClause::Or(Vec::new()),
+                WhereCondition::NotIn(_, values) if values.is_empty() => {
+                    WhereClause::And(Vec::new())
+                }
+                WhereCondition::In(field, values) => {
+                    let mut values = dedup(values);
+                    match values.len() {
+                        1 => {
+                            WhereClause::Condition(WhereCondition::Equals(field, values.remove(0)))
+                        }
+                        _ => WhereClause::Condition(WhereCondition::In(field, values)),
                     }
-                    _ => left.build()?,
+                }
+                WhereCondition::NotIn(field, values) => {
+                    WhereClause::Condition(WhereCondition::NotIn(field, dedup(values)))
+                }
+                WhereCondition::RawCondition(condition) => match raw_constant(&condition) {
+                    Some(true) => WhereClause::And(Vec::new()),
+                    Some(false) => WhereClause::Or(Vec::new()),
+                    None => WhereClause::raw_condition(condition),
+                },
+                condition => WhereClause::Condition(condition),
+            },
+        }
+    }
+
+    /// Whether the clause obviously matches every row, e.g. `1=1`, `TRUE`, `x = x`
+    /// or an `OR` with such a branch.
+    pub fn is_tautology(&self) -> bool {
+        match self {
+            WhereClause::And(clauses) => clauses.iter().all(WhereClause::is_tautology),
+            WhereClause::Or(clauses) => clauses.iter().any(WhereClause::is_tautology),
+            WhereClause::Not(_) => false,
+            WhereClause::Condition(condition) => condition.is_tautology(),
+        }
+    }
+}
+
+impl WhereCondition {
+    /// The opposite condition, which also holds under sql's NULL semantics,
+    /// e.g. both `a > 1` and `a <= 1` are unknown when `a` is NULL.
+    /// Raw conditions are opaque and have none.
+    pub fn negate(&self) -> Option<WhereCondition> {
+        Some(match self.clone() {
+            WhereCondition::Equals(field, value) => WhereCondition::NotEquals(field, value),
+            WhereCondition::NotEquals(field, value) => WhereCondition::Equals(field, value),
+            WhereCondition::GreaterThan(field, value) => {
+                WhereCondition::LessThanOrEquals(field, value)
+            }
+            WhereCondition::GreaterThanOrEquals(field, value) => {
+                WhereCondition::LessThan(field, value)
+            }
+            WhereCondition::LessThan(field, value) => {
+                WhereCondition::GreaterThanOrEquals(field, value)
+            }
+            WhereCondition::LessThanOrEquals(field, value) => {
+                WhereCondition::GreaterThan(field, value)
+            }
+            WhereCondition::Like(field, value) => WhereCondition::NotLike(field, value),
+            WhereCondition::NotLike(field, value) => WhereCondition::Like(field, value),
+            WhereCondition::In(field, values) => WhereCondition::NotIn(field, values),
+            WhereCondition::NotIn(field, values) => WhereCondition::In(field, values),
+            WhereCondition::Between(field, min, max) => WhereCondition::NotBetween(field, min, max),
+            WhereCondition::NotBetween(field, min, max) => WhereCondition::Between(field, min, max),
+            WhereCondition::IsNull(field) => WhereCondition::IsNotNull(field),
+            WhereCondition::IsNotNull(field) => WhereCondition::IsNull(field),
+            WhereCondition::RawCondition(_) => return None,
+        })
+    }
+
+    pub fn is_tautology(&self) -> bool {
+        match self {
+            WhereCondition::Equals(field, value)
+            | WhereCondition::GreaterThanOrEquals(field, value)
+            | WhereCondition::LessThanOrEquals(field, value) => field.trim() == value.trim(),
+            WhereCondition::NotIn(_, values) => values.is_empty(),
+            WhereCondition::RawCondition(condition) => is_tautology_raw(condition),
+            _ => false,
+        }
+    }
+}
+
+fn simplify_group(clauses: Vec<WhereClause>, and: bool) -> WhereClause {
+    // an empty `And` is true and an empty `Or` false, a nested group of the same
+    // kind is flattened so its identity disappears, the other one absorbs the group
+    let absorbing = match and {
+        true => WhereClause::Or(Vec::new()),
+        false => WhereClause::And(Vec::new()),
+    };
+    let mut seen = HashSet::new();
+    let mut simplified = Vec::new();
+    for clause in clauses.into_iter().map(WhereClause::simplify) {
+        let flattened = match clause {
+            WhereClause::And(clauses) if and => clauses,
+            WhereClause::Or(clauses) if !and => clauses,
+            clause => vec![clause],
+        };
+        for clause in flattened {
+            if clause == absorbing {
+                return absorbing;
+            }
+            if seen.insert(clause.clone()) {
+                simplified.push(clause);
+            }
+        }
+    }
+    if !and {
+        simplified = merge_in(simplified);
+    }
+    match (simplified.len(), and) {
+        (1, _) => simplified.remove(0),
+        (_, true) => WhereClause::And(simplified),
+        (_, false) => WhereClause::Or(simplified),
+    }
+}
+
+/// Merges the `=` and `IN` conditions on the same field of an `Or` into a single
+/// `IN`, in place of the first one.
+fn merge_in(clauses: Vec<WhereClause>) -> Vec<WhereClause> {
+    let mut merged: HashMap<&'static str, Vec<String>> = HashMap::new();
+    let mut counts: HashMap<&'static str, usize> = HashMap::new();
+    for clause in &clauses {
+        if let Some((field, values)) = in_values(clause) {
+            *counts.entry(field).or_default() += 1;
+            merged.entry(field).or_default().extend_from_slice(values);
+        }
+    }
+    clauses
+        .into_iter()
+        .filter_map(|clause| match in_values(&clause) {
+            Some((field, _)) if counts[field] > 1 => merged
+                .remove(field)
+                .map(|values| WhereClause::Condition(WhereCondition::In(field, dedup(values)))),
+            _ => Some(clause),
+        })
+        .collect()
+}
+
+fn in_values(clause: &WhereClause) -> Option<(&'static str, &[String])> {
+    match clause {
+        WhereClause::Condition(WhereCondition::Equals(field, value)) => {
+            Some((field, std::slice::from_ref(value)))
+        }
+        WhereClause::Condition(WhereCondition::In(field, values)) => Some((field, values)),
+        _ => None,
+    }
+}
+
+fn dedup(values: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    values
+        .into_iter()
+        .filter(|value| seen.insert(value.clone()))
+        .collect()
+}
+
+/// The value of a raw condition that is a constant such as `1=1` or `FALSE`.
+fn raw_constant(condition: &str) -> Option<bool> {
+    let mut condition = condition.trim();
+    while is_wrapped_in_parentheses(condition) {
+        condition = condition[1..condition.len() - 1].trim();
+    }
+    let condition = condition.replace(' ', "").to_uppercase();
+    match condition.as_str() {
+        "TRUE" | "1" | "1=1" | "NOTFALSE" => Some(true),
+        "FALSE" | "0" | "1=0" | "NOTTRUE" => Some(false),
+        _ => None,
+    }
+}
+
+fn is_tautology_raw(condition: &str) -> bool {
+    let mut condition = condition.trim();
+    while is_wrapped_in_parentheses(condition) {
+        condition = condition[1..condition.len() - 1].trim();
+    }
+    let upper = condition.to_uppercase();
+    if upper == "TRUE" || upper == "1" || upper == "NOT FALSE" {
+        return true;
+    }
+    if let Some((left, right)) = split_top_level(condition, " OR ") {
+        return is_tautology_raw(left) || is_tautology_raw(right);
+    }
+    if let Some((left, right)) = split_top_level(condition, " AND ") {
+        return is_tautology_raw(left) && is_tautology_raw(right);
+    }
+    for operator in [">=", "<=", "="] {
+        if let Some((left, right)) = condition.split_once(operator) {
+            let left = left.trim_end_matches(['!', '<', '>']);
+            return left.len() + operator.len() + right.len() == condition.len()
+                && left.trim() == right.trim();
+        }
+    }
+    false
+}
+
+fn is_wrapped_in_parentheses(condition: &str) -> bool {
+    if !condition.starts_with('(') || !condition.ends_with(')') {
+        return false;
+    }
+    let mut depth = 0;
+    for (i, c) in condition.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return i == condition.len() - 1;
+        }
+    }
+    false
+}
+
+/// Splits on the first `separator` (case insensitive) outside of parentheses and quotes.
+fn split_top_level<'a>(condition: &'a str, separator: &str) -> Option<(&'a str, &'a str)> {
+    let upper = condition.to_uppercase();
+    let mut depth = 0;
+    let mut quoted = false;
+    for (i, c) in upper.char_indices() {
+        match c {
+            '\'' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            _ if depth == 0 && !quoted && upper[i..].starts_with(separator) => {
+                return Some((&condition[..i], &condition[i + separator.len()..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Prefixed to statements built after `allow_full_table()` so the opt-in shows up in query logs.
+pub const FULL_TABLE_AUDIT_COMMENT: &str = "/* allow_full_table */";
+
+/// ` WHERE ...`, or nothing when there is no where clause.
+pub(crate) fn where_sql(where_clause: Option<&WhereClause>) -> Result<String> {
+    match where_clause {
+        Some(where_clause) => Ok(format!(" WHERE {}", where_clause.build()?)),
+        None => Ok(String::new()),
+    }
+}
+
+/// `!clause`, or `WhereClause::not(clause)` with `std::ops::Not` in scope.
+impl Not for WhereClause {
+    type Output = WhereClause;
+
+    fn not(self) -> WhereClause {
+        WhereClause::Not(Box::new(self))
+    }
+}
+
+/// How tightly a rendered clause binds, NOT binds tighter than AND, AND than OR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Or,
+    And,
+    Not,
+    Condition,
+}
+
+impl WhereClause {
+    fn precedence(&self) -> Precedence {
+        match self {
+            WhereClause::And(clauses) | WhereClause::Or(clauses) if clauses.is_empty() => {
+                Precedence::Condition
+            }
+            WhereClause::And(_) => Precedence::And,
+            WhereClause::Or(_) => Precedence::Or,
+            WhereClause::Not(_) => Precedence::Not,
+            WhereClause::Condition(WhereCondition::RawCondition(condition)) => {
+                raw_precedence(condition)
+            }
+            WhereClause::Condition(_) => Precedence::Condition,
+        }
+    }
+
+    /// Renders into `sql`, so the whole clause is built in a single buffer.
+    /// The clause is parenthesized only when it binds looser than `context`.
+    fn write_sql(&self, sql: &mut String, context: Precedence) -> Result<()> {
+        if let WhereClause::And(clauses) | WhereClause::Or(clauses) = self {
+            if let [clause] = clauses.as_slice() {
+                return clause.write_sql(sql, context);
+            }
+        }
+        let grouped = self.precedence() < context;
+        if grouped {
+            sql.push('(');
+        }
+        match self {
+            WhereClause::And(clauses) | WhereClause::Or(clauses) => {
+                let (separator, empty) = match self {
+                    WhereClause::And(_) => (" AND ", "1 = 1"),
+                    _ => (" OR ", "1 = 0"),
                 };
-                let right = match right.as_ref() {
-                    WhereClause::Or(_, _) => {
-                        format!("({})", right.build()?)
+                if clauses.is_empty() {
+                    sql.push_str(empty);
+                }
+                for (i, clause) in clauses.iter().enumerate() {
+                    if i > 0 {
+                        sql.push_str(separator);
                     }
-                    _ => right.build()?,
-                };
-
-                Ok(format!("{} AND {}", left, right))
+                    clause.write_sql(sql, self.precedence())?;
+                }
+            }
+            WhereClause::Not(clause) => {
+                sql.push_str("NOT ");
+                clause.write_sql(sql, Precedence::Not)?;
             }
-            WhereClause::Or(left, right) => Ok(format!("{} OR {}", left.build()?, right.build()?)),
+            WhereClause::Condition(condition) => sql.push_str(&condition.build()?),
+        }
+        if grouped {
+            sql.push(')');
         }
+        Ok(())
+    }
+}
+
+/// Raw conditions are opaque, look for top level operators to decide how they bind.
+fn raw_precedence(condition: &str) -> Precedence {
+    if split_top_level(condition, " OR ").is_some() {
+        Precedence::Or
+    } else if split_top_level(condition, " AND ").is_some() {
+        // also matches `x BETWEEN 1 AND 2`, which at worst gets redundant parentheses
+        Precedence::And
+    } else if condition.trim_start().to_uppercase().starts_with("NOT ") {
+        Precedence::Not
+    } else {
+        Precedence::Condition
+    }
+}
+
+impl SqlBuilder for WhereClause {
+    fn build(&self) -> Result<String> {
+        let mut sql = String::new();
+        self.write_sql(&mut sql, Precedence::Or)?;
+        Ok(sql)
     }
 }
 
@@ -467,6 +688,9 @@ impl SqlBuilder for WhereCondition {
             WhereCondition::LessThanOrEquals(field, value) => format!("{} <= {}", field, value),
             WhereCondition::Like(field, value) => format!("{} LIKE {}", field, value),
             WhereCondition::NotLike(field, value) => format!("{} NOT LIKE {}", field, value),
+            // `IN ()` is a syntax error, an empty list matches no row
+            WhereCondition::In(_, values) if values.is_empty() => "1 = 0".to_string(),
+            WhereCondition::NotIn(_, values) if values.is_empty() => "1 = 1".to_string(),
             WhereCondition::In(field, values) => format!("{} IN ({})", field, values.join(", ")),
             WhereCondition::NotIn(field, values) => {
                 format!("{} NOT IN ({})", field, values.join(", "))
@@ -567,6 +791,122 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_where_clause_flatten() {
+        let where_clause =
+            WhereClause::equals("a", 1).or(WhereClause::equals("b", 2).or_equals("c", 3));
+        assert_eq!(where_clause.build().unwrap(), "a = 1 OR b = 2 OR c = 3");
+        match where_clause {
+            WhereClause::Or(clauses) => assert_eq!(clauses.len(), 3),
+            _ => unreachable!(),
+        }
+        assert_eq!(WhereClause::And(vec![]).build().unwrap(), "1 = 1");
+        assert_eq!(WhereClause::Or(vec![]).build().unwrap(), "1 = 0");
+    }
+
+    #[test]
+    fn test_where_clause_long_chain() {
+        let where_clause = (1..100_000).fold(WhereClause::equals("id", 0), |clause, i| {
+            clause.and_equals("id", i)
+        });
+        let sql = where_clause.build().unwrap();
+        assert!(sql.starts_with("id = 0 AND id = 1 AND "));
+        assert!(sql.ends_with(" AND id = 99999"));
+        match where_clause {
+            WhereClause::And(clauses) => assert_eq!(clauses.len(), 100_000),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_where_clause_parentheses() {
+        let a = || WhereClause::equals("a", 1);
+        let b = || WhereClause::equals("b", 2);
+        let not = WhereClause::not;
+        assert_eq!(not(a()).and(b()).build().unwrap(), "NOT a = 1 AND b = 2");
+        assert_eq!(not(a().and(b())).build().unwrap(), "NOT (a = 1 AND b = 2)");
+        assert_eq!(
+            not(not(a().or(b()))).build().unwrap(),
+            "NOT NOT (a = 1 OR b = 2)"
+        );
+        assert_eq!(
+            WhereClause::And(vec![WhereClause::Or(vec![a()]), b()])
+                .build()
+                .unwrap(),
+            "a = 1 AND b = 2"
+        );
+        assert_eq!(
+            WhereClause::raw_condition("x = 1 OR y = 2".to_string())
+                .and(a())
+                .build()
+                .unwrap(),
+            "(x = 1 OR y = 2) AND a = 1"
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let where_clause = WhereClause::not(
+            WhereClause::equals("a", 1)
+                .or_is_null("b")
+                .or(WhereClause::not(WhereClause::in_("c", &vec![1, 2]))),
+        );
+        assert_eq!(
+            where_clause.normalize().build().unwrap(),
+            "a != 1 AND b IS NOT NULL AND c IN (1, 2)"
+        );
+        let where_clause = WhereClause::not(
+            WhereClause::greater_than("a", 1)
+                .and_not_like("b", "x%")
+                .and(WhereClause::between("c", 1, 2).or_less_than_or_equals("d", 3)),
+        );
+        assert_eq!(
+            where_clause.normalize().build().unwrap(),
+            "a <= 1 OR b LIKE 'x%' OR c NOT BETWEEN 1 AND 2 AND d > 3"
+        );
+        let where_clause = WhereClause::not(WhereClause::not(
+            WhereClause::raw_condition("EXISTS (SELECT 1)".to_string()).and_equals("a", 1),
+        ));
+        assert_eq!(
+            where_clause.normalize().build().unwrap(),
+            "EXISTS (SELECT 1) AND a = 1"
+        );
+        let where_clause =
+            WhereClause::not(WhereClause::raw_condition("EXISTS (SELECT 1)".to_string()));
+        assert_eq!(where_clause.clone().normalize(), where_clause);
+    }
+
+    #[test]
+    fn test_simplify() {
+        let where_clause = WhereClause::equals("a", 1)
+            .or_equals("b", 2)
+            .or_in("a", vec![2, 1, 3])
+            .or_equals("a", 4)
+            .or_equals("b", 2);
+        assert_eq!(
+            where_clause.simplify().build().unwrap(),
+            "a IN (1, 2, 3, 4) OR b = 2"
+        );
+        let where_clause = WhereClause::raw_condition("1=1".to_string())
+            .and(WhereClause::And(vec![
+                WhereClause::equals("a", 1),
+                WhereClause::Or(vec![WhereClause::equals("b", 2)]),
+            ]))
+            .and_not_in::<i32>("c", vec![])
+            .and_equals("a", 1);
+        assert_eq!(where_clause.simplify().build().unwrap(), "a = 1 AND b = 2");
+        let where_clause = WhereClause::equals("a", 1).and_in::<i32>("b", vec![]);
+        assert_eq!(where_clause.clone().build().unwrap(), "a = 1 AND 1 = 0");
+        assert_eq!(where_clause.simplify(), WhereClause::Or(vec![]));
+        let where_clause =
+            WhereClause::equals("a", 1).or(!WhereClause::raw_condition("FALSE".to_string()));
+        assert_eq!(where_clause.simplify(), WhereClause::And(vec![]));
+        assert_eq!(
+            WhereClause::in_("a", &vec![1, 1]).simplify(),
+            WhereClause::equals("a", 1)
+        );
+    }
+
     #[test]
     fn test_in_struct() {
         struct User {
@@ -621,4 +961,27 @@ mod test {
             "name = 'Jack' OR name LIKE '%Jack%'"
         );
     }
+
+    #[test]
+    fn test_is_tautology() {
+        assert!(WhereClause::raw_condition("1=1".to_string()).is_tautology());
+        assert!(WhereClause::raw_condition(" ( 1 = 1 ) ".to_string()).is_tautology());
+        assert!(WhereClause::raw_condition("true".to_string()).is_tautology());
+        assert!(WhereClause::raw_condition("id = id".to_string()).is_tautology());
+        assert!(WhereClause::raw_condition("id = 1 or 'a' = 'a'".to_string()).is_tautology());
+        assert!(WhereClause::equals("id", 1)
+            .or_raw_condition("1=1".to_string())
+            .is_tautology());
+        assert!(WhereClause::raw_condition("1=1".to_string())
+            .and_raw_condition("TRUE".to_string())
+            .is_tautology());
+        assert!(!WhereClause::raw_condition("1=1".to_string())
+            .and_equals("id", 1)
+            .is_tautology());
+        assert!(!WhereClause::raw_condition("id != id".to_string()).is_tautology());
+        assert!(!WhereClause::raw_condition("id = 1".to_string()).is_tautology());
+        assert!(!WhereClause::raw_condition("(a = 1) OR (b = 2)".to_string()).is_tautology());
+        assert!(WhereClause::raw_condition("(a = 1) OR (2 = 2)".to_string()).is_tautology());
+        assert!(!WhereClause::equals("id", 1).is_tautology());
+    }
 }